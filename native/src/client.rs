@@ -0,0 +1,72 @@
+//! Helpers for fetching and decoding program accounts over RPC, only available off-chain with the
+//! `client` feature
+
+use {
+	crate::state::{Account, VestingSchedule},
+	solana_program::{
+		clock::Clock, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey, sysvar,
+	},
+	solana_rpc_client::rpc_client::RpcClient,
+	solana_rpc_client_api::client_error::Error as RpcError,
+	thiserror::Error,
+};
+
+/// Errors returned by the client helpers
+#[derive(Debug, Error)]
+pub enum ClientError {
+	/// The RPC request failed
+	#[error(transparent)]
+	Rpc(Box<RpcError>),
+	/// No account exists at the address
+	#[error("Account {0} not found")]
+	AccountNotFound(Pubkey),
+	/// The account data could not be decoded
+	#[error(transparent)]
+	Program(#[from] ProgramError),
+}
+
+impl From<RpcError> for ClientError {
+	fn from(e: RpcError) -> Self {
+		Self::Rpc(Box::new(e))
+	}
+}
+
+/// Fetches and unpacks the vesting schedule at `address`
+pub fn get_vesting_schedule(
+	rpc: &RpcClient,
+	address: &Pubkey,
+) -> Result<VestingSchedule, ClientError> {
+	Ok(VestingSchedule::unpack(&rpc.get_account_data(address)?)?)
+}
+
+/// Fetches and unpacks the vesting account at `address`
+pub fn get_account(rpc: &RpcClient, address: &Pubkey) -> Result<Account, ClientError> {
+	Ok(Account::unpack(&rpc.get_account_data(address)?)?)
+}
+
+/// Amount the vesting account at `account_address` can claim at the cluster's current clock. The
+/// schedule, account and clock sysvar are fetched in a single request so they reflect the same
+/// slot.
+pub fn get_claimable_now(
+	rpc: &RpcClient,
+	schedule_address: &Pubkey,
+	account_address: &Pubkey,
+) -> Result<u64, ClientError> {
+	let addresses = [*schedule_address, *account_address, sysvar::clock::id()];
+	let mut accounts = rpc.get_multiple_accounts(&addresses)?.into_iter();
+	let mut next_data = |address: &Pubkey| {
+		accounts
+			.next()
+			.flatten()
+			.map(|account| account.data)
+			.ok_or(ClientError::AccountNotFound(*address))
+	};
+	let schedule = VestingSchedule::unpack(&next_data(schedule_address)?)?;
+	let account = Account::unpack(&next_data(account_address)?)?;
+	let clock: Clock = bincode::deserialize(&next_data(&sysvar::clock::id())?)
+		.map_err(|_| ProgramError::InvalidAccountData)?;
+	if account.vesting_schedule != *schedule_address {
+		return Err(ProgramError::InvalidAccountData.into());
+	}
+	Ok(schedule.account_claimable(&account, clock.unix_timestamp)?)
+}
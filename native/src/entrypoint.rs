@@ -1,4 +1,5 @@
 use crate::{error::VestingError, processor::Processor};
+#[allow(deprecated)]
 use solana_program::{
 	account_info::AccountInfo, entrypoint::ProgramResult, program_error::PrintProgramError,
 	pubkey::Pubkey,
@@ -11,6 +12,7 @@ pub fn process_instruction(
 	data: &[u8],
 ) -> ProgramResult {
 	if let Err(e) = Processor::process(program_id, accounts, data) {
+		#[allow(deprecated)]
 		e.print::<VestingError>();
 		return Err(e);
 	}
@@ -1,5 +1,6 @@
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive as FromPrimitiveTrait;
+#[allow(deprecated)]
 use solana_program::{
 	decode_error::DecodeError,
 	msg,
@@ -9,8 +10,90 @@ use thiserror::Error;
 
 #[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
 pub enum VestingError {
-    #[error("Invalid instruction")]
-    InvalidInstruction,
+	#[error("Invalid instruction")]
+	InvalidInstruction,
+	#[error("Account is not of the expected type")]
+	WrongAccountType,
+	#[error("Arithmetic overflow")]
+	Overflow,
+	#[error("Too many accounts for a single query")]
+	TooManyAccounts,
+	#[error("Account is not initialized")]
+	NotInitialized,
+	#[error("Account is already initialized")]
+	AlreadyInitialized,
+	#[error("Signer is not the vesting schedule authority")]
+	InvalidAuthority,
+	#[error("Mint does not match the vesting schedule mint")]
+	InvalidMint,
+	#[error("Nothing to claim")]
+	NothingToClaim,
+	#[error("Vesting has not started")]
+	VestingNotStarted,
+	#[error("Invalid vesting schedule")]
+	InvalidSchedule,
+	#[error("Insufficient vault balance")]
+	InsufficientVaultBalance,
+	#[error("Program account is not the expected program")]
+	IncorrectProgramId,
+	#[error("Amount is too small to emit any tokens per period")]
+	EmissionsRoundToZero,
+	#[error("Acknowledged forfeit does not match the claimable amount")]
+	ForfeitAcknowledgementMismatch,
+	#[error("Vesting schedule still holds tokens")]
+	ScheduleNotEmpty,
+	#[error("Destination is not the vesting account's allowed destination")]
+	DestinationNotAllowed,
+	#[error("Vault is not the vesting schedule's associated token account")]
+	InvalidVault,
+	#[error("A required account is missing")]
+	MissingRequiredAccount,
+	#[error("Vesting schedule has no vault")]
+	NoVault,
+	#[error("Vesting schedule's mint is not the native mint")]
+	NotNativeMint,
+	#[error("Allocations exceed the vesting schedule's pool")]
+	AllocationExceedsPool,
+	#[error("Vesting schedule's start does not match the expected start")]
+	StartMismatch,
+	#[error("Adjustment leaves the claimed amount unchanged")]
+	AdjustmentOutOfRange,
+	#[error("Claim limit for the current window has been reached")]
+	WindowLimitReached,
+	#[error("Vesting schedule has already completed")]
+	ScheduleAlreadyCompleted,
+	#[error("Vesting account is locked")]
+	AccountLocked,
+	#[error("Voucher has expired")]
+	VoucherExpired,
+	#[error("Voucher was not signed by the vesting schedule's authority")]
+	InvalidVoucher,
+	#[error("Vesting schedule is immutable")]
+	ScheduleImmutable,
+	#[error("Vesting duration is negative")]
+	InvalidDuration,
+	#[error("Vesting account owner has not been inactive for long enough")]
+	FallbackNotYetActive,
+	#[error("Token account balance is too low")]
+	InsufficientTokenBalance,
+	#[error("Token account is frozen")]
+	TokenAccountFrozen,
+	#[error("Token account is owned by someone else")]
+	TokenOwnerMismatch,
+	#[error("Amended start is too far in the past")]
+	StartTooFarInPast,
+	#[error("Vesting account has nothing left to forfeit")]
+	NothingToForfeit,
+	#[error("Migration ratio must be positive")]
+	InvalidMigrationRatio,
+	#[error("Vesting account has already migrated to the schedule's mint")]
+	AccountAlreadyMigrated,
+	#[error("Vesting account has not migrated to the schedule's mint")]
+	AccountNotMigrated,
+	#[error("Claims from this vesting account are paused")]
+	AccountClaimsPaused,
+	#[error("Vesting schedule has already migrated to a new mint")]
+	ScheduleAlreadyMigrated,
 }
 
 impl From<VestingError> for ProgramError {
@@ -19,12 +102,14 @@ impl From<VestingError> for ProgramError {
 	}
 }
 
+#[allow(deprecated)]
 impl<T> DecodeError<T> for VestingError {
 	fn type_of() -> &'static str {
 		"Vesting error"
 	}
 }
 
+#[allow(deprecated)]
 impl PrintProgramError for VestingError {
 	fn print<E>(&self)
 	where
@@ -1,41 +1,249 @@
 use {
-	crate::{error::VestingError, state::Frequency},
+	crate::{
+		error::VestingError, find_vault_address, find_vesting_schedule_address_from_hash,
+		hash_identifier, state::Frequency, IDENTIFIER_LEN, LABEL_LEN,
+	},
+	num_enum::TryFromPrimitive,
 	solana_program::{
 		clock::UnixTimestamp,
+		hash::hashv,
 		instruction::{AccountMeta, Instruction},
+		program::MAX_RETURN_DATA,
 		program_error::ProgramError,
 		program_option::COption,
 		pubkey::Pubkey,
+		sysvar,
 	},
 };
 
+#[allow(deprecated)]
+use solana_program::system_program;
+
 const PUBKEY_BYTES: usize = 32;
 const BYTES_64: usize = 8;
+const BYTES_32: usize = 4;
+const BYTES_16: usize = 2;
+const SIGNATURE_BYTES: usize = 64;
+/// Maximum size of a serialized transaction
+const PACKET_DATA_SIZE: usize = 1232;
+/// Maximum number of accounts a transaction may reference
+const MAX_TX_ACCOUNT_LOCKS: usize = 64;
+
+/// Maximum number of vesting accounts a `GetClaimableMany` query accepts, bounded by the size of
+/// the return data buffer
+pub const MAX_CLAIMABLE_QUERY_ACCOUNTS: usize = MAX_RETURN_DATA / BYTES_64;
+
+/// Maximum number of points a `SampleCurve` query returns, bounded by the size of the return data
+/// buffer
+pub const MAX_CURVE_POINTS: u16 = (MAX_RETURN_DATA / (2 * BYTES_64)) as u16;
+
+/// Prefix of the instruction data a schedule's manager program receives on each claim, followed
+/// by the amount claimed as a little-endian `u64`
+pub const ON_CLAIM_DISCRIMINATOR: [u8; 8] = *b"on_claim";
+
+/// Magic bytes prefixed to instruction data with the `instruction-magic` feature, so that data
+/// packed for a fork sharing this program's tags is rejected rather than misread
+pub const INSTRUCTION_MAGIC: [u8; 2] = *b"fv";
+
+/// Prefix of all packed instruction data, [`INSTRUCTION_MAGIC`] with the `instruction-magic`
+/// feature and empty without it
+pub const INSTRUCTION_PREFIX: &[u8] = if cfg!(feature = "instruction-magic") {
+	&INSTRUCTION_MAGIC
+} else {
+	&[]
+};
+
+/// Length of the Anchor-style discriminators, see [`ANCHOR_DISCRIMINATORS`]
+pub const ANCHOR_DISCRIMINATOR_LEN: usize = 8;
+
+/// Length of the tag identifying each instruction after [`INSTRUCTION_PREFIX`], its
+/// [`ANCHOR_DISCRIMINATORS`] entry with the `anchor-compat` feature and a single byte without it
+pub const TAG_LEN: usize = if cfg!(feature = "anchor-compat") {
+	ANCHOR_DISCRIMINATOR_LEN
+} else {
+	1
+};
+
+/// Name and Anchor discriminator of each instruction indexed by its [`InstructionTag`], the
+/// discriminator being the [`anchor_sighash`] of the name so that Anchor-generated clients can
+/// address this program
+pub const ANCHOR_DISCRIMINATORS: [(&str, [u8; ANCHOR_DISCRIMINATOR_LEN]); 35] = [
+	(
+		"init_vesting_schedule",
+		[108, 203, 254, 216, 36, 236, 146, 22],
+	),
+	("create_account", [99, 20, 130, 119, 196, 235, 131, 149]),
+	("amend_amount", [206, 79, 229, 234, 28, 121, 23, 106]),
+	("amend_schedule", [209, 21, 81, 235, 64, 28, 44, 182]),
+	("claim", [62, 198, 214, 193, 213, 159, 108, 210]),
+	("close_account", [125, 255, 149, 14, 110, 34, 72, 24]),
+	(
+		"close_vesting_schedule",
+		[53, 177, 56, 104, 70, 183, 187, 179],
+	),
+	(
+		"get_claimable_many",
+		[231, 182, 234, 181, 224, 149, 30, 217],
+	),
+	("schedule_amend", [195, 238, 242, 192, 210, 159, 246, 48]),
+	("crank_claim", [193, 62, 163, 14, 168, 236, 179, 103]),
+	("get_claimable", [111, 52, 219, 251, 78, 77, 231, 124]),
+	(
+		"set_allowed_destination",
+		[32, 141, 104, 74, 177, 151, 199, 81],
+	),
+	("claim_to", [111, 34, 225, 195, 66, 4, 242, 165]),
+	("claim_and_unwrap", [211, 181, 221, 8, 249, 222, 181, 150]),
+	("create_account_by_bps", [2, 209, 179, 39, 82, 221, 158, 91]),
+	("set_label", [73, 237, 115, 116, 255, 219, 9, 17]),
+	("reassign_rent_payer", [145, 185, 226, 76, 111, 127, 74, 8]),
+	("adjust_claimed", [243, 48, 213, 52, 63, 119, 139, 124]),
+	("set_claim_window", [190, 124, 26, 181, 134, 231, 61, 190]),
+	("get_ui_context", [120, 233, 79, 236, 224, 144, 17, 128]),
+	("lock_account", [96, 230, 27, 93, 190, 236, 88, 0]),
+	("unlock_account", [217, 242, 41, 211, 95, 46, 30, 39]),
+	("sample_curve", [71, 53, 102, 20, 130, 92, 90, 23]),
+	("redeem_voucher", [50, 219, 8, 127, 45, 96, 161, 92]),
+	(
+		"renounce_amend_authority",
+		[50, 245, 148, 159, 68, 247, 58, 124],
+	),
+	("set_fallback", [248, 94, 224, 38, 5, 235, 51, 131]),
+	("claim_fallback", [159, 151, 104, 156, 13, 28, 74, 168]),
+	("init_by_count", [129, 20, 10, 231, 191, 227, 110, 206]),
+	("forfeit", [80, 154, 237, 158, 244, 198, 154, 9]),
+	("rotate_vault", [192, 205, 175, 133, 189, 211, 141, 109]),
+	(
+		"get_supported_instructions",
+		[19, 53, 136, 131, 66, 1, 5, 9],
+	),
+	("migrate_mint", [44, 219, 122, 235, 251, 138, 113, 150]),
+	("migrate_account_mint", [75, 215, 168, 225, 58, 90, 6, 8]),
+	("pause_account", [136, 35, 45, 188, 18, 236, 43, 72]),
+	("unpause_account", [78, 179, 75, 220, 103, 167, 113, 175]),
+];
+
+/// Length of the bitmask returned by `GetSupportedInstructions`, a bit for every possible tag
+pub const SUPPORTED_INSTRUCTIONS_LEN: usize = 32;
+
+/// Bitmask of the [`InstructionTag`]s this build handles, see
+/// [`VestingInstruction::GetSupportedInstructions`]
+pub fn supported_instructions() -> [u8; SUPPORTED_INSTRUCTIONS_LEN] {
+	let mut mask = [0; SUPPORTED_INSTRUCTIONS_LEN];
+	for tag in (0..=u8::MAX).filter(|&tag| InstructionTag::try_from(tag).is_ok()) {
+		mask[usize::from(tag / 8)] |= 1 << (tag % 8);
+	}
+	mask
+}
 
+/// The discriminator Anchor derives for an instruction named `name`, the first bytes of the
+/// SHA-256 hash of `global:<name>`
+pub fn anchor_sighash(name: &str) -> [u8; ANCHOR_DISCRIMINATOR_LEN] {
+	let hash = hashv(&[b"global:", name.as_bytes()]);
+	let mut sighash = [0; ANCHOR_DISCRIMINATOR_LEN];
+	sighash.copy_from_slice(&hash.as_ref()[..ANCHOR_DISCRIMINATOR_LEN]);
+	sighash
+}
+
+/// What a `CloseAccount` or `CloseVestingSchedule` returned, set as return data by both as the
+/// little-endian `u64`s `lamports_refunded` then `tokens_swept`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CloseReceipt {
+	/// Lamports refunded from the closed accounts
+	pub lamports_refunded: u64,
+	/// Tokens swept to the authority
+	pub tokens_swept: u64,
+}
+
+impl CloseReceipt {
+	/// Length of a packed receipt
+	pub const LEN: usize = 2 * BYTES_64;
+
+	/// Packs the receipt as it is set as return data
+	pub fn pack(&self) -> [u8; Self::LEN] {
+		let mut data = [0; Self::LEN];
+		data[..BYTES_64].copy_from_slice(&self.lamports_refunded.to_le_bytes());
+		data[BYTES_64..].copy_from_slice(&self.tokens_swept.to_le_bytes());
+		data
+	}
+
+	/// Unpacks a receipt from the return data of a close
+	pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+		if data.len() != Self::LEN {
+			return Err(ProgramError::InvalidArgument);
+		}
+		let (lamports_refunded, rest) = VestingInstruction::unpack_u64(data)?;
+		let (tokens_swept, _) = VestingInstruction::unpack_u64(rest)?;
+		Ok(Self {
+			lamports_refunded,
+			tokens_swept,
+		})
+	}
+}
+
+/// Prefix of the message an authority signs to issue a voucher, see [`voucher_message`]
+pub const VOUCHER_DOMAIN: &[u8] = b"fsp-vesting:voucher";
+
+/// Suggested `past_start_tolerance` for initialising a schedule, allowing for the time taken to
+/// land the transaction
+pub const DEFAULT_PAST_START_TOLERANCE: i64 = 3_600;
+
+/// Instructions supported by the vesting program. Wherever a token program is expected, either
+/// the legacy token program or Token-2022 may be passed, whichever owns the schedule's mint.
 #[repr(C)]
-#[derive(Clone, Debug)]
-pub enum VestingInstruction<'a> {
-	/// Initialises a vesting schedule
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum VestingInstruction {
+	/// Initialises a vesting schedule at the address derived from `mint` and the hashed
+	/// `identifier`. If `quantize_start_to` is set `start` is rounded down to a multiple of that
+	/// many seconds before being stored, bringing the first emission forward by up to as much.
+	/// A `start` more than `past_start_tolerance` seconds in the past is rejected, since it would
+	/// vest immediately, unless `allow_past_start` is set. The tolerance is stored on the schedule
+	/// and may not be negative. `pool_total` is shared out by `CreateAccountByBps`, zero if
+	/// accounts are only created with explicit amounts. `cliff_bps` basis points of each
+	/// account's amount, at most `10_000`, are released in a lump at `start + cliff` and the rest
+	/// vests from the cliff on. The mint's decimals are stored on the schedule, see
+	/// [`Self::GetUiContext`].
 	///
 	/// Accounts expected:
 	/// 0. `[w]` Vesting schedule account
 	/// 1. `[w, s]` Payer
 	/// 2. `[]` System program
+	/// 3. `[]` Mint
 	///
-	/// Optional accounts:
-	/// 3. `[w]` Vault account (Vesting schedule ATA)
-	/// 4. `[]` Token program
-	/// 5. `[]` Associated token program
+	/// Optional accounts, required if `vault` is set:
+	/// 4. `[w]` Vault account (Vesting schedule ATA)
+	/// 5. `[]` Token program
+	/// 6. `[]` Associated token program
 	InitVestingSchedule {
 		authority: Pubkey,
 		mint: Pubkey,
 		schedule: Frequency,
 		start: UnixTimestamp,
 		duration: i64,
+		cliff: i64,
 		vault: COption<Pubkey>,
+		anchor_to_creation: bool,
+		identifier: [u8; IDENTIFIER_LEN],
+		receipt_mint: COption<Pubkey>,
+		tip_lamports: u64,
+		manager_program: COption<Pubkey>,
+		quantize_start_to: Option<i64>,
+		allow_past_start: bool,
+		past_start_tolerance: i64,
+		pool_total: u64,
+		cliff_bps: u16,
 	},
 
-	/// Creates a vesting account
+	/// Creates a vesting account for `owner`, and its ATA for pre-loading tokens if the schedule
+	/// has no vault. `immediate_bps` basis points of `amount` are released at the start with the
+	/// rest vesting over the schedule, at most `10_000` together with the schedule's `cliff_bps`.
+	/// Fails with `ScheduleAlreadyCompleted` if the schedule has fully vested, so the account
+	/// could be claimed in full at once, unless `allow_post_completion` is set.
+	///
+	/// The ATA must be owned by the vesting account with no delegate or close authority, so only
+	/// the program can move its tokens and the authority can't drain it once pre-loaded. An ATA
+	/// created ahead of time that doesn't meet this fails with `TokenOwnerMismatch`.
 	///
 	/// Accounts expected:
 	///
@@ -44,49 +252,81 @@ pub enum VestingInstruction<'a> {
 	/// 2. `[]` Mint
 	/// 3. `[w]` Vesting account
 	/// 4. `[w]` Vesting account ATA
-	/// 3. `[]` System program
-	/// 4. `[]` Token program
-	/// 5. `[]` Associated token program
-	CreateAccount { owner: Pubkey, amount: u64 },
+	/// 5. `[]` System program
+	/// 6. `[]` Token program
+	/// 7. `[]` Associated token program
+	CreateAccount {
+		owner: Pubkey,
+		amount: u64,
+		immediate_bps: u16,
+		allow_post_completion: bool,
+	},
 
-	/// Amend amount
+	/// Amend the total amount allocated to a vesting account, which may not fall below the
+	/// amount already claimed. On schedules without a vault, increases must be covered by the
+	/// vesting account ATA's balance. A vault is shared by the whole schedule so it isn't checked
+	/// against a single account, a shortfall only surfaces as `InsufficientVaultBalance` on claim.
+	/// Once the account has started vesting, what has vested so far is kept and the rest of the
+	/// new amount is emitted over the remainder of the schedule.
 	///
 	/// Accounts expected:
 	///
 	/// 0. `[]` Vesting schedule account
 	/// 1. `[w, s]` Authority
-	/// 2. `[w]` Vesting account ATA
-	/// 2. `[]` Token program
+	/// 2. `[w]` Vesting account
+	/// 3. `[]` Vesting account ATA, or the vault if the schedule has one
+	/// 4. `[]` Token program
 	AmendAmount { amount: u64 },
 
-	/// Amend the vesting schedule
+	/// Amend the vesting schedule, fields left as `None` are unchanged. Amendments that would
+	/// fully vest a schedule which hasn't completed yet are rejected unless `force` is set. The
+	/// start may only be moved back to at most the schedule's `past_start_tolerance` in the past,
+	/// failing with `StartTooFarInPast` even when forced.
 	///
 	/// Accounts expected:
 	///
-	/// 0. `[]` Vesting schedule account
+	/// 0. `[w]` Vesting schedule account
 	/// 1. `[w, s]` Authority
 	AmendSchedule {
 		start: Option<UnixTimestamp>,
 		schedule: Option<Frequency>,
 		duration: Option<i64>,
+		force: bool,
 	},
 
-	/// Claim vested tokens
+	/// Claim vested tokens. If `expected_start` is set the claim fails with `StartMismatch` unless
+	/// it equals the schedule's stored start, letting a recipient pin the start they agreed to.
+	/// The recipient may be a PDA, signing through `invoke_signed`, its ATA is derived the same
+	/// way as for any other wallet. Programs that can't sign can be paid with `CrankClaim`.
 	///
 	/// Accounts expected:
 	///
 	/// 0. `[]` Vesting schedule
 	/// 1. `[]` Mint
 	/// 2. `[w]` Vesting account
-	/// 3. `[w]` Vesting account ATA
+	/// 3. `[w]` Vesting account ATA, or the vault if the schedule has one
 	/// 4. `[w,s]` Recipient wallet
 	/// 5. `[w]` Reciepients ATA
 	/// 6. `[]` System program
 	/// 7. `[]` Token program
 	/// 8. `[]` Associated token program
-	Claim,
+	///
+	/// Optional accounts, required if the schedule has a receipt mint:
+	/// 9. `[w]` Receipt mint
+	/// 10. `[w]` Recipient's receipt mint ATA
+	///
+	/// Optional accounts, required if the schedule has a manager program, following any receipt
+	/// accounts. Tokens are sent to the manager's token account instead of the recipient's ATA:
+	/// 0. `[]` Manager program
+	/// 1. `[w]` Manager account, owned by the manager program
+	/// 2. `[w]` Manager token account, owned by the manager account
+	Claim { expected_start: Option<i64> },
 
-	/// Closes a vesting account and its ATA
+	/// Closes a vesting account and its ATA, returning unclaimed tokens to the authority and rent
+	/// to the account's rent payer. `acknowledged_forfeit` must equal the amount the account can
+	/// currently claim, confirming the vested tokens being given up. It is checked against the
+	/// account's state when the instruction runs, so a close bundled with a claim of the same
+	/// account only succeeds if it acknowledges what is left after any claim ahead of it.
 	///
 	/// Accounts expected:
 	///
@@ -94,79 +334,1052 @@ pub enum VestingInstruction<'a> {
 	/// 1. `[w, s]` Authority
 	/// 2. `[]` Mint
 	/// 3. `[w]` Vesting account
-	/// 4. `[w]` Vesting account ATA
-	/// 5. `[]` Recipient wallet
-	/// 6. `[w]` Recipient's ATA
-	/// 7. `[]` System program
-	/// 8. `[]` Token program
-	/// 9. `[]` Associated token program
-	CloseAccount,
+	/// 4. `[w]` Vesting account ATA, or the vault if the schedule has one
+	/// 5. `[w]` Authority's token account, receiving the unclaimed tokens
+	/// 6. `[]` Token program
+	///
+	/// Optional account, required if the rent payer has been reassigned from the authority:
+	/// 7. `[w]` Rent payer
+	///
+	/// Returns a [`CloseReceipt`] via return data, its lamports being those of the vesting account
+	/// and its ATA refunded to the rent payer.
+	CloseAccount { acknowledged_forfeit: u64 },
 
-	/// Closes a vesting schedule
+	/// Closes a vesting schedule and its vault, refunding their lamports to the authority. The
+	/// vault must be empty, schedules without a vault can't be checked for every vesting account
+	/// holding tokens so can't be closed.
 	///
 	/// Accounts expected:
 	/// 0. `[w]` Vesting schedule
 	/// 1. `[w, s]` Authority
 	/// 2. `[]` System program
+	/// 3. `[w]` Vault
+	/// 4. `[]` Token program
+	///
+	/// Returns a [`CloseReceipt`] via return data, its lamports being those of the schedule and
+	/// the vault, no tokens are swept from the empty vault.
 	CloseVestingSchedule,
-}
 
-impl<'a> VestingInstruction<'a> {
-	/// Unpacks a byte buffer into a [VestingInstruction](enum.VestingInstruction.html).
-	fn unpack(input: &'a [u8]) -> Result<Self, ProgramError> {
-		use VestingError::InvalidInstruction;
-		let (&tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
-		Ok(match tag {
-			0 => {}
-			1 => {}
-			2 => {}
-			3 => {}
-			4 => {}
-			5 => {}
-			6 => {}
-		})
-	}
-	/// Packs a [VestingInstruction](enum.VestingInstruction.html) into a byte buffer
-	fn pack(&self) -> Vec<u8> {
-		let mut buf = Vec::with_capacity(size_of::<Self>());
-		match self {
-            &Self::InitVestingSchedule
-        }
-	}
+	/// Permissionlessly claims vested tokens on the recipient's behalf, paying the cranker the
+	/// schedule's tip if the lamports deposited in the schedule account cover it
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[w]` Vesting schedule
+	/// 1. `[]` Mint
+	/// 2. `[w]` Vesting account
+	/// 3. `[w]` Vesting account ATA, or the vault if the schedule has one
+	/// 4. `[]` Recipient wallet
+	/// 5. `[w]` Reciepients ATA
+	/// 6. `[]` System program
+	/// 7. `[]` Token program
+	/// 8. `[]` Associated token program
+	/// 9. `[w, s]` Cranker
+	///
+	/// Optional accounts, required if the schedule has a receipt mint:
+	/// 10. `[w]` Receipt mint
+	/// 11. `[w]` Recipient's receipt mint ATA
+	///
+	/// Optional accounts, required if the schedule has a manager program, as for `Claim`
+	CrankClaim,
 
-	pub(crate) fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
-		let pk = input
-			.get(..PUBKEY_BYTES)
-			.and_then(|x| Pubkey::try_from(x).ok())
-			.ok_or(VestingError::InvalidInstruction)?;
-		Ok((pk, &input[PUBKEY_BYTES..]))
-	}
+	/// Returns the amount currently claimable by each vesting account as little-endian `u64`s via
+	/// return data, in the order the accounts were passed
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. ..1 + N `[]` Vesting accounts, at most `MAX_CLAIMABLE_QUERY_ACCOUNTS`
+	GetClaimableMany,
 
-	pub(crate) fn unpack_pubkey_option(
-		input: &[u8],
-	) -> Result<(COption<Pubkey>, &[u8]), ProgramError> {
-		match input.split_first() {
-			Option::Some((&0, rest)) => Ok((COption::None, rest)),
-			Option::Some((&1, rest)) => {
-				let (pk, rest) = Self::unpack_pubkey(rest)?;
-				Ok((COption::Some(pk), rest))
-			}
-			_ => Err(VestingError::InvalidInstruction.into()),
-		}
-	}
+	/// Schedules an amendment to the vesting schedule that takes effect at `effective_at`, which
+	/// must be in the future, replacing any amendment already pending. Fields left as `None` are
+	/// unchanged. Until then claims follow the current schedule, giving recipients notice. The
+	/// start is limited as for `AmendSchedule`, relative to `effective_at`.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[w]` Vesting schedule account
+	/// 1. `[s]` Authority
+	ScheduleAmend {
+		effective_at: UnixTimestamp,
+		start: Option<UnixTimestamp>,
+		schedule: Option<Frequency>,
+		duration: Option<i64>,
+	},
 
-	pub(crate) fn pack_pubkey_option(value: &COption<Pubkey>, buf: &mut Vec<u8>) {
-		match *value {
-			COption::Some(ref key) => {
-				buf.push(1);
-				buf.extend_from_slice(&key.to_bytes());
-			}
-			COption::None => buf.push(0),
+	/// Returns the amount currently claimable by a vesting account as a little-endian `u64` via
+	/// return data, meant to be simulated rather than submitted
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. `[]` Vesting account
+	GetClaimable,
+
+	/// Restricts where a vesting account's claims may be sent to a single token account, or lifts
+	/// the restriction if `allowed_destination` is `None`
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. `[s]` Authority
+	/// 2. `[w]` Vesting account
+	SetAllowedDestination {
+		allowed_destination: COption<Pubkey>,
+	},
+
+	/// Claim vested tokens into a token account of the recipient's choosing, which must be the
+	/// vesting account's allowed destination if it has one. Not available to schedules with a
+	/// manager program, whose claims always go to the manager.
+	///
+	/// The destination may belong to any wallet or program derived address, such as a multisig
+	/// vault, only the recipient has to sign. It must already exist, and its delegate and close
+	/// authority aren't inspected: whoever they name can move the tokens once claimed, an
+	/// authority wanting claims to land in one particular account pins it with
+	/// `SetAllowedDestination`.
+	///
+	/// Accounts expected as for `Claim`, with the destination token account in place of the
+	/// recipient's ATA:
+	///
+	/// 5. `[w]` Destination token account
+	ClaimTo,
+
+	/// Claim vested wrapped SOL as native SOL, the tokens are claimed into the recipient's ATA for
+	/// the native mint which is then closed into the recipient's wallet. Only available to
+	/// schedules vesting the native mint without a manager program.
+	///
+	/// Accounts expected as for `Claim`
+	ClaimAndUnwrap,
+
+	/// Creates a vesting account for `owner` allocated `bps` basis points of the schedule's
+	/// `pool_total`, rounded down. The basis points allocated across the schedule may not exceed
+	/// `10_000`.
+	///
+	/// Accounts expected as for `CreateAccount`, with the vesting schedule writable:
+	///
+	/// 0. `[w]` Vesting schedule account
+	CreateAccountByBps { owner: Pubkey, bps: u16 },
+
+	/// Sets a vesting schedule's descriptive label, which must be valid UTF-8. The schedule's
+	/// address is unaffected.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[w]` Vesting schedule
+	/// 1. `[s]` Authority
+	SetLabel { label: [u8; LABEL_LEN] },
+
+	/// Reassigns who is refunded a vesting account's rent when it is closed, e.g. to switch an
+	/// account the authority funded to one its owner has paid for. Token vesting is unaffected.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[s]` Current rent payer
+	/// 1. `[w]` Vesting account
+	ReassignRentPayer { new_payer: Pubkey },
+
+	/// Corrects the amount a vesting account has claimed by `delta`, clamped so it stays between
+	/// zero and the account's amount. Fails with `AdjustmentOutOfRange` if the clamped
+	/// adjustment would leave it unchanged.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. `[s]` Authority
+	/// 2. `[w]` Vesting account
+	AdjustClaimed { delta: i64 },
+
+	/// Limits how much of a vesting account can be claimed per window of `window_seconds`,
+	/// aligned to the Unix epoch, so that claims can be spread across tax periods. Claims are cut
+	/// to what is left of the window and fail with `WindowLimitReached` once it is used up. A zero
+	/// `max_claim_per_window` lifts the limit.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[s]` Owner
+	/// 1. `[w]` Vesting account
+	SetClaimWindow {
+		max_claim_per_window: u64,
+		window_seconds: i64,
+	},
+
+	/// Returns what clients need to render a schedule's amounts, currently the mint's decimals
+	/// when the schedule was initialised as a single byte, via return data. Meant to be simulated
+	/// rather than submitted.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	GetUiContext,
+
+	/// Freezes a vesting account, e.g. for a legal hold, so claims and amendments to it fail with
+	/// `AccountLocked` until it is unlocked. Nothing is revoked and the account keeps vesting.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. `[s]` Authority
+	/// 2. `[w]` Vesting account
+	LockAccount,
+
+	/// Lifts a [`Self::LockAccount`] freeze
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. `[s]` Authority
+	/// 2. `[w]` Vesting account
+	UnlockAccount,
+
+	/// Returns the amount a vesting account has vested, regardless of what it has claimed, at
+	/// `points` timestamps spread evenly from `start_ts` to `end_ts` inclusive, as pairs of
+	/// little-endian `i64` timestamp and `u64` amount via return data. Lets clients draw the
+	/// unlock curve with one simulation. `points` may be at most `MAX_CURVE_POINTS`.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. `[]` Vesting account
+	SampleCurve {
+		start_ts: UnixTimestamp,
+		end_ts: UnixTimestamp,
+		points: u16,
+	},
+
+	/// Creates the signing owner's vesting account, paid for by them, from a voucher the
+	/// schedule's authority signed off-chain for `amount` until `expiry`, see [`voucher_message`].
+	/// The instruction before this one must be an ed25519 program instruction verifying
+	/// `signature` over the voucher by the authority, otherwise it fails with `InvalidVoucher`.
+	/// Fails with `VoucherExpired` after `expiry`. A voucher can be redeemed again until it
+	/// expires if the account it created is closed.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule account
+	/// 1. `[w, s]` Owner
+	/// 2. `[]` Mint
+	/// 3. `[w]` Vesting account
+	/// 4. `[w]` Vesting account ATA
+	/// 5. `[]` System program
+	/// 6. `[]` Token program
+	/// 7. `[]` Associated token program
+	/// 8. `[]` Instructions sysvar
+	RedeemVoucher {
+		amount: u64,
+		expiry: UnixTimestamp,
+		signature: [u8; SIGNATURE_BYTES],
+	},
+
+	/// Permanently renounces the authority's power to amend the schedule and its accounts, so
+	/// recipients can trust their terms won't change. Amendments then fail with
+	/// `ScheduleImmutable`, as does closing an account before it has fully vested. Claims are
+	/// unaffected. There is no way to undo it.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[w]` Vesting schedule
+	/// 1. `[s]` Authority
+	RenounceAmendAuthority,
+
+	/// Lets `fallback_owner`, e.g. an heir, claim from the owner's vesting account with
+	/// `ClaimFallback` once the owner has gone `inactivity_seconds` without claiming. `None`
+	/// removes the fallback. Counts as activity by the owner.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[s]` Owner
+	/// 1. `[w]` Vesting account
+	SetFallback {
+		fallback_owner: COption<Pubkey>,
+		inactivity_seconds: i64,
+	},
+
+	/// Claim vested tokens as a vesting account's fallback owner, into their ATA. Fails with
+	/// `FallbackNotYetActive` until the account's `inactivity_seconds` have passed since the owner
+	/// last claimed or set the fallback. Claims by the fallback owner don't restart the clock.
+	///
+	/// Accounts expected as for `Claim`, with the fallback owner in place of the recipient:
+	///
+	/// 4. `[w,s]` Fallback owner wallet
+	/// 5. `[w]` Fallback owner's ATA
+	ClaimFallback,
+
+	/// Initialises a vesting schedule as for `InitVestingSchedule`, but as a number of equal
+	/// `emissions` rather than a duration, e.g. 36 monthly unlocks. The duration stored is
+	/// `emissions` periods of `schedule`, which must be periodic, and each emission releases an
+	/// equal share of every allocation. Fails with `InvalidSchedule` if `emissions` is zero.
+	///
+	/// Accounts expected as for `InitVestingSchedule`
+	InitByCount {
+		authority: Pubkey,
+		mint: Pubkey,
+		schedule: Frequency,
+		start: UnixTimestamp,
+		emissions: u32,
+		cliff: i64,
+		vault: COption<Pubkey>,
+		anchor_to_creation: bool,
+		identifier: [u8; IDENTIFIER_LEN],
+		receipt_mint: COption<Pubkey>,
+		tip_lamports: u64,
+		manager_program: COption<Pubkey>,
+		quantize_start_to: Option<i64>,
+		allow_past_start: bool,
+		past_start_tolerance: i64,
+		pool_total: u64,
+		cliff_bps: u16,
+	},
+
+	/// Forfeit a vesting account's unvested tokens, returning them to the authority. The
+	/// account's amount is reduced to what it has vested so far, which it can still claim, and it
+	/// vests nothing further. Signed by the owner, unlike `CloseAccount` which is the authority's.
+	/// Fails with `NothingToForfeit` once the account has fully vested.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. `[s]` Owner
+	/// 2. `[]` Mint
+	/// 3. `[w]` Vesting account
+	/// 4. `[w]` Vesting account ATA, or the vault if the schedule has one
+	/// 5. `[w]` Authority's token account, receiving the forfeited tokens
+	/// 6. `[]` Token program
+	Forfeit,
+
+	/// Rotate the schedule's vault, moving its whole balance to a new vault owned by the schedule
+	/// at [`crate::find_vault_address`] and closing the old one, its rent going to the authority.
+	/// Fails with `NoVault` if the schedule doesn't have one.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[w]` Vesting schedule
+	/// 1. `[s, w]` Authority
+	/// 2. `[s, w]` Payer of the new vault's rent
+	/// 3. `[]` Mint
+	/// 4. `[w]` Current vault
+	/// 5. `[w]` New vault
+	/// 6. `[]` System program
+	/// 7. `[]` Token program
+	RotateVault,
+
+	/// Query which instructions this deployment supports, so clients can hide features it
+	/// predates. Returns [`supported_instructions`] as return data, bit `tag % 8` of byte
+	/// `tag / 8` set for each supported [`InstructionTag`].
+	///
+	/// No accounts expected.
+	GetSupportedInstructions,
+
+	/// Migrate the schedule to `new_mint`, e.g. after a token relaunch, at `ratio_num` new tokens
+	/// for every `ratio_den` old ones. Each vesting account is rebound with
+	/// `MigrateAccountMint`, until then it can't be claimed from, amended or closed. The swap of
+	/// the tokens themselves happens outside the program. Fails with `InvalidMigrationRatio`
+	/// unless both parts of the ratio are positive, and with `ScheduleAlreadyMigrated` if the
+	/// schedule has migrated before since accounts still on its previous mint could then never
+	/// be rebound.
+	///
+	/// With a vault, the schedule's ATA of `new_mint` becomes its vault. It must already hold the
+	/// old vault's balance converted at the ratio, or the migration fails with
+	/// `InsufficientTokenBalance`. The old vault's tokens are then sent to `destination` and the
+	/// old vault is closed, refunding its rent to the authority. Both mints must belong to the
+	/// same token program.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[w]` Vesting schedule
+	/// 1. `[w, s]` Authority
+	/// 2. `[]` New mint
+	///
+	/// Optional accounts, required if the schedule has a vault:
+	/// 3. `[w]` New vault (Vesting schedule ATA of the new mint)
+	/// 4. `[w]` Vault
+	/// 5. `[]` Mint
+	/// 6. `[w]` Destination token account of the mint
+	/// 7. `[]` System program
+	/// 8. `[]` Token program
+	/// 9. `[]` Associated token program
+	MigrateMint {
+		new_mint: Pubkey,
+		ratio_num: u64,
+		ratio_den: u64,
+	},
+
+	/// Rebind a vesting account to the mint its schedule migrated to, scaling its amounts by the
+	/// migration ratio, see `MigrateMint`. Fails with `AccountAlreadyMigrated` if it has been.
+	///
+	/// Without a vault, the vesting account's ATA of the new mint must already hold what remains
+	/// to be claimed after scaling, or the migration fails with `InsufficientTokenBalance`. Its
+	/// ATA of the previous mint is then emptied into `destination` and closed, refunding its rent
+	/// to the account's rent payer.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. `[s]` Authority
+	/// 2. `[w]` Vesting account
+	///
+	/// Optional accounts, required if the schedule has no vault:
+	/// 3. `[w]` Vesting account ATA of the previous mint
+	/// 4. `[]` Previous mint
+	/// 5. `[]` Vesting account ATA of the new mint
+	/// 6. `[w]` Destination token account of the previous mint
+	/// 7. `[w]` Rent payer
+	/// 8. `[]` Token program
+	MigrateAccountMint,
+
+	/// Pauses claims from a single vesting account, e.g. during a dispute, so they fail with
+	/// `AccountClaimsPaused` while other accounts under the schedule keep claiming. Unlike
+	/// `LockAccount` the authority can still amend the account. It keeps vesting while paused, so
+	/// once unpaused its owner can claim everything that accrued in the meantime.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. `[s]` Authority
+	/// 2. `[w]` Vesting account
+	PauseAccount,
+
+	/// Lifts a [`Self::PauseAccount`] pause
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Vesting schedule
+	/// 1. `[s]` Authority
+	/// 2. `[w]` Vesting account
+	UnpauseAccount,
+}
+
+/// Tag identifying each [`VestingInstruction`] in its packed data, the one place instructions are
+/// numbered. Packing, unpacking and the processor's dispatch all go through it, so a variant
+/// without a tag, or a tag without a variant, fails to compile.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
+pub enum InstructionTag {
+	InitVestingSchedule = 0,
+	CreateAccount = 1,
+	AmendAmount = 2,
+	AmendSchedule = 3,
+	Claim = 4,
+	CloseAccount = 5,
+	CloseVestingSchedule = 6,
+	GetClaimableMany = 7,
+	ScheduleAmend = 8,
+	CrankClaim = 9,
+	GetClaimable = 10,
+	SetAllowedDestination = 11,
+	ClaimTo = 12,
+	ClaimAndUnwrap = 13,
+	CreateAccountByBps = 14,
+	SetLabel = 15,
+	ReassignRentPayer = 16,
+	AdjustClaimed = 17,
+	SetClaimWindow = 18,
+	GetUiContext = 19,
+	LockAccount = 20,
+	UnlockAccount = 21,
+	SampleCurve = 22,
+	RedeemVoucher = 23,
+	RenounceAmendAuthority = 24,
+	SetFallback = 25,
+	ClaimFallback = 26,
+	InitByCount = 27,
+	Forfeit = 28,
+	RotateVault = 29,
+	GetSupportedInstructions = 30,
+	MigrateMint = 31,
+	MigrateAccountMint = 32,
+	PauseAccount = 33,
+	UnpauseAccount = 34,
+}
+
+impl VestingInstruction {
+	/// The instruction's [`InstructionTag`]
+	pub fn tag(&self) -> InstructionTag {
+		match self {
+			Self::InitVestingSchedule { .. } => InstructionTag::InitVestingSchedule,
+			Self::CreateAccount { .. } => InstructionTag::CreateAccount,
+			Self::AmendAmount { .. } => InstructionTag::AmendAmount,
+			Self::AmendSchedule { .. } => InstructionTag::AmendSchedule,
+			Self::Claim { .. } => InstructionTag::Claim,
+			Self::CloseAccount { .. } => InstructionTag::CloseAccount,
+			Self::CloseVestingSchedule => InstructionTag::CloseVestingSchedule,
+			Self::GetClaimableMany => InstructionTag::GetClaimableMany,
+			Self::ScheduleAmend { .. } => InstructionTag::ScheduleAmend,
+			Self::CrankClaim => InstructionTag::CrankClaim,
+			Self::GetClaimable => InstructionTag::GetClaimable,
+			Self::SetAllowedDestination { .. } => InstructionTag::SetAllowedDestination,
+			Self::ClaimTo => InstructionTag::ClaimTo,
+			Self::ClaimAndUnwrap => InstructionTag::ClaimAndUnwrap,
+			Self::CreateAccountByBps { .. } => InstructionTag::CreateAccountByBps,
+			Self::SetLabel { .. } => InstructionTag::SetLabel,
+			Self::ReassignRentPayer { .. } => InstructionTag::ReassignRentPayer,
+			Self::AdjustClaimed { .. } => InstructionTag::AdjustClaimed,
+			Self::SetClaimWindow { .. } => InstructionTag::SetClaimWindow,
+			Self::GetUiContext => InstructionTag::GetUiContext,
+			Self::LockAccount => InstructionTag::LockAccount,
+			Self::UnlockAccount => InstructionTag::UnlockAccount,
+			Self::SampleCurve { .. } => InstructionTag::SampleCurve,
+			Self::RedeemVoucher { .. } => InstructionTag::RedeemVoucher,
+			Self::RenounceAmendAuthority => InstructionTag::RenounceAmendAuthority,
+			Self::SetFallback { .. } => InstructionTag::SetFallback,
+			Self::ClaimFallback => InstructionTag::ClaimFallback,
+			Self::InitByCount { .. } => InstructionTag::InitByCount,
+			Self::Forfeit => InstructionTag::Forfeit,
+			Self::RotateVault => InstructionTag::RotateVault,
+			Self::GetSupportedInstructions => InstructionTag::GetSupportedInstructions,
+			Self::MigrateMint { .. } => InstructionTag::MigrateMint,
+			Self::MigrateAccountMint => InstructionTag::MigrateAccountMint,
+			Self::PauseAccount => InstructionTag::PauseAccount,
+			Self::UnpauseAccount => InstructionTag::UnpauseAccount,
 		}
 	}
 
-	pub(crate) fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
-		let value = input
+	/// Unpacks a byte buffer into a [VestingInstruction](enum.VestingInstruction.html).
+	pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+		use VestingError::InvalidInstruction;
+		let input = input
+			.strip_prefix(INSTRUCTION_PREFIX)
+			.ok_or(InvalidInstruction)?;
+		let (tag, rest) = Self::unpack_tag(input)?;
+		let (instruction, rest) = match tag {
+			InstructionTag::InitVestingSchedule => {
+				let (authority, rest) = Self::unpack_pubkey(rest)?;
+				let (mint, rest) = Self::unpack_pubkey(rest)?;
+				let (schedule, rest) = Self::unpack_frequency(rest)?;
+				let (start, rest) = Self::unpack_i64(rest)?;
+				let (duration, rest) = Self::unpack_i64(rest)?;
+				let (cliff, rest) = Self::unpack_i64(rest)?;
+				let (vault, rest) = Self::unpack_pubkey_option(rest)?;
+				let (anchor_to_creation, rest) = Self::unpack_bool(rest)?;
+				let (identifier, rest) = Self::unpack_identifier(rest)?;
+				let (receipt_mint, rest) = Self::unpack_pubkey_option(rest)?;
+				let (tip_lamports, rest) = Self::unpack_u64(rest)?;
+				let (manager_program, rest) = Self::unpack_pubkey_option(rest)?;
+				let (quantize_start_to, rest) = Self::unpack_i64_option(rest)?;
+				let (allow_past_start, rest) = Self::unpack_bool(rest)?;
+				let (past_start_tolerance, rest) = Self::unpack_i64(rest)?;
+				let (pool_total, rest) = Self::unpack_u64(rest)?;
+				let (cliff_bps, rest) = Self::unpack_u16(rest)?;
+				(
+					Self::InitVestingSchedule {
+						authority,
+						mint,
+						schedule,
+						start,
+						duration,
+						cliff,
+						vault,
+						anchor_to_creation,
+						identifier,
+						receipt_mint,
+						tip_lamports,
+						manager_program,
+						quantize_start_to,
+						allow_past_start,
+						past_start_tolerance,
+						pool_total,
+						cliff_bps,
+					},
+					rest,
+				)
+			}
+			InstructionTag::CreateAccount => {
+				let (owner, rest) = Self::unpack_pubkey(rest)?;
+				let (amount, rest) = Self::unpack_u64(rest)?;
+				let (immediate_bps, rest) = Self::unpack_u16(rest)?;
+				let (allow_post_completion, rest) = Self::unpack_bool(rest)?;
+				(
+					Self::CreateAccount {
+						owner,
+						amount,
+						immediate_bps,
+						allow_post_completion,
+					},
+					rest,
+				)
+			}
+			InstructionTag::AmendAmount => {
+				let (amount, rest) = Self::unpack_u64(rest)?;
+				(Self::AmendAmount { amount }, rest)
+			}
+			InstructionTag::AmendSchedule => {
+				let (start, rest) = Self::unpack_i64_option(rest)?;
+				let (schedule, rest) = Self::unpack_frequency_option(rest)?;
+				let (duration, rest) = Self::unpack_i64_option(rest)?;
+				let (force, rest) = Self::unpack_bool(rest)?;
+				(
+					Self::AmendSchedule {
+						start,
+						schedule,
+						duration,
+						force,
+					},
+					rest,
+				)
+			}
+			InstructionTag::Claim => {
+				let (expected_start, rest) = Self::unpack_i64_option(rest)?;
+				(Self::Claim { expected_start }, rest)
+			}
+			InstructionTag::CloseAccount => {
+				let (acknowledged_forfeit, rest) = Self::unpack_u64(rest)?;
+				(
+					Self::CloseAccount {
+						acknowledged_forfeit,
+					},
+					rest,
+				)
+			}
+			InstructionTag::CloseVestingSchedule => (Self::CloseVestingSchedule, rest),
+			InstructionTag::GetClaimableMany => (Self::GetClaimableMany, rest),
+			InstructionTag::ScheduleAmend => {
+				let (effective_at, rest) = Self::unpack_i64(rest)?;
+				let (start, rest) = Self::unpack_i64_option(rest)?;
+				let (schedule, rest) = Self::unpack_frequency_option(rest)?;
+				let (duration, rest) = Self::unpack_i64_option(rest)?;
+				(
+					Self::ScheduleAmend {
+						effective_at,
+						start,
+						schedule,
+						duration,
+					},
+					rest,
+				)
+			}
+			InstructionTag::CrankClaim => (Self::CrankClaim, rest),
+			InstructionTag::GetClaimable => (Self::GetClaimable, rest),
+			InstructionTag::SetAllowedDestination => {
+				let (allowed_destination, rest) = Self::unpack_pubkey_option(rest)?;
+				(
+					Self::SetAllowedDestination {
+						allowed_destination,
+					},
+					rest,
+				)
+			}
+			InstructionTag::ClaimTo => (Self::ClaimTo, rest),
+			InstructionTag::ClaimAndUnwrap => (Self::ClaimAndUnwrap, rest),
+			InstructionTag::CreateAccountByBps => {
+				let (owner, rest) = Self::unpack_pubkey(rest)?;
+				let (bps, rest) = Self::unpack_u16(rest)?;
+				(Self::CreateAccountByBps { owner, bps }, rest)
+			}
+			InstructionTag::SetLabel => {
+				let (label, rest) = Self::unpack_label(rest)?;
+				(Self::SetLabel { label }, rest)
+			}
+			InstructionTag::ReassignRentPayer => {
+				let (new_payer, rest) = Self::unpack_pubkey(rest)?;
+				(Self::ReassignRentPayer { new_payer }, rest)
+			}
+			InstructionTag::AdjustClaimed => {
+				let (delta, rest) = Self::unpack_i64(rest)?;
+				(Self::AdjustClaimed { delta }, rest)
+			}
+			InstructionTag::SetClaimWindow => {
+				let (max_claim_per_window, rest) = Self::unpack_u64(rest)?;
+				let (window_seconds, rest) = Self::unpack_i64(rest)?;
+				(
+					Self::SetClaimWindow {
+						max_claim_per_window,
+						window_seconds,
+					},
+					rest,
+				)
+			}
+			InstructionTag::GetUiContext => (Self::GetUiContext, rest),
+			InstructionTag::LockAccount => (Self::LockAccount, rest),
+			InstructionTag::UnlockAccount => (Self::UnlockAccount, rest),
+			InstructionTag::SampleCurve => {
+				let (start_ts, rest) = Self::unpack_i64(rest)?;
+				let (end_ts, rest) = Self::unpack_i64(rest)?;
+				let (points, rest) = Self::unpack_u16(rest)?;
+				(
+					Self::SampleCurve {
+						start_ts,
+						end_ts,
+						points,
+					},
+					rest,
+				)
+			}
+			InstructionTag::RedeemVoucher => {
+				let (amount, rest) = Self::unpack_u64(rest)?;
+				let (expiry, rest) = Self::unpack_i64(rest)?;
+				let (signature, rest) = Self::unpack_signature(rest)?;
+				(
+					Self::RedeemVoucher {
+						amount,
+						expiry,
+						signature,
+					},
+					rest,
+				)
+			}
+			InstructionTag::RenounceAmendAuthority => (Self::RenounceAmendAuthority, rest),
+			InstructionTag::SetFallback => {
+				let (fallback_owner, rest) = Self::unpack_pubkey_option(rest)?;
+				let (inactivity_seconds, rest) = Self::unpack_i64(rest)?;
+				(
+					Self::SetFallback {
+						fallback_owner,
+						inactivity_seconds,
+					},
+					rest,
+				)
+			}
+			InstructionTag::ClaimFallback => (Self::ClaimFallback, rest),
+			InstructionTag::InitByCount => {
+				let (authority, rest) = Self::unpack_pubkey(rest)?;
+				let (mint, rest) = Self::unpack_pubkey(rest)?;
+				let (schedule, rest) = Self::unpack_frequency(rest)?;
+				let (start, rest) = Self::unpack_i64(rest)?;
+				let (emissions, rest) = Self::unpack_u32(rest)?;
+				let (cliff, rest) = Self::unpack_i64(rest)?;
+				let (vault, rest) = Self::unpack_pubkey_option(rest)?;
+				let (anchor_to_creation, rest) = Self::unpack_bool(rest)?;
+				let (identifier, rest) = Self::unpack_identifier(rest)?;
+				let (receipt_mint, rest) = Self::unpack_pubkey_option(rest)?;
+				let (tip_lamports, rest) = Self::unpack_u64(rest)?;
+				let (manager_program, rest) = Self::unpack_pubkey_option(rest)?;
+				let (quantize_start_to, rest) = Self::unpack_i64_option(rest)?;
+				let (allow_past_start, rest) = Self::unpack_bool(rest)?;
+				let (past_start_tolerance, rest) = Self::unpack_i64(rest)?;
+				let (pool_total, rest) = Self::unpack_u64(rest)?;
+				let (cliff_bps, rest) = Self::unpack_u16(rest)?;
+				(
+					Self::InitByCount {
+						authority,
+						mint,
+						schedule,
+						start,
+						emissions,
+						cliff,
+						vault,
+						anchor_to_creation,
+						identifier,
+						receipt_mint,
+						tip_lamports,
+						manager_program,
+						quantize_start_to,
+						allow_past_start,
+						past_start_tolerance,
+						pool_total,
+						cliff_bps,
+					},
+					rest,
+				)
+			}
+			InstructionTag::Forfeit => (Self::Forfeit, rest),
+			InstructionTag::RotateVault => (Self::RotateVault, rest),
+			InstructionTag::GetSupportedInstructions => (Self::GetSupportedInstructions, rest),
+			InstructionTag::MigrateMint => {
+				let (new_mint, rest) = Self::unpack_pubkey(rest)?;
+				let (ratio_num, rest) = Self::unpack_u64(rest)?;
+				let (ratio_den, rest) = Self::unpack_u64(rest)?;
+				(
+					Self::MigrateMint {
+						new_mint,
+						ratio_num,
+						ratio_den,
+					},
+					rest,
+				)
+			}
+			InstructionTag::MigrateAccountMint => (Self::MigrateAccountMint, rest),
+			InstructionTag::PauseAccount => (Self::PauseAccount, rest),
+			InstructionTag::UnpauseAccount => (Self::UnpauseAccount, rest),
+		};
+		if !rest.is_empty() {
+			return Err(InvalidInstruction.into());
+		}
+		Ok(instruction)
+	}
+
+	/// Packs a [VestingInstruction](enum.VestingInstruction.html) into a byte buffer
+	pub fn pack(&self) -> Vec<u8> {
+		let mut buf = Vec::with_capacity(INSTRUCTION_PREFIX.len() + TAG_LEN + size_of::<Self>());
+		buf.extend_from_slice(INSTRUCTION_PREFIX);
+		Self::pack_tag(self.tag(), &mut buf);
+		match *self {
+			Self::InitVestingSchedule {
+				ref authority,
+				ref mint,
+				schedule,
+				start,
+				duration,
+				cliff,
+				ref vault,
+				anchor_to_creation,
+				ref identifier,
+				ref receipt_mint,
+				tip_lamports,
+				ref manager_program,
+				quantize_start_to,
+				allow_past_start,
+				past_start_tolerance,
+				pool_total,
+				cliff_bps,
+			} => {
+				buf.extend_from_slice(&authority.to_bytes());
+				buf.extend_from_slice(&mint.to_bytes());
+				buf.push(schedule as u8);
+				buf.extend_from_slice(&start.to_le_bytes());
+				buf.extend_from_slice(&duration.to_le_bytes());
+				buf.extend_from_slice(&cliff.to_le_bytes());
+				Self::pack_pubkey_option(vault, &mut buf);
+				buf.push(anchor_to_creation as u8);
+				buf.extend_from_slice(identifier);
+				Self::pack_pubkey_option(receipt_mint, &mut buf);
+				buf.extend_from_slice(&tip_lamports.to_le_bytes());
+				Self::pack_pubkey_option(manager_program, &mut buf);
+				Self::pack_i64_option(&quantize_start_to, &mut buf);
+				buf.push(allow_past_start as u8);
+				buf.extend_from_slice(&past_start_tolerance.to_le_bytes());
+				buf.extend_from_slice(&pool_total.to_le_bytes());
+				buf.extend_from_slice(&cliff_bps.to_le_bytes());
+			}
+			Self::CreateAccount {
+				ref owner,
+				amount,
+				immediate_bps,
+				allow_post_completion,
+			} => {
+				buf.extend_from_slice(&owner.to_bytes());
+				buf.extend_from_slice(&amount.to_le_bytes());
+				buf.extend_from_slice(&immediate_bps.to_le_bytes());
+				buf.push(allow_post_completion as u8);
+			}
+			Self::AmendAmount { amount } => {
+				buf.extend_from_slice(&amount.to_le_bytes());
+			}
+			Self::AmendSchedule {
+				start,
+				schedule,
+				duration,
+				force,
+			} => {
+				Self::pack_i64_option(&start, &mut buf);
+				Self::pack_frequency_option(&schedule, &mut buf);
+				Self::pack_i64_option(&duration, &mut buf);
+				buf.push(force as u8);
+			}
+			Self::Claim { ref expected_start } => {
+				Self::pack_i64_option(expected_start, &mut buf);
+			}
+			Self::CloseAccount {
+				acknowledged_forfeit,
+			} => {
+				buf.extend_from_slice(&acknowledged_forfeit.to_le_bytes());
+			}
+			Self::ScheduleAmend {
+				effective_at,
+				start,
+				schedule,
+				duration,
+			} => {
+				buf.extend_from_slice(&effective_at.to_le_bytes());
+				Self::pack_i64_option(&start, &mut buf);
+				Self::pack_frequency_option(&schedule, &mut buf);
+				Self::pack_i64_option(&duration, &mut buf);
+			}
+			Self::SetAllowedDestination {
+				ref allowed_destination,
+			} => {
+				Self::pack_pubkey_option(allowed_destination, &mut buf);
+			}
+			Self::CreateAccountByBps { ref owner, bps } => {
+				buf.extend_from_slice(&owner.to_bytes());
+				buf.extend_from_slice(&bps.to_le_bytes());
+			}
+			Self::SetLabel { ref label } => {
+				buf.extend_from_slice(label);
+			}
+			Self::ReassignRentPayer { ref new_payer } => {
+				buf.extend_from_slice(&new_payer.to_bytes());
+			}
+			Self::AdjustClaimed { delta } => {
+				buf.extend_from_slice(&delta.to_le_bytes());
+			}
+			Self::MigrateMint {
+				ref new_mint,
+				ratio_num,
+				ratio_den,
+			} => {
+				buf.extend_from_slice(&new_mint.to_bytes());
+				buf.extend_from_slice(&ratio_num.to_le_bytes());
+				buf.extend_from_slice(&ratio_den.to_le_bytes());
+			}
+			Self::SetClaimWindow {
+				max_claim_per_window,
+				window_seconds,
+			} => {
+				buf.extend_from_slice(&max_claim_per_window.to_le_bytes());
+				buf.extend_from_slice(&window_seconds.to_le_bytes());
+			}
+			Self::SampleCurve {
+				start_ts,
+				end_ts,
+				points,
+			} => {
+				buf.extend_from_slice(&start_ts.to_le_bytes());
+				buf.extend_from_slice(&end_ts.to_le_bytes());
+				buf.extend_from_slice(&points.to_le_bytes());
+			}
+			Self::RedeemVoucher {
+				amount,
+				expiry,
+				ref signature,
+			} => {
+				buf.extend_from_slice(&amount.to_le_bytes());
+				buf.extend_from_slice(&expiry.to_le_bytes());
+				buf.extend_from_slice(signature);
+			}
+			Self::SetFallback {
+				ref fallback_owner,
+				inactivity_seconds,
+			} => {
+				Self::pack_pubkey_option(fallback_owner, &mut buf);
+				buf.extend_from_slice(&inactivity_seconds.to_le_bytes());
+			}
+			Self::InitByCount {
+				ref authority,
+				ref mint,
+				schedule,
+				start,
+				emissions,
+				cliff,
+				ref vault,
+				anchor_to_creation,
+				ref identifier,
+				ref receipt_mint,
+				tip_lamports,
+				ref manager_program,
+				quantize_start_to,
+				allow_past_start,
+				past_start_tolerance,
+				pool_total,
+				cliff_bps,
+			} => {
+				buf.extend_from_slice(&authority.to_bytes());
+				buf.extend_from_slice(&mint.to_bytes());
+				buf.push(schedule as u8);
+				buf.extend_from_slice(&start.to_le_bytes());
+				buf.extend_from_slice(&emissions.to_le_bytes());
+				buf.extend_from_slice(&cliff.to_le_bytes());
+				Self::pack_pubkey_option(vault, &mut buf);
+				buf.push(anchor_to_creation as u8);
+				buf.extend_from_slice(identifier);
+				Self::pack_pubkey_option(receipt_mint, &mut buf);
+				buf.extend_from_slice(&tip_lamports.to_le_bytes());
+				Self::pack_pubkey_option(manager_program, &mut buf);
+				Self::pack_i64_option(&quantize_start_to, &mut buf);
+				buf.push(allow_past_start as u8);
+				buf.extend_from_slice(&past_start_tolerance.to_le_bytes());
+				buf.extend_from_slice(&pool_total.to_le_bytes());
+				buf.extend_from_slice(&cliff_bps.to_le_bytes());
+			}
+			Self::CloseVestingSchedule
+			| Self::GetClaimableMany
+			| Self::CrankClaim
+			| Self::GetClaimable
+			| Self::ClaimTo
+			| Self::ClaimAndUnwrap
+			| Self::GetUiContext
+			| Self::LockAccount
+			| Self::UnlockAccount
+			| Self::RenounceAmendAuthority
+			| Self::ClaimFallback
+			| Self::Forfeit
+			| Self::RotateVault
+			| Self::GetSupportedInstructions
+			| Self::MigrateAccountMint
+			| Self::PauseAccount
+			| Self::UnpauseAccount => {}
+		};
+		buf
+	}
+
+	fn unpack_tag(input: &[u8]) -> Result<(InstructionTag, &[u8]), ProgramError> {
+		let (tag, rest) = if cfg!(feature = "anchor-compat") {
+			let discriminator = input
+				.get(..ANCHOR_DISCRIMINATOR_LEN)
+				.ok_or(VestingError::InvalidInstruction)?;
+			let tag = ANCHOR_DISCRIMINATORS
+				.iter()
+				.position(|(_, known)| known == discriminator)
+				.ok_or(VestingError::InvalidInstruction)?;
+			(tag as u8, &input[ANCHOR_DISCRIMINATOR_LEN..])
+		} else {
+			let (&tag, rest) = input
+				.split_first()
+				.ok_or(VestingError::InvalidInstruction)?;
+			(tag, rest)
+		};
+		let tag = InstructionTag::try_from(tag).map_err(|_| VestingError::InvalidInstruction)?;
+		Ok((tag, rest))
+	}
+
+	fn pack_tag(tag: InstructionTag, buf: &mut Vec<u8>) {
+		if cfg!(feature = "anchor-compat") {
+			buf.extend_from_slice(&ANCHOR_DISCRIMINATORS[tag as usize].1);
+		} else {
+			buf.push(tag as u8);
+		}
+	}
+
+	pub(crate) fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+		let pk = input
+			.get(..PUBKEY_BYTES)
+			.and_then(|x| Pubkey::try_from(x).ok())
+			.ok_or(VestingError::InvalidInstruction)?;
+		Ok((pk, &input[PUBKEY_BYTES..]))
+	}
+
+	pub(crate) fn unpack_pubkey_option(
+		input: &[u8],
+	) -> Result<(COption<Pubkey>, &[u8]), ProgramError> {
+		match input.split_first() {
+			Option::Some((&0, rest)) => Ok((COption::None, rest)),
+			Option::Some((&1, rest)) => {
+				let (pk, rest) = Self::unpack_pubkey(rest)?;
+				Ok((COption::Some(pk), rest))
+			}
+			_ => Err(VestingError::InvalidInstruction.into()),
+		}
+	}
+
+	pub(crate) fn pack_pubkey_option(value: &COption<Pubkey>, buf: &mut Vec<u8>) {
+		match *value {
+			COption::Some(ref key) => {
+				buf.push(1);
+				buf.extend_from_slice(&key.to_bytes());
+			}
+			COption::None => buf.push(0),
+		}
+	}
+
+	pub(crate) fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+		let value = input
+			.get(..BYTES_16)
+			.and_then(|slice| slice.try_into().ok())
+			.map(u16::from_le_bytes)
+			.ok_or(VestingError::InvalidInstruction)?;
+		Ok((value, &input[BYTES_16..]))
+	}
+
+	pub(crate) fn unpack_u32(input: &[u8]) -> Result<(u32, &[u8]), ProgramError> {
+		let value = input
+			.get(..BYTES_32)
+			.and_then(|slice| slice.try_into().ok())
+			.map(u32::from_le_bytes)
+			.ok_or(VestingError::InvalidInstruction)?;
+		Ok((value, &input[BYTES_32..]))
+	}
+
+	pub(crate) fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+		let value = input
 			.get(..BYTES_64)
 			.and_then(|slice| slice.try_into().ok())
 			.map(u64::from_le_bytes)
@@ -182,4 +1395,1411 @@ impl<'a> VestingInstruction<'a> {
 			.ok_or(VestingError::InvalidInstruction)?;
 		Ok((value, &input[BYTES_64..]))
 	}
+
+	pub(crate) fn unpack_bool(input: &[u8]) -> Result<(bool, &[u8]), ProgramError> {
+		match input.split_first() {
+			Option::Some((&0, rest)) => Ok((false, rest)),
+			Option::Some((&1, rest)) => Ok((true, rest)),
+			_ => Err(VestingError::InvalidInstruction.into()),
+		}
+	}
+
+	pub(crate) fn unpack_identifier(
+		input: &[u8],
+	) -> Result<([u8; IDENTIFIER_LEN], &[u8]), ProgramError> {
+		let identifier = input
+			.get(..IDENTIFIER_LEN)
+			.and_then(|slice| slice.try_into().ok())
+			.ok_or(VestingError::InvalidInstruction)?;
+		Ok((identifier, &input[IDENTIFIER_LEN..]))
+	}
+
+	pub(crate) fn unpack_signature(
+		input: &[u8],
+	) -> Result<([u8; SIGNATURE_BYTES], &[u8]), ProgramError> {
+		let signature = input
+			.get(..SIGNATURE_BYTES)
+			.and_then(|slice| slice.try_into().ok())
+			.ok_or(VestingError::InvalidInstruction)?;
+		Ok((signature, &input[SIGNATURE_BYTES..]))
+	}
+
+	pub(crate) fn unpack_label(input: &[u8]) -> Result<([u8; LABEL_LEN], &[u8]), ProgramError> {
+		let label: [u8; LABEL_LEN] = input
+			.get(..LABEL_LEN)
+			.and_then(|slice| slice.try_into().ok())
+			.filter(|label: &[u8; LABEL_LEN]| std::str::from_utf8(label).is_ok())
+			.ok_or(VestingError::InvalidInstruction)?;
+		Ok((label, &input[LABEL_LEN..]))
+	}
+
+	pub(crate) fn unpack_i64_option(input: &[u8]) -> Result<(Option<i64>, &[u8]), ProgramError> {
+		match input.split_first() {
+			Option::Some((&0, rest)) => Ok((Option::None, rest)),
+			Option::Some((&1, rest)) => {
+				let (value, rest) = Self::unpack_i64(rest)?;
+				Ok((Option::Some(value), rest))
+			}
+			_ => Err(VestingError::InvalidInstruction.into()),
+		}
+	}
+
+	pub(crate) fn pack_i64_option(value: &Option<i64>, buf: &mut Vec<u8>) {
+		match *value {
+			Option::Some(value) => {
+				buf.push(1);
+				buf.extend_from_slice(&value.to_le_bytes());
+			}
+			Option::None => buf.push(0),
+		}
+	}
+
+	pub(crate) fn unpack_frequency(input: &[u8]) -> Result<(Frequency, &[u8]), ProgramError> {
+		let (&value, rest) = input
+			.split_first()
+			.ok_or(VestingError::InvalidInstruction)?;
+		let frequency =
+			Frequency::try_from_primitive(value).or(Err(VestingError::InvalidInstruction))?;
+		Ok((frequency, rest))
+	}
+
+	pub(crate) fn unpack_frequency_option(
+		input: &[u8],
+	) -> Result<(Option<Frequency>, &[u8]), ProgramError> {
+		match input.split_first() {
+			Option::Some((&0, rest)) => Ok((Option::None, rest)),
+			Option::Some((&1, rest)) => {
+				let (frequency, rest) = Self::unpack_frequency(rest)?;
+				Ok((Option::Some(frequency), rest))
+			}
+			_ => Err(VestingError::InvalidInstruction.into()),
+		}
+	}
+
+	pub(crate) fn pack_frequency_option(value: &Option<Frequency>, buf: &mut Vec<u8>) {
+		match *value {
+			Option::Some(frequency) => {
+				buf.push(1);
+				buf.push(frequency as u8);
+			}
+			Option::None => buf.push(0),
+		}
+	}
+}
+
+/// Creates an `InitVestingSchedule` instruction for the schedule derived from `mint` and
+/// `identifier`, the vault accounts are only included if `vault` is set
+#[allow(clippy::too_many_arguments)]
+pub fn init_vesting_schedule(
+	program_id: &Pubkey,
+	identifier: &str,
+	payer: &Pubkey,
+	authority: &Pubkey,
+	mint: &Pubkey,
+	schedule: Frequency,
+	start: UnixTimestamp,
+	duration: i64,
+	cliff: i64,
+	vault: COption<Pubkey>,
+	anchor_to_creation: bool,
+	receipt_mint: COption<Pubkey>,
+	tip_lamports: u64,
+	manager_program: COption<Pubkey>,
+	quantize_start_to: Option<i64>,
+	allow_past_start: bool,
+	past_start_tolerance: i64,
+	pool_total: u64,
+	cliff_bps: u16,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let identifier = hash_identifier(identifier);
+	let (vesting_schedule, _) =
+		find_vesting_schedule_address_from_hash(program_id, mint, &identifier);
+	let data = VestingInstruction::InitVestingSchedule {
+		authority: *authority,
+		mint: *mint,
+		schedule,
+		start,
+		duration,
+		cliff,
+		vault,
+		anchor_to_creation,
+		identifier,
+		receipt_mint,
+		tip_lamports,
+		manager_program,
+		quantize_start_to,
+		allow_past_start,
+		past_start_tolerance,
+		pool_total,
+		cliff_bps,
+	}
+	.pack();
+
+	let mut accounts = vec![
+		AccountMeta::new(vesting_schedule, false),
+		AccountMeta::new(*payer, true),
+		AccountMeta::new_readonly(system_program::id(), false),
+		AccountMeta::new_readonly(*mint, false),
+	];
+	if let COption::Some(vault) = vault {
+		accounts.push(AccountMeta::new(vault, false));
+		accounts.push(AccountMeta::new_readonly(*token_program_id, false));
+		accounts.push(AccountMeta::new_readonly(
+			spl_associated_token_account::id(),
+			false,
+		));
+	}
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates an `InitByCount` instruction, see [`init_vesting_schedule`]
+#[allow(clippy::too_many_arguments)]
+pub fn init_by_count(
+	program_id: &Pubkey,
+	identifier: &str,
+	payer: &Pubkey,
+	authority: &Pubkey,
+	mint: &Pubkey,
+	schedule: Frequency,
+	start: UnixTimestamp,
+	emissions: u32,
+	cliff: i64,
+	vault: COption<Pubkey>,
+	anchor_to_creation: bool,
+	receipt_mint: COption<Pubkey>,
+	tip_lamports: u64,
+	manager_program: COption<Pubkey>,
+	quantize_start_to: Option<i64>,
+	allow_past_start: bool,
+	past_start_tolerance: i64,
+	pool_total: u64,
+	cliff_bps: u16,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = init_vesting_schedule(
+		program_id,
+		identifier,
+		payer,
+		authority,
+		mint,
+		schedule,
+		start,
+		0,
+		cliff,
+		vault,
+		anchor_to_creation,
+		receipt_mint,
+		tip_lamports,
+		manager_program,
+		quantize_start_to,
+		allow_past_start,
+		past_start_tolerance,
+		pool_total,
+		cliff_bps,
+		token_program_id,
+	);
+	instruction.data = VestingInstruction::InitByCount {
+		authority: *authority,
+		mint: *mint,
+		schedule,
+		start,
+		emissions,
+		cliff,
+		vault,
+		anchor_to_creation,
+		identifier: hash_identifier(identifier),
+		receipt_mint,
+		tip_lamports,
+		manager_program,
+		quantize_start_to,
+		allow_past_start,
+		past_start_tolerance,
+		pool_total,
+		cliff_bps,
+	}
+	.pack();
+	instruction
+}
+
+/// Creates a `CreateAccount` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn create_account(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	owner: &Pubkey,
+	amount: u64,
+	immediate_bps: u16,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::CreateAccount {
+		owner: *owner,
+		amount,
+		immediate_bps,
+		allow_post_completion: false,
+	}
+	.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*vesting_schedule, false),
+		AccountMeta::new(*authority, true),
+		AccountMeta::new_readonly(*mint, false),
+		AccountMeta::new(*vesting_account, false),
+		AccountMeta::new(*vesting_account_ata, false),
+		AccountMeta::new_readonly(system_program::id(), false),
+		AccountMeta::new_readonly(*token_program_id, false),
+		AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `CreateAccount` instruction that may create an account on a schedule that has
+/// already fully vested
+#[allow(clippy::too_many_arguments)]
+pub fn create_account_after_completion(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	owner: &Pubkey,
+	amount: u64,
+	immediate_bps: u16,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = create_account(
+		program_id,
+		vesting_schedule,
+		authority,
+		mint,
+		vesting_account,
+		vesting_account_ata,
+		owner,
+		amount,
+		immediate_bps,
+		token_program_id,
+	);
+	instruction.data = VestingInstruction::CreateAccount {
+		owner: *owner,
+		amount,
+		immediate_bps,
+		allow_post_completion: true,
+	}
+	.pack();
+	instruction
+}
+
+/// Creates a `CreateAccountByBps` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn create_account_by_bps(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	owner: &Pubkey,
+	bps: u16,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = create_account(
+		program_id,
+		vesting_schedule,
+		authority,
+		mint,
+		vesting_account,
+		vesting_account_ata,
+		owner,
+		0,
+		0,
+		token_program_id,
+	);
+	instruction.data = VestingInstruction::CreateAccountByBps { owner: *owner, bps }.pack();
+	instruction.accounts[0].is_writable = true;
+	instruction
+}
+
+/// Creates an `AmendAmount` instruction, `vesting_account_ata` is the schedule's vault if it has
+/// one
+pub fn amend_amount(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	amount: u64,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::AmendAmount { amount }.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*vesting_schedule, false),
+		AccountMeta::new(*authority, true),
+		AccountMeta::new(*vesting_account, false),
+		AccountMeta::new_readonly(*vesting_account_ata, false),
+		AccountMeta::new_readonly(*token_program_id, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates an `AmendSchedule` instruction, fields left as `None` are not amended
+#[allow(clippy::too_many_arguments)]
+pub fn amend_schedule(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	start: Option<UnixTimestamp>,
+	schedule: Option<Frequency>,
+	duration: Option<i64>,
+	force: bool,
+) -> Instruction {
+	let data = VestingInstruction::AmendSchedule {
+		start,
+		schedule,
+		duration,
+		force,
+	}
+	.pack();
+
+	let accounts = vec![
+		AccountMeta::new(*vesting_schedule, false),
+		AccountMeta::new(*authority, true),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `ScheduleAmend` instruction taking effect at `effective_at`, fields left as `None`
+/// are not amended
+pub fn schedule_amend(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	effective_at: UnixTimestamp,
+	start: Option<UnixTimestamp>,
+	schedule: Option<Frequency>,
+	duration: Option<i64>,
+) -> Instruction {
+	let data = VestingInstruction::ScheduleAmend {
+		effective_at,
+		start,
+		schedule,
+		duration,
+	}
+	.pack();
+
+	let accounts = vec![
+		AccountMeta::new(*vesting_schedule, false),
+		AccountMeta::new_readonly(*authority, true),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `Claim` instruction, `vesting_account_ata` is the schedule's vault if it has one
+#[allow(clippy::too_many_arguments)]
+pub fn claim(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	recipient: &Pubkey,
+	recipient_ata: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::Claim {
+		expected_start: None,
+	}
+	.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*vesting_schedule, false),
+		AccountMeta::new_readonly(*mint, false),
+		AccountMeta::new(*vesting_account, false),
+		AccountMeta::new(*vesting_account_ata, false),
+		AccountMeta::new(*recipient, true),
+		AccountMeta::new(*recipient_ata, false),
+		AccountMeta::new_readonly(system_program::id(), false),
+		AccountMeta::new_readonly(*token_program_id, false),
+		AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `Claim` instruction that fails unless the schedule's start is `expected_start`
+#[allow(clippy::too_many_arguments)]
+pub fn claim_expecting_start(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	recipient: &Pubkey,
+	recipient_ata: &Pubkey,
+	expected_start: i64,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = claim(
+		program_id,
+		vesting_schedule,
+		mint,
+		vesting_account,
+		vesting_account_ata,
+		recipient,
+		recipient_ata,
+		token_program_id,
+	);
+	instruction.data = VestingInstruction::Claim {
+		expected_start: Some(expected_start),
+	}
+	.pack();
+	instruction
+}
+
+/// Creates a `ClaimTo` instruction, sending the claimed tokens to `destination`
+#[allow(clippy::too_many_arguments)]
+pub fn claim_to(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	recipient: &Pubkey,
+	destination: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = claim(
+		program_id,
+		vesting_schedule,
+		mint,
+		vesting_account,
+		vesting_account_ata,
+		recipient,
+		destination,
+		token_program_id,
+	);
+	instruction.data = VestingInstruction::ClaimTo.pack();
+	instruction
+}
+
+/// Creates a `ClaimAndUnwrap` instruction, `recipient_ata` is the recipient's ATA for the native
+/// mint
+#[allow(clippy::too_many_arguments)]
+pub fn claim_and_unwrap(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	recipient: &Pubkey,
+	recipient_ata: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = claim(
+		program_id,
+		vesting_schedule,
+		mint,
+		vesting_account,
+		vesting_account_ata,
+		recipient,
+		recipient_ata,
+		token_program_id,
+	);
+	instruction.data = VestingInstruction::ClaimAndUnwrap.pack();
+	instruction
+}
+
+/// Creates a `ClaimFallback` instruction, `vesting_account` is still the owner's
+#[allow(clippy::too_many_arguments)]
+pub fn claim_fallback(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	fallback_owner: &Pubkey,
+	fallback_owner_ata: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = claim(
+		program_id,
+		vesting_schedule,
+		mint,
+		vesting_account,
+		vesting_account_ata,
+		fallback_owner,
+		fallback_owner_ata,
+		token_program_id,
+	);
+	instruction.data = VestingInstruction::ClaimFallback.pack();
+	instruction
+}
+
+/// Creates a `Claim` instruction for a schedule with a receipt mint, the receipt is minted to
+/// the recipient's ATA for `receipt_mint`
+#[allow(clippy::too_many_arguments)]
+pub fn claim_with_receipt(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	recipient: &Pubkey,
+	recipient_ata: &Pubkey,
+	receipt_mint: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = claim(
+		program_id,
+		vesting_schedule,
+		mint,
+		vesting_account,
+		vesting_account_ata,
+		recipient,
+		recipient_ata,
+		token_program_id,
+	);
+	instruction.accounts.extend([
+		AccountMeta::new(*receipt_mint, false),
+		AccountMeta::new(
+			spl_associated_token_account::get_associated_token_address_with_program_id(
+				recipient,
+				receipt_mint,
+				token_program_id,
+			),
+			false,
+		),
+	]);
+	instruction
+}
+
+/// Creates a `Claim` instruction for a schedule with a manager program, the claimed tokens are
+/// sent to `manager_token_account`
+#[allow(clippy::too_many_arguments)]
+pub fn claim_with_manager(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	recipient: &Pubkey,
+	recipient_ata: &Pubkey,
+	manager_program: &Pubkey,
+	manager_account: &Pubkey,
+	manager_token_account: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = claim(
+		program_id,
+		vesting_schedule,
+		mint,
+		vesting_account,
+		vesting_account_ata,
+		recipient,
+		recipient_ata,
+		token_program_id,
+	);
+	instruction.accounts.extend([
+		AccountMeta::new_readonly(*manager_program, false),
+		AccountMeta::new(*manager_account, false),
+		AccountMeta::new(*manager_token_account, false),
+	]);
+	instruction
+}
+
+/// Creates a `CrankClaim` instruction, claiming for `recipient` with `cranker` as the signer
+#[allow(clippy::too_many_arguments)]
+pub fn crank_claim(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	recipient: &Pubkey,
+	recipient_ata: &Pubkey,
+	cranker: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::CrankClaim.pack();
+
+	let accounts = vec![
+		AccountMeta::new(*vesting_schedule, false),
+		AccountMeta::new_readonly(*mint, false),
+		AccountMeta::new(*vesting_account, false),
+		AccountMeta::new(*vesting_account_ata, false),
+		AccountMeta::new_readonly(*recipient, false),
+		AccountMeta::new(*recipient_ata, false),
+		AccountMeta::new_readonly(system_program::id(), false),
+		AccountMeta::new_readonly(*token_program_id, false),
+		AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+		AccountMeta::new(*cranker, true),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `CloseAccount` instruction, `vesting_account_ata` is the schedule's vault if it has
+/// one
+#[allow(clippy::too_many_arguments)]
+pub fn close_account(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	destination: &Pubkey,
+	acknowledged_forfeit: u64,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::CloseAccount {
+		acknowledged_forfeit,
+	}
+	.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*vesting_schedule, false),
+		AccountMeta::new(*authority, true),
+		AccountMeta::new_readonly(*mint, false),
+		AccountMeta::new(*vesting_account, false),
+		AccountMeta::new(*vesting_account_ata, false),
+		AccountMeta::new(*destination, false),
+		AccountMeta::new_readonly(*token_program_id, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `CloseAccount` instruction for an account whose rent payer has been reassigned
+/// from the authority, refunding its rent to `rent_payer`
+#[allow(clippy::too_many_arguments)]
+pub fn close_account_with_rent_payer(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	destination: &Pubkey,
+	acknowledged_forfeit: u64,
+	rent_payer: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = close_account(
+		program_id,
+		vesting_schedule,
+		authority,
+		mint,
+		vesting_account,
+		vesting_account_ata,
+		destination,
+		acknowledged_forfeit,
+		token_program_id,
+	);
+	instruction
+		.accounts
+		.push(AccountMeta::new(*rent_payer, false));
+	instruction
+}
+
+/// Creates a `Forfeit` instruction, `vesting_account_ata` is the schedule's vault if it has one
+#[allow(clippy::too_many_arguments)]
+pub fn forfeit(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	owner: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	destination: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::Forfeit.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*vesting_schedule, false),
+		AccountMeta::new_readonly(*owner, true),
+		AccountMeta::new_readonly(*mint, false),
+		AccountMeta::new(*vesting_account, false),
+		AccountMeta::new(*vesting_account_ata, false),
+		AccountMeta::new(*destination, false),
+		AccountMeta::new_readonly(*token_program_id, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `RotateVault` instruction, moving the schedule's tokens from `vault` to the address
+/// derived from it by [`crate::find_vault_address`]
+pub fn rotate_vault(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	payer: &Pubkey,
+	mint: &Pubkey,
+	vault: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::RotateVault.pack();
+	let (new_vault, _) = find_vault_address(program_id, vesting_schedule, vault);
+
+	let accounts = vec![
+		AccountMeta::new(*vesting_schedule, false),
+		AccountMeta::new(*authority, true),
+		AccountMeta::new(*payer, true),
+		AccountMeta::new_readonly(*mint, false),
+		AccountMeta::new(*vault, false),
+		AccountMeta::new(new_vault, false),
+		AccountMeta::new_readonly(system_program::id(), false),
+		AccountMeta::new_readonly(*token_program_id, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `CloseVestingSchedule` instruction
+pub fn close_vesting_schedule(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	vault: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::CloseVestingSchedule.pack();
+
+	let accounts = vec![
+		AccountMeta::new(*vesting_schedule, false),
+		AccountMeta::new(*authority, true),
+		AccountMeta::new_readonly(system_program::id(), false),
+		AccountMeta::new(*vault, false),
+		AccountMeta::new_readonly(*token_program_id, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `GetClaimableMany` instruction, meant to be simulated rather than submitted
+pub fn get_claimable_many(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	vesting_accounts: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+	if vesting_accounts.len() > MAX_CLAIMABLE_QUERY_ACCOUNTS {
+		return Err(VestingError::TooManyAccounts.into());
+	}
+	let data = VestingInstruction::GetClaimableMany.pack();
+
+	let mut accounts = Vec::with_capacity(1 + vesting_accounts.len());
+	accounts.push(AccountMeta::new_readonly(*vesting_schedule, false));
+	for vesting_account in vesting_accounts {
+		accounts.push(AccountMeta::new_readonly(*vesting_account, false));
+	}
+
+	Ok(Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	})
+}
+
+/// Batch instructions whose capacity [`max_batch_items`] estimates
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BatchKind {
+	/// [`VestingInstruction::GetClaimableMany`], one vesting account per item
+	GetClaimableMany,
+}
+
+/// Estimates how many items a batch instruction of `ix_kind` can carry in a transaction signed
+/// only by its fee payer, within the transaction size and account limits, so clients can chunk
+/// batches up front. `has_vault` accounts for batches that also pass the schedule's vault,
+/// `GetClaimableMany` doesn't so it makes no difference there.
+pub fn max_batch_items(ix_kind: BatchKind, has_vault: bool) -> usize {
+	// Accounts passed alongside the items, whether the vault is among them when the schedule
+	// has one, the data length and the most items the instruction accepts
+	let (accounts, passes_vault, data_len, max_items) = match ix_kind {
+		BatchKind::GetClaimableMany => (
+			1,
+			false,
+			INSTRUCTION_PREFIX.len() + TAG_LEN,
+			MAX_CLAIMABLE_QUERY_ACCOUNTS,
+		),
+	};
+	let accounts = accounts + usize::from(passes_vault && has_vault);
+	// Fee payer and program id besides the instruction's own accounts
+	let keys = accounts + 2;
+	// Signature count and signature, message header, blockhash, instruction count and program id
+	// index, then the three compact-u16 lengths of the keys, account indices and data at their
+	// widest, and the fixed keys and account indices
+	let fixed = 1
+		+ SIGNATURE_BYTES
+		+ 3 + PUBKEY_BYTES
+		+ 2 + 3 * 3
+		+ data_len
+		+ keys * PUBKEY_BYTES
+		+ accounts;
+	// Each item adds a key and its index
+	let by_size = (PACKET_DATA_SIZE - fixed) / (PUBKEY_BYTES + 1);
+	by_size.min(MAX_TX_ACCOUNT_LOCKS - keys).min(max_items)
+}
+
+/// Creates a `GetClaimable` instruction, meant to be simulated rather than submitted
+pub fn get_claimable(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	vesting_account: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::GetClaimable.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*vesting_schedule, false),
+		AccountMeta::new_readonly(*vesting_account, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `GetUiContext` instruction
+pub fn get_ui_context(program_id: &Pubkey, vesting_schedule: &Pubkey) -> Instruction {
+	let data = VestingInstruction::GetUiContext.pack();
+
+	let accounts = vec![AccountMeta::new_readonly(*vesting_schedule, false)];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `GetSupportedInstructions` instruction
+pub fn get_supported_instructions(program_id: &Pubkey) -> Instruction {
+	let data = VestingInstruction::GetSupportedInstructions.pack();
+
+	Instruction {
+		program_id: *program_id,
+		accounts: vec![],
+		data,
+	}
+}
+
+/// Message the schedule's authority signs to let `owner` create their own vesting account for
+/// `amount` with `RedeemVoucher` until `expiry`
+pub fn voucher_message(
+	vesting_schedule: &Pubkey,
+	owner: &Pubkey,
+	amount: u64,
+	expiry: UnixTimestamp,
+) -> Vec<u8> {
+	[
+		VOUCHER_DOMAIN,
+		vesting_schedule.as_ref(),
+		owner.as_ref(),
+		&amount.to_le_bytes(),
+		&expiry.to_le_bytes(),
+	]
+	.concat()
+}
+
+/// Creates a `RedeemVoucher` instruction, to follow the ed25519 program instruction verifying
+/// `signature` over [`voucher_message`]
+#[allow(clippy::too_many_arguments)]
+pub fn redeem_voucher(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	owner: &Pubkey,
+	mint: &Pubkey,
+	vesting_account: &Pubkey,
+	vesting_account_ata: &Pubkey,
+	amount: u64,
+	expiry: UnixTimestamp,
+	signature: [u8; SIGNATURE_BYTES],
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = create_account(
+		program_id,
+		vesting_schedule,
+		owner,
+		mint,
+		vesting_account,
+		vesting_account_ata,
+		owner,
+		amount,
+		0,
+		token_program_id,
+	);
+	instruction.data = VestingInstruction::RedeemVoucher {
+		amount,
+		expiry,
+		signature,
+	}
+	.pack();
+	instruction
+		.accounts
+		.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+	instruction
+}
+
+/// Creates a `SampleCurve` instruction
+pub fn sample_curve(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	vesting_account: &Pubkey,
+	start_ts: UnixTimestamp,
+	end_ts: UnixTimestamp,
+	points: u16,
+) -> Instruction {
+	let mut instruction = get_claimable(program_id, vesting_schedule, vesting_account);
+	instruction.data = VestingInstruction::SampleCurve {
+		start_ts,
+		end_ts,
+		points,
+	}
+	.pack();
+	instruction
+}
+
+/// Creates a `LockAccount` instruction
+pub fn lock_account(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	vesting_account: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::LockAccount.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*vesting_schedule, false),
+		AccountMeta::new_readonly(*authority, true),
+		AccountMeta::new(*vesting_account, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates an `UnlockAccount` instruction
+pub fn unlock_account(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	vesting_account: &Pubkey,
+) -> Instruction {
+	let mut instruction = lock_account(program_id, vesting_schedule, authority, vesting_account);
+	instruction.data = VestingInstruction::UnlockAccount.pack();
+	instruction
+}
+
+/// Creates a `PauseAccount` instruction
+pub fn pause_account(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	vesting_account: &Pubkey,
+) -> Instruction {
+	let mut instruction = lock_account(program_id, vesting_schedule, authority, vesting_account);
+	instruction.data = VestingInstruction::PauseAccount.pack();
+	instruction
+}
+
+/// Creates an `UnpauseAccount` instruction
+pub fn unpause_account(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	vesting_account: &Pubkey,
+) -> Instruction {
+	let mut instruction = lock_account(program_id, vesting_schedule, authority, vesting_account);
+	instruction.data = VestingInstruction::UnpauseAccount.pack();
+	instruction
+}
+
+/// Creates a `SetAllowedDestination` instruction, `None` lifts the restriction
+pub fn set_allowed_destination(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	vesting_account: &Pubkey,
+	allowed_destination: COption<Pubkey>,
+) -> Instruction {
+	let data = VestingInstruction::SetAllowedDestination {
+		allowed_destination,
+	}
+	.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*vesting_schedule, false),
+		AccountMeta::new_readonly(*authority, true),
+		AccountMeta::new(*vesting_account, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `SetLabel` instruction, see [`crate::pad_label`]
+pub fn set_label(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	label: [u8; LABEL_LEN],
+) -> Instruction {
+	let data = VestingInstruction::SetLabel { label }.pack();
+
+	let accounts = vec![
+		AccountMeta::new(*vesting_schedule, false),
+		AccountMeta::new_readonly(*authority, true),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `RenounceAmendAuthority` instruction
+pub fn renounce_amend_authority(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::RenounceAmendAuthority.pack();
+
+	let accounts = vec![
+		AccountMeta::new(*vesting_schedule, false),
+		AccountMeta::new_readonly(*authority, true),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates an `AdjustClaimed` instruction
+pub fn adjust_claimed(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	vesting_account: &Pubkey,
+	delta: i64,
+) -> Instruction {
+	let data = VestingInstruction::AdjustClaimed { delta }.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*vesting_schedule, false),
+		AccountMeta::new_readonly(*authority, true),
+		AccountMeta::new(*vesting_account, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `ReassignRentPayer` instruction
+pub fn reassign_rent_payer(
+	program_id: &Pubkey,
+	rent_payer: &Pubkey,
+	vesting_account: &Pubkey,
+	new_payer: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::ReassignRentPayer {
+		new_payer: *new_payer,
+	}
+	.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*rent_payer, true),
+		AccountMeta::new(*vesting_account, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `MigrateMint` instruction, `new_vault` is the schedule's ATA of `new_mint` if the
+/// schedule has a vault
+#[allow(clippy::too_many_arguments)]
+pub fn migrate_mint(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	new_mint: &Pubkey,
+	ratio_num: u64,
+	ratio_den: u64,
+) -> Instruction {
+	let data = VestingInstruction::MigrateMint {
+		new_mint: *new_mint,
+		ratio_num,
+		ratio_den,
+	}
+	.pack();
+
+	let accounts = vec![
+		AccountMeta::new(*vesting_schedule, false),
+		AccountMeta::new(*authority, true),
+		AccountMeta::new_readonly(*new_mint, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `MigrateMint` instruction for a schedule with a vault, sending the vault's tokens of
+/// `mint` to `destination`
+#[allow(clippy::too_many_arguments)]
+pub fn migrate_mint_with_vault(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	mint: &Pubkey,
+	vault: &Pubkey,
+	new_mint: &Pubkey,
+	destination: &Pubkey,
+	ratio_num: u64,
+	ratio_den: u64,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction = migrate_mint(
+		program_id,
+		vesting_schedule,
+		authority,
+		new_mint,
+		ratio_num,
+		ratio_den,
+	);
+	instruction.accounts.extend([
+		AccountMeta::new(
+			spl_associated_token_account::get_associated_token_address_with_program_id(
+				vesting_schedule,
+				new_mint,
+				token_program_id,
+			),
+			false,
+		),
+		AccountMeta::new(*vault, false),
+		AccountMeta::new_readonly(*mint, false),
+		AccountMeta::new(*destination, false),
+		AccountMeta::new_readonly(system_program::id(), false),
+		AccountMeta::new_readonly(*token_program_id, false),
+		AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+	]);
+	instruction
+}
+
+/// Creates a `MigrateAccountMint` instruction
+pub fn migrate_account_mint(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	vesting_account: &Pubkey,
+) -> Instruction {
+	let data = VestingInstruction::MigrateAccountMint.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*vesting_schedule, false),
+		AccountMeta::new_readonly(*authority, true),
+		AccountMeta::new(*vesting_account, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `MigrateAccountMint` instruction for a schedule without a vault, sending the
+/// vesting account's pre-loaded tokens of `previous_mint` to `destination`
+#[allow(clippy::too_many_arguments)]
+pub fn migrate_pre_loaded_account_mint(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	authority: &Pubkey,
+	vesting_account: &Pubkey,
+	previous_mint: &Pubkey,
+	new_mint: &Pubkey,
+	destination: &Pubkey,
+	rent_payer: &Pubkey,
+	token_program_id: &Pubkey,
+) -> Instruction {
+	let mut instruction =
+		migrate_account_mint(program_id, vesting_schedule, authority, vesting_account);
+	let ata = |mint: &Pubkey| {
+		spl_associated_token_account::get_associated_token_address_with_program_id(
+			vesting_account,
+			mint,
+			token_program_id,
+		)
+	};
+	instruction.accounts.extend([
+		AccountMeta::new(ata(previous_mint), false),
+		AccountMeta::new_readonly(*previous_mint, false),
+		AccountMeta::new_readonly(ata(new_mint), false),
+		AccountMeta::new(*destination, false),
+		AccountMeta::new(*rent_payer, false),
+		AccountMeta::new_readonly(*token_program_id, false),
+	]);
+	instruction
+}
+
+/// Creates a `SetClaimWindow` instruction
+pub fn set_claim_window(
+	program_id: &Pubkey,
+	owner: &Pubkey,
+	vesting_account: &Pubkey,
+	max_claim_per_window: u64,
+	window_seconds: i64,
+) -> Instruction {
+	let data = VestingInstruction::SetClaimWindow {
+		max_claim_per_window,
+		window_seconds,
+	}
+	.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*owner, true),
+		AccountMeta::new(*vesting_account, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates a `SetFallback` instruction, `None` removes the fallback
+pub fn set_fallback(
+	program_id: &Pubkey,
+	owner: &Pubkey,
+	vesting_account: &Pubkey,
+	fallback_owner: COption<Pubkey>,
+	inactivity_seconds: i64,
+) -> Instruction {
+	let data = VestingInstruction::SetFallback {
+		fallback_owner,
+		inactivity_seconds,
+	}
+	.pack();
+
+	let accounts = vec![
+		AccountMeta::new_readonly(*owner, true),
+		AccountMeta::new(*vesting_account, false),
+	];
+
+	Instruction {
+		program_id: *program_id,
+		accounts,
+		data,
+	}
+}
+
+/// Creates the `on_claim` callback invoked on a schedule's manager program after `amount` has been
+/// claimed into `manager_token_account`
+///
+/// Accounts passed:
+///
+/// 0. `[w]` Manager account
+/// 1. `[w]` Manager token account
+/// 2. `[]` Vesting account
+/// 3. `[]` Recipient wallet
+pub fn on_claim(
+	manager_program: &Pubkey,
+	manager_account: &Pubkey,
+	manager_token_account: &Pubkey,
+	vesting_account: &Pubkey,
+	recipient: &Pubkey,
+	amount: u64,
+) -> Instruction {
+	let mut data = Vec::with_capacity(ON_CLAIM_DISCRIMINATOR.len() + BYTES_64);
+	data.extend_from_slice(&ON_CLAIM_DISCRIMINATOR);
+	data.extend_from_slice(&amount.to_le_bytes());
+
+	let accounts = vec![
+		AccountMeta::new(*manager_account, false),
+		AccountMeta::new(*manager_token_account, false),
+		AccountMeta::new_readonly(*vesting_account, false),
+		AccountMeta::new_readonly(*recipient, false),
+	];
+
+	Instruction {
+		program_id: *manager_program,
+		accounts,
+		data,
+	}
 }
@@ -1,5 +1,126 @@
+#[cfg(all(feature = "client", not(target_os = "solana")))]
+pub mod client;
 pub mod entrypoint;
 pub mod error;
 pub mod instruction;
 pub mod processor;
+#[cfg(any(feature = "serde", feature = "borsh"))]
+pub mod serialization;
 pub mod state;
+
+use solana_program::{
+	hash::hash,
+	pubkey::{Pubkey, PubkeyError},
+};
+
+/// Seed prefix for vesting schedule program addresses
+pub const VESTING_SCHEDULE_SEED: &[u8] = b"vesting";
+
+/// Seed prefix for vesting account program addresses
+pub const VESTING_ACCOUNT_SEED: &[u8] = b"account";
+
+/// Seed prefix for the program addresses of vaults a schedule's vault is rotated to
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Length of the hashed identifier used as a vesting schedule address seed
+pub const IDENTIFIER_LEN: usize = 8;
+
+/// Length of a vesting schedule's descriptive label
+pub const LABEL_LEN: usize = 32;
+
+/// Pads `label` with trailing spaces to [`LABEL_LEN`] bytes for a vesting schedule's label,
+/// returns `None` if it is longer
+pub fn pad_label(label: &str) -> Option<[u8; LABEL_LEN]> {
+	let mut padded = [b' '; LABEL_LEN];
+	padded
+		.get_mut(..label.len())?
+		.copy_from_slice(label.as_bytes());
+	Some(padded)
+}
+
+/// Hashes a vesting schedule's string identifier and returns the first [`IDENTIFIER_LEN`] bytes
+pub fn hash_identifier(identifier: &str) -> [u8; IDENTIFIER_LEN] {
+	let mut hashed = [0; IDENTIFIER_LEN];
+	hashed.copy_from_slice(&hash(identifier.as_bytes()).to_bytes()[..IDENTIFIER_LEN]);
+	hashed
+}
+
+/// Derives the vesting schedule address and bump seed for `mint` and `identifier`, allowing
+/// multiple schedules to exist for a single mint
+pub fn find_vesting_schedule_address(
+	program_id: &Pubkey,
+	mint: &Pubkey,
+	identifier: &str,
+) -> (Pubkey, u8) {
+	find_vesting_schedule_address_from_hash(program_id, mint, &hash_identifier(identifier))
+}
+
+/// Derives the vesting schedule address and bump seed from an already hashed identifier, as
+/// stored in [`state::VestingSchedule::identifier`]
+pub fn find_vesting_schedule_address_from_hash(
+	program_id: &Pubkey,
+	mint: &Pubkey,
+	identifier: &[u8; IDENTIFIER_LEN],
+) -> (Pubkey, u8) {
+	Pubkey::find_program_address(
+		&[VESTING_SCHEDULE_SEED, mint.as_ref(), identifier],
+		program_id,
+	)
+}
+
+/// Creates the vesting schedule address for `mint` and an already hashed identifier from the bump
+/// seed stored in [`state::VestingSchedule::bump`], cheaper than
+/// [`find_vesting_schedule_address_from_hash`] for use within the program
+pub fn create_vesting_schedule_address(
+	program_id: &Pubkey,
+	mint: &Pubkey,
+	identifier: &[u8; IDENTIFIER_LEN],
+	bump: u8,
+) -> Result<Pubkey, PubkeyError> {
+	Pubkey::create_program_address(
+		&[VESTING_SCHEDULE_SEED, mint.as_ref(), identifier, &[bump]],
+		program_id,
+	)
+}
+
+/// Seeds the vesting schedule signs with, recreating its address from the stored seed mint,
+/// hashed identifier and bump seed
+pub fn schedule_signer_seeds(vesting_schedule: &state::VestingSchedule) -> [&[u8]; 4] {
+	[
+		VESTING_SCHEDULE_SEED,
+		vesting_schedule.seed_mint.as_ref(),
+		&vesting_schedule.identifier,
+		std::slice::from_ref(&vesting_schedule.bump),
+	]
+}
+
+/// Derives the address and bump seed of `owner`'s vesting account under `vesting_schedule`, the
+/// vesting account is the token authority of its pre-loaded ATA
+pub fn find_vesting_account_address(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	owner: &Pubkey,
+) -> (Pubkey, u8) {
+	Pubkey::find_program_address(
+		&[
+			VESTING_ACCOUNT_SEED,
+			vesting_schedule.as_ref(),
+			owner.as_ref(),
+		],
+		program_id,
+	)
+}
+
+/// Derives the address and bump seed of the vault `vesting_schedule`'s `vault` is rotated to, see
+/// [`instruction::VestingInstruction::RotateVault`]. Seeded by the current vault, so each rotation
+/// moves to a fresh address.
+pub fn find_vault_address(
+	program_id: &Pubkey,
+	vesting_schedule: &Pubkey,
+	vault: &Pubkey,
+) -> (Pubkey, u8) {
+	Pubkey::find_program_address(
+		&[VAULT_SEED, vesting_schedule.as_ref(), vault.as_ref()],
+		program_id,
+	)
+}
@@ -1,9 +1,2650 @@
-use solana_program::{account_info::AccountInfo, pubkey::Pubkey, entrypoint::ProgramResult};
+use {
+	crate::{
+		create_vesting_schedule_address,
+		error::VestingError,
+		find_vault_address, find_vesting_account_address, find_vesting_schedule_address_from_hash,
+		instruction::{
+			on_claim, supported_instructions, voucher_message, CloseReceipt, VestingInstruction,
+			MAX_CLAIMABLE_QUERY_ACCOUNTS, MAX_CURVE_POINTS,
+		},
+		schedule_signer_seeds,
+		state::{
+			quantize_start, sample_curve, transfer_fee, Account, Frequency, PendingAmendment,
+			VestingSchedule, MAX_BPS,
+		},
+		IDENTIFIER_LEN, LABEL_LEN, VAULT_SEED, VESTING_ACCOUNT_SEED,
+	},
+	num_traits::FromPrimitive,
+	solana_program::{
+		account_info::{next_account_info, AccountInfo},
+		clock::{Clock, UnixTimestamp},
+		ed25519_program,
+		entrypoint::ProgramResult,
+		instruction::Instruction,
+		msg,
+		program::{invoke, invoke_signed, set_return_data},
+		program_error::ProgramError,
+		program_option::COption,
+		program_pack::{IsInitialized, Pack},
+		pubkey::{Pubkey, PUBKEY_BYTES},
+		rent::Rent,
+		sysvar::{
+			self,
+			instructions::{load_current_index_checked, load_instruction_at_checked},
+			Sysvar,
+		},
+	},
+	spl_associated_token_account::{
+		get_associated_token_address_with_program_id, instruction::create_associated_token_account,
+	},
+	spl_token_2022::{
+		error::TokenError,
+		extension::{
+			transfer_fee::instruction::transfer_checked_with_fee, BaseStateWithExtensions,
+			ExtensionType, StateWithExtensions,
+		},
+		state::{Account as TokenAccount, Mint},
+	},
+};
+
+#[allow(deprecated)]
+use solana_program::{system_instruction, system_program};
+
+/// How a claim was requested, see [`VestingInstruction::Claim`],
+/// [`VestingInstruction::CrankClaim`], [`VestingInstruction::ClaimTo`] and
+/// [`VestingInstruction::ClaimAndUnwrap`]
+#[derive(Clone, Copy, PartialEq)]
+enum ClaimKind {
+	Claim,
+	Crank,
+	ClaimTo,
+	ClaimAndUnwrap,
+	Fallback,
+}
+
+/// Who is creating a vesting account and paying for it, the schedule's authority or, with
+/// [`VestingInstruction::RedeemVoucher`], the account's owner
+#[derive(Clone, Copy, PartialEq)]
+enum Creator {
+	Authority,
+	Owner,
+}
 
 pub struct Processor;
 
 impl Processor {
 	pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-		unimplemented!();
+		let instruction = VestingInstruction::unpack(data)?;
+		msg!("Instruction: {:?}", instruction.tag());
+		match instruction {
+			VestingInstruction::InitVestingSchedule {
+				authority,
+				mint,
+				schedule,
+				start,
+				duration,
+				cliff,
+				vault,
+				anchor_to_creation,
+				identifier,
+				receipt_mint,
+				tip_lamports,
+				manager_program,
+				quantize_start_to,
+				allow_past_start,
+				past_start_tolerance,
+				pool_total,
+				cliff_bps,
+			} => Self::process_init_vesting_schedule(
+				program_id,
+				accounts,
+				authority,
+				mint,
+				schedule,
+				start,
+				duration,
+				cliff,
+				vault,
+				anchor_to_creation,
+				identifier,
+				receipt_mint,
+				tip_lamports,
+				manager_program,
+				quantize_start_to,
+				allow_past_start,
+				past_start_tolerance,
+				pool_total,
+				cliff_bps,
+			),
+			VestingInstruction::CreateAccount {
+				owner,
+				amount,
+				immediate_bps,
+				allow_post_completion,
+			} => Self::process_create_account(
+				program_id,
+				accounts,
+				owner,
+				amount,
+				immediate_bps,
+				allow_post_completion,
+				Creator::Authority,
+			),
+			VestingInstruction::CreateAccountByBps { owner, bps } => {
+				Self::process_create_account_by_bps(program_id, accounts, owner, bps)
+			}
+			VestingInstruction::SetLabel { label } => {
+				Self::process_set_label(program_id, accounts, label)
+			}
+			VestingInstruction::ReassignRentPayer { new_payer } => {
+				Self::process_reassign_rent_payer(program_id, accounts, new_payer)
+			}
+			VestingInstruction::AdjustClaimed { delta } => {
+				Self::process_adjust_claimed(program_id, accounts, delta)
+			}
+			VestingInstruction::SetClaimWindow {
+				max_claim_per_window,
+				window_seconds,
+			} => Self::process_set_claim_window(
+				program_id,
+				accounts,
+				max_claim_per_window,
+				window_seconds,
+			),
+			VestingInstruction::GetUiContext => Self::process_get_ui_context(program_id, accounts),
+			VestingInstruction::LockAccount => Self::process_set_locked(program_id, accounts, true),
+			VestingInstruction::UnlockAccount => {
+				Self::process_set_locked(program_id, accounts, false)
+			}
+			VestingInstruction::SampleCurve {
+				start_ts,
+				end_ts,
+				points,
+			} => Self::process_sample_curve(program_id, accounts, start_ts, end_ts, points),
+			VestingInstruction::RedeemVoucher {
+				amount,
+				expiry,
+				signature,
+			} => Self::process_redeem_voucher(program_id, accounts, amount, expiry, signature),
+			VestingInstruction::RenounceAmendAuthority => {
+				Self::process_renounce_amend_authority(program_id, accounts)
+			}
+			VestingInstruction::AmendAmount { amount } => {
+				Self::process_amend_amount(program_id, accounts, amount)
+			}
+			VestingInstruction::AmendSchedule {
+				start,
+				schedule,
+				duration,
+				force,
+			} => {
+				Self::process_amend_schedule(program_id, accounts, start, schedule, duration, force)
+			}
+			VestingInstruction::Claim { expected_start } => {
+				Self::process_claim(program_id, accounts, ClaimKind::Claim, expected_start)
+			}
+			VestingInstruction::CloseAccount {
+				acknowledged_forfeit,
+			} => Self::process_close_account(program_id, accounts, acknowledged_forfeit),
+			VestingInstruction::CloseVestingSchedule => {
+				Self::process_close_vesting_schedule(program_id, accounts)
+			}
+			VestingInstruction::CrankClaim => {
+				Self::process_claim(program_id, accounts, ClaimKind::Crank, None)
+			}
+			VestingInstruction::GetClaimable => Self::process_get_claimable(program_id, accounts),
+			VestingInstruction::GetClaimableMany => {
+				Self::process_get_claimable_many(program_id, accounts)
+			}
+			VestingInstruction::ScheduleAmend {
+				effective_at,
+				start,
+				schedule,
+				duration,
+			} => Self::process_schedule_amend(
+				program_id,
+				accounts,
+				effective_at,
+				start,
+				schedule,
+				duration,
+			),
+			VestingInstruction::SetAllowedDestination {
+				allowed_destination,
+			} => Self::process_set_allowed_destination(program_id, accounts, allowed_destination),
+			VestingInstruction::ClaimTo => {
+				Self::process_claim(program_id, accounts, ClaimKind::ClaimTo, None)
+			}
+			VestingInstruction::ClaimAndUnwrap => {
+				Self::process_claim(program_id, accounts, ClaimKind::ClaimAndUnwrap, None)
+			}
+			VestingInstruction::SetFallback {
+				fallback_owner,
+				inactivity_seconds,
+			} => {
+				Self::process_set_fallback(program_id, accounts, fallback_owner, inactivity_seconds)
+			}
+			VestingInstruction::InitByCount {
+				authority,
+				mint,
+				schedule,
+				start,
+				emissions,
+				cliff,
+				vault,
+				anchor_to_creation,
+				identifier,
+				receipt_mint,
+				tip_lamports,
+				manager_program,
+				quantize_start_to,
+				allow_past_start,
+				past_start_tolerance,
+				pool_total,
+				cliff_bps,
+			} => {
+				let duration = schedule.duration_of(emissions)?;
+				Self::process_init_vesting_schedule(
+					program_id,
+					accounts,
+					authority,
+					mint,
+					schedule,
+					start,
+					duration,
+					cliff,
+					vault,
+					anchor_to_creation,
+					identifier,
+					receipt_mint,
+					tip_lamports,
+					manager_program,
+					quantize_start_to,
+					allow_past_start,
+					past_start_tolerance,
+					pool_total,
+					cliff_bps,
+				)
+			}
+			VestingInstruction::ClaimFallback => {
+				Self::process_claim(program_id, accounts, ClaimKind::Fallback, None)
+			}
+			VestingInstruction::Forfeit => Self::process_forfeit(program_id, accounts),
+			VestingInstruction::RotateVault => Self::process_rotate_vault(program_id, accounts),
+			VestingInstruction::GetSupportedInstructions => {
+				Self::process_get_supported_instructions()
+			}
+			VestingInstruction::MigrateMint {
+				new_mint,
+				ratio_num,
+				ratio_den,
+			} => Self::process_migrate_mint(program_id, accounts, new_mint, ratio_num, ratio_den),
+			VestingInstruction::MigrateAccountMint => {
+				Self::process_migrate_account_mint(program_id, accounts)
+			}
+			VestingInstruction::PauseAccount => {
+				Self::process_set_claims_paused(program_id, accounts, true)
+			}
+			VestingInstruction::UnpauseAccount => {
+				Self::process_set_claims_paused(program_id, accounts, false)
+			}
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	fn process_init_vesting_schedule(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		authority: Pubkey,
+		mint: Pubkey,
+		schedule: Frequency,
+		start: UnixTimestamp,
+		duration: i64,
+		cliff: i64,
+		vault: COption<Pubkey>,
+		anchor_to_creation: bool,
+		identifier: [u8; IDENTIFIER_LEN],
+		receipt_mint: COption<Pubkey>,
+		tip_lamports: u64,
+		manager_program: COption<Pubkey>,
+		quantize_start_to: Option<i64>,
+		allow_past_start: bool,
+		past_start_tolerance: i64,
+		pool_total: u64,
+		cliff_bps: u16,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let payer_info = next_account_info(account_info_iter)?;
+		let system_program_info = next_required_account(account_info_iter, "system program")?;
+		let mint_info = next_required_account(account_info_iter, "mint")?;
+
+		assert_program_id(system_program_info, &system_program::id())?;
+		if *mint_info.key != mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		if !is_token_program(mint_info.owner) {
+			return Err(ProgramError::IllegalOwner);
+		}
+		if !payer_info.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+		let (address, bump) =
+			find_vesting_schedule_address_from_hash(program_id, &mint, &identifier);
+		if address != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidSeeds);
+		}
+		if !vesting_schedule_info.data_is_empty() {
+			return Err(VestingError::AlreadyInitialized.into());
+		}
+
+		// Future starts are always allowed, a start well in the past vests immediately so must be
+		// opted into
+		let now = Clock::get()?.unix_timestamp;
+		if past_start_tolerance < 0 {
+			return Err(VestingError::InvalidSchedule.into());
+		}
+		if !allow_past_start && start < now.saturating_sub(past_start_tolerance) {
+			msg!("Start is more than {}s in the past", past_start_tolerance);
+			return Err(VestingError::InvalidSchedule.into());
+		}
+		let start = match quantize_start_to {
+			Some(interval) => quantize_start(start, interval)?,
+			None => start,
+		};
+		let vesting_schedule = VestingSchedule {
+			is_initialized: true,
+			authority,
+			mint,
+			frequency: schedule,
+			start,
+			duration,
+			vault,
+			anchor_to_creation,
+			identifier,
+			cliff,
+			pending_amendment: None,
+			receipt_mint,
+			tip_lamports,
+			manager_program,
+			bump,
+			past_start_tolerance,
+			pool_total,
+			allocated_bps: 0,
+			label: [b' '; LABEL_LEN],
+			decimals: unpack_mint(mint_info)?.decimals,
+			immutable: false,
+			seed_mint: mint,
+			previous_mint: COption::None,
+			migration_ratio_num: 0,
+			migration_ratio_den: 0,
+			cliff_bps,
+		};
+		vesting_schedule.validate()?;
+		vesting_schedule.total_periods()?;
+
+		let rent = Rent::get()?;
+		invoke_as_schedule(
+			&system_instruction::create_account(
+				payer_info.key,
+				vesting_schedule_info.key,
+				rent.minimum_balance(VestingSchedule::LEN),
+				VestingSchedule::LEN as u64,
+				program_id,
+			),
+			&[
+				payer_info.clone(),
+				vesting_schedule_info.clone(),
+				system_program_info.clone(),
+			],
+			&vesting_schedule,
+		)?;
+		VestingSchedule::pack(
+			vesting_schedule,
+			&mut vesting_schedule_info.data.borrow_mut(),
+		)?;
+
+		if let COption::Some(vault) = vault {
+			let vault_info = next_required_account(account_info_iter, "vault")?;
+			let token_program_info = next_required_account(account_info_iter, "token program")?;
+			let associated_token_program_info =
+				next_required_account(account_info_iter, "associated token program")?;
+
+			assert_token_program(token_program_info)?;
+			assert_program_id(
+				associated_token_program_info,
+				&spl_associated_token_account::id(),
+			)?;
+			if *vault_info.key != vault
+				|| vault
+					!= get_associated_token_address_with_program_id(
+						vesting_schedule_info.key,
+						&mint,
+						token_program_info.key,
+					) {
+				return Err(VestingError::InvalidVault.into());
+			}
+			create_ata_if_missing(
+				payer_info,
+				vesting_schedule_info,
+				mint_info,
+				vault_info,
+				system_program_info,
+				token_program_info,
+				associated_token_program_info,
+			)?;
+		}
+		Ok(())
+	}
+
+	fn process_create_account(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		owner: Pubkey,
+		amount: u64,
+		immediate_bps: u16,
+		allow_post_completion: bool,
+		creator: Creator,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let payer_info = next_account_info(account_info_iter)?;
+		let mint_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+		let vesting_account_ata_info = next_account_info(account_info_iter)?;
+		let system_program_info = next_required_account(account_info_iter, "system program")?;
+		let token_program_info = next_required_account(account_info_iter, "token program")?;
+		let associated_token_program_info =
+			next_required_account(account_info_iter, "associated token program")?;
+
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		assert_program_id(system_program_info, &system_program::id())?;
+		assert_token_program(token_program_info)?;
+		assert_program_id(
+			associated_token_program_info,
+			&spl_associated_token_account::id(),
+		)?;
+
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		let creator_key = match creator {
+			Creator::Authority => vesting_schedule.authority,
+			Creator::Owner => owner,
+		};
+		if !payer_info.is_signer || *payer_info.key != creator_key {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		if *mint_info.key != vesting_schedule.mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		vesting_schedule.validate_amount(amount)?;
+		// The immediate share and the cliff lump are released on top of each other
+		if immediate_bps.saturating_add(vesting_schedule.cliff_bps) > MAX_BPS {
+			return Err(ProgramError::InvalidArgument);
+		}
+		let now = Clock::get()?.unix_timestamp;
+		if !allow_post_completion && vesting_schedule.is_fully_vested(now) {
+			return Err(VestingError::ScheduleAlreadyCompleted.into());
+		}
+		let (address, bump) =
+			find_vesting_account_address(program_id, vesting_schedule_info.key, &owner);
+		if address != *vesting_account_info.key {
+			return Err(ProgramError::InvalidSeeds);
+		}
+		if !vesting_account_info.data_is_empty() {
+			return Err(VestingError::AlreadyInitialized.into());
+		}
+
+		let rent = Rent::get()?;
+		invoke_signed(
+			&system_instruction::create_account(
+				payer_info.key,
+				vesting_account_info.key,
+				rent.minimum_balance(Account::LEN),
+				Account::LEN as u64,
+				program_id,
+			),
+			&[
+				payer_info.clone(),
+				vesting_account_info.clone(),
+				system_program_info.clone(),
+			],
+			&[&[
+				VESTING_ACCOUNT_SEED,
+				vesting_schedule_info.key.as_ref(),
+				owner.as_ref(),
+				&[bump],
+			]],
+		)?;
+		let vesting_account = Account {
+			is_initialized: true,
+			vesting_schedule: *vesting_schedule_info.key,
+			owner,
+			mint: vesting_schedule.mint,
+			amount,
+			claimed: 0,
+			created_at: now,
+			allowed_destination: COption::None,
+			rent_payer: *payer_info.key,
+			immediate_bps,
+			anchored_at: 0,
+			vested_at_anchor: 0,
+			max_claim_per_window: 0,
+			window_seconds: 0,
+			window_start: 0,
+			claimed_in_window: 0,
+			locked: false,
+			fallback_owner: COption::None,
+			inactivity_seconds: 0,
+			last_claim: 0,
+			claims_paused: false,
+		};
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())?;
+
+		// Tokens are pre-loaded into the vesting account's ATA unless the schedule pays out of a
+		// vault
+		if vesting_schedule.vault.is_none() {
+			if *vesting_account_ata_info.key
+				!= get_associated_token_address_with_program_id(
+					vesting_account_info.key,
+					mint_info.key,
+					token_program_info.key,
+				) {
+				return Err(ProgramError::InvalidSeeds);
+			}
+			create_ata_if_missing(
+				payer_info,
+				vesting_account_info,
+				mint_info,
+				vesting_account_ata_info,
+				system_program_info,
+				token_program_info,
+				associated_token_program_info,
+			)?;
+
+			// Only the vesting account may move pre-loaded tokens, otherwise whoever else holds
+			// authority over the ATA could drain it
+			let ata = unpack_token_account(vesting_account_ata_info)?;
+			if ata.owner != *vesting_account_info.key
+				|| ata.delegate.is_some()
+				|| ata.close_authority.is_some()
+			{
+				return Err(VestingError::TokenOwnerMismatch.into());
+			}
+		}
+		Ok(())
+	}
+
+	fn process_redeem_voucher(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		amount: u64,
+		expiry: UnixTimestamp,
+		signature: [u8; 64],
+	) -> ProgramResult {
+		let vesting_schedule_info = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+		let owner_info = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+		let instructions_info =
+			next_required_account(&mut accounts.iter().skip(8), "instructions sysvar")?;
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		assert_program_id(instructions_info, &sysvar::instructions::id())?;
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+
+		if Clock::get()?.unix_timestamp > expiry {
+			return Err(VestingError::VoucherExpired.into());
+		}
+		let message = voucher_message(vesting_schedule_info.key, owner_info.key, amount, expiry);
+		assert_ed25519_signed(
+			instructions_info,
+			&vesting_schedule.authority,
+			&message,
+			&signature,
+		)?;
+		Self::process_create_account(
+			program_id,
+			accounts,
+			*owner_info.key,
+			amount,
+			0,
+			false,
+			Creator::Owner,
+		)
+	}
+
+	fn process_create_account_by_bps(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		owner: Pubkey,
+		bps: u16,
+	) -> ProgramResult {
+		let vesting_schedule_info = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let mut vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		let amount = vesting_schedule.allocate_bps(bps)?;
+		Self::process_create_account(
+			program_id,
+			accounts,
+			owner,
+			amount,
+			0,
+			false,
+			Creator::Authority,
+		)?;
+		VestingSchedule::pack(
+			vesting_schedule,
+			&mut vesting_schedule_info.data.borrow_mut(),
+		)
+	}
+
+	fn process_amend_amount(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		amount: u64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+		let source_info = next_account_info(account_info_iter)?;
+		let token_program_info = next_required_account(account_info_iter, "token program")?;
+
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		assert_token_program(token_program_info)?;
+
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		if vesting_schedule.immutable {
+			return Err(VestingError::ScheduleImmutable.into());
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+		if vesting_account.mint != vesting_schedule.mint {
+			return Err(VestingError::AccountNotMigrated.into());
+		}
+		if vesting_account.locked {
+			return Err(VestingError::AccountLocked.into());
+		}
+		if amount < vesting_account.claimed {
+			return Err(VestingError::InvalidSchedule.into());
+		}
+		vesting_schedule.validate_amount(amount)?;
+
+		// Only a pre-loaded ATA backs a single account, so it alone can be checked here. A vault is
+		// shared by every account on the schedule and is only checked when claims are paid out.
+		// Claimed tokens have already left the ATA, it only needs to cover the remainder
+		if amount > vesting_account.amount {
+			match vesting_schedule.vault {
+				COption::Some(vault) => {
+					if *source_info.key != vault {
+						return Err(ProgramError::InvalidAccountData);
+					}
+				}
+				COption::None => {
+					let expected_source = get_associated_token_address_with_program_id(
+						vesting_account_info.key,
+						&vesting_schedule.mint,
+						token_program_info.key,
+					);
+					if *source_info.key != expected_source
+						|| source_info.owner != token_program_info.key
+					{
+						return Err(ProgramError::InvalidAccountData);
+					}
+					if unpack_token_account(source_info)?.amount < amount - vesting_account.claimed
+					{
+						return Err(VestingError::InsufficientVaultBalance.into());
+					}
+				}
+			}
+		}
+
+		// Anchor emissions at what has vested so far so the new amount only applies from now on
+		let now = Clock::get()?.unix_timestamp;
+		if now
+			>= vesting_schedule
+				.effective(now)
+				.account_start(&vesting_account)
+		{
+			vesting_account.vested_at_anchor =
+				vesting_schedule.account_vested(&vesting_account, now)?;
+			vesting_account.anchored_at = now;
+		}
+
+		msg!(
+			"Amount amended from {} to {}",
+			vesting_account.amount,
+			amount
+		);
+		vesting_account.amount = amount;
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())?;
+		Ok(())
+	}
+
+	fn process_adjust_claimed(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		delta: i64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		if vesting_schedule.immutable {
+			return Err(VestingError::ScheduleImmutable.into());
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+		if vesting_account.locked {
+			return Err(VestingError::AccountLocked.into());
+		}
+
+		let claimed = match delta.is_negative() {
+			true => vesting_account.claimed.saturating_sub(delta.unsigned_abs()),
+			false => vesting_account
+				.claimed
+				.saturating_add(delta.unsigned_abs())
+				.min(vesting_account.amount),
+		};
+		if claimed == vesting_account.claimed {
+			return Err(VestingError::AdjustmentOutOfRange.into());
+		}
+
+		msg!(
+			"CORRECTION: claimed by {} adjusted from {} to {} by {}",
+			vesting_account.owner,
+			vesting_account.claimed,
+			claimed,
+			authority_info.key
+		);
+		vesting_account.claimed = claimed;
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())
+	}
+
+	fn process_set_allowed_destination(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		allowed_destination: COption<Pubkey>,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+
+		match allowed_destination {
+			COption::Some(destination) => msg!("Claims restricted to {}", destination),
+			COption::None => msg!("Claim destination restriction lifted"),
+		}
+		vesting_account.allowed_destination = allowed_destination;
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())?;
+		Ok(())
+	}
+
+	fn process_set_locked(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		locked: bool,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+
+		match locked {
+			true => msg!("Vesting account {} locked", vesting_account_info.key),
+			false => msg!("Vesting account {} unlocked", vesting_account_info.key),
+		}
+		vesting_account.locked = locked;
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())
+	}
+
+	fn process_set_claims_paused(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		claims_paused: bool,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+
+		match claims_paused {
+			true => msg!("Claims from {} paused", vesting_account_info.key),
+			false => msg!("Claims from {} unpaused", vesting_account_info.key),
+		}
+		vesting_account.claims_paused = claims_paused;
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())
+	}
+
+	fn process_set_label(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		label: [u8; LABEL_LEN],
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let mut vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+
+		vesting_schedule.label = label;
+		VestingSchedule::pack(
+			vesting_schedule,
+			&mut vesting_schedule_info.data.borrow_mut(),
+		)
+	}
+
+	fn process_renounce_amend_authority(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let mut vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		if vesting_schedule.immutable {
+			return Err(VestingError::ScheduleImmutable.into());
+		}
+
+		vesting_schedule.immutable = true;
+		VestingSchedule::pack(
+			vesting_schedule,
+			&mut vesting_schedule_info.data.borrow_mut(),
+		)
+	}
+
+	fn process_reassign_rent_payer(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		new_payer: Pubkey,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let rent_payer_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+
+		if vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if !rent_payer_info.is_signer || *rent_payer_info.key != vesting_account.rent_payer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		msg!("Rent payer reassigned to {}", new_payer);
+		vesting_account.rent_payer = new_payer;
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())
+	}
+
+	fn process_set_claim_window(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		max_claim_per_window: u64,
+		window_seconds: i64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let owner_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+
+		if vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if !owner_info.is_signer || *owner_info.key != vesting_account.owner {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+		if max_claim_per_window > 0 && window_seconds <= 0 {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		msg!(
+			"Claims limited to {} per {} seconds",
+			max_claim_per_window,
+			window_seconds
+		);
+		vesting_account.max_claim_per_window = max_claim_per_window;
+		vesting_account.window_seconds = window_seconds;
+		vesting_account.window_start = 0;
+		vesting_account.claimed_in_window = 0;
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())
+	}
+
+	fn process_set_fallback(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		fallback_owner: COption<Pubkey>,
+		inactivity_seconds: i64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let owner_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+
+		if vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if !owner_info.is_signer || *owner_info.key != vesting_account.owner {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+		if fallback_owner.is_some() && inactivity_seconds <= 0 {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		// Setting a fallback shows the owner is active, restarting the inactivity clock
+		vesting_account.fallback_owner = fallback_owner;
+		vesting_account.inactivity_seconds = inactivity_seconds;
+		vesting_account.last_claim = Clock::get()?.unix_timestamp;
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())
+	}
+
+	fn process_amend_schedule(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		start: Option<UnixTimestamp>,
+		schedule: Option<Frequency>,
+		duration: Option<i64>,
+		force: bool,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		if vesting_schedule.immutable {
+			return Err(VestingError::ScheduleImmutable.into());
+		}
+
+		// Amendments apply on top of any pending amendment that has already taken effect
+		let now = Clock::get()?.unix_timestamp;
+		let previous = vesting_schedule.effective(now);
+		let mut vesting_schedule = previous.clone();
+		vesting_schedule.amend(start, schedule, duration);
+		validate_amendment(&previous, &vesting_schedule, now, force)?;
+
+		if vesting_schedule.start != previous.start {
+			msg!(
+				"Start amended from {} to {}",
+				previous.start,
+				vesting_schedule.start
+			);
+		}
+		if vesting_schedule.frequency != previous.frequency {
+			msg!(
+				"Frequency amended from {:?} to {:?}",
+				previous.frequency,
+				vesting_schedule.frequency
+			);
+		}
+		if vesting_schedule.duration != previous.duration {
+			msg!(
+				"Duration amended from {} to {}",
+				previous.duration,
+				vesting_schedule.duration
+			);
+		}
+		if force {
+			msg!("Amendment forced");
+		}
+		VestingSchedule::pack(
+			vesting_schedule,
+			&mut vesting_schedule_info.data.borrow_mut(),
+		)?;
+		Ok(())
+	}
+
+	fn process_schedule_amend(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		effective_at: UnixTimestamp,
+		start: Option<UnixTimestamp>,
+		schedule: Option<Frequency>,
+		duration: Option<i64>,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		if vesting_schedule.immutable {
+			return Err(VestingError::ScheduleImmutable.into());
+		}
+
+		// A pending amendment that has already taken effect is applied before being replaced
+		let now = Clock::get()?.unix_timestamp;
+		if effective_at <= now {
+			return Err(VestingError::InvalidSchedule.into());
+		}
+		let mut vesting_schedule = vesting_schedule.effective(now);
+		let mut amended = vesting_schedule.clone();
+		amended.amend(start, schedule, duration);
+		validate_amendment(&vesting_schedule, &amended, effective_at, false)?;
+
+		if let Some(pending) = vesting_schedule.pending_amendment {
+			msg!("Replacing amendment pending from {}", pending.effective_at);
+		}
+		msg!("Amendment scheduled for {}", effective_at);
+		vesting_schedule.pending_amendment = Some(PendingAmendment {
+			effective_at,
+			start,
+			frequency: schedule,
+			duration,
+		});
+		VestingSchedule::pack(
+			vesting_schedule,
+			&mut vesting_schedule_info.data.borrow_mut(),
+		)?;
+		Ok(())
+	}
+
+	/// Claims on behalf of the recipient, who must sign when claiming for themselves. A crank
+	/// claim may be signed by anyone, who pays for any ATAs created and is paid the schedule's
+	/// tip if its tip pool covers it.
+	fn process_claim(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		kind: ClaimKind,
+		expected_start: Option<i64>,
+	) -> ProgramResult {
+		let crank = kind == ClaimKind::Crank;
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let mint_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+		let source_info = next_account_info(account_info_iter)?;
+		let recipient_info = next_account_info(account_info_iter)?;
+		let recipient_ata_info = next_account_info(account_info_iter)?;
+		let system_program_info = next_required_account(account_info_iter, "system program")?;
+		let token_program_info = next_required_account(account_info_iter, "token program")?;
+		let associated_token_program_info =
+			next_required_account(account_info_iter, "associated token program")?;
+
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		assert_program_id(system_program_info, &system_program::id())?;
+		assert_token_program(token_program_info)?;
+		assert_program_id(
+			associated_token_program_info,
+			&spl_associated_token_account::id(),
+		)?;
+		let payer_info = match crank {
+			true => next_account_info(account_info_iter)?,
+			false => recipient_info,
+		};
+		if !payer_info.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if *mint_info.key != vesting_schedule.mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		if expected_start.is_some_and(|expected_start| expected_start != vesting_schedule.start) {
+			return Err(VestingError::StartMismatch.into());
+		}
+		if kind == ClaimKind::ClaimAndUnwrap
+			&& !spl_token::native_mint::check_id(&vesting_schedule.mint)
+			&& !spl_token_2022::native_mint::check_id(&vesting_schedule.mint)
+		{
+			return Err(VestingError::NotNativeMint.into());
+		}
+		if matches!(kind, ClaimKind::ClaimTo | ClaimKind::ClaimAndUnwrap)
+			&& vesting_schedule.manager_program.is_some()
+		{
+			return Err(ProgramError::InvalidArgument);
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+		if vesting_account.mint != vesting_schedule.mint {
+			return Err(VestingError::AccountNotMigrated.into());
+		}
+		// The fallback owner claims in place of the owner, whose key still seeds the account
+		let claimant = match kind {
+			ClaimKind::Fallback => vesting_account.fallback_owner,
+			_ => COption::Some(vesting_account.owner),
+		};
+		if claimant != COption::Some(*recipient_info.key) {
+			return Err(ProgramError::IllegalOwner);
+		}
+		if vesting_account.locked {
+			return Err(VestingError::AccountLocked.into());
+		}
+		if vesting_account.claims_paused {
+			return Err(VestingError::AccountClaimsPaused.into());
+		}
+		if kind != ClaimKind::ClaimTo
+			&& *recipient_ata_info.key
+				!= get_associated_token_address_with_program_id(
+					recipient_info.key,
+					mint_info.key,
+					token_program_info.key,
+				) {
+			return Err(ProgramError::InvalidSeeds);
+		}
+
+		let now = Clock::get()?.unix_timestamp;
+		if kind == ClaimKind::Fallback && !vesting_account.fallback_active(now) {
+			return Err(VestingError::FallbackNotYetActive.into());
+		}
+		let amount = vesting_schedule.account_claimable(&vesting_account, now)?;
+		if amount == 0 {
+			if now
+				< vesting_schedule
+					.effective(now)
+					.account_start(&vesting_account)
+			{
+				return Err(VestingError::VestingNotStarted.into());
+			}
+			return Err(VestingError::NothingToClaim.into());
+		}
+		let amount = match vesting_account.window_remaining(now) {
+			0 => return Err(VestingError::WindowLimitReached.into()),
+			remaining => amount.min(remaining),
+		};
+
+		let receipt_infos = match vesting_schedule.receipt_mint {
+			COption::Some(_) => Some((
+				next_account_info(account_info_iter)?,
+				next_account_info(account_info_iter)?,
+			)),
+			COption::None => None,
+		};
+		let manager_infos = match vesting_schedule.manager_program {
+			COption::Some(manager_program) => {
+				let manager_program_info =
+					next_required_account(account_info_iter, "manager program")?;
+				let manager_info = next_account_info(account_info_iter)?;
+				let manager_token_info = next_account_info(account_info_iter)?;
+				assert_program_id(manager_program_info, &manager_program)?;
+				if manager_program == *program_id || !manager_program_info.executable {
+					return Err(VestingError::IncorrectProgramId.into());
+				}
+				if !manager_info.is_writable {
+					return Err(ProgramError::InvalidArgument);
+				}
+				if manager_info.owner != manager_program_info.key {
+					return Err(ProgramError::IllegalOwner);
+				}
+				Some((manager_program_info, manager_info, manager_token_info))
+			}
+			COption::None => None,
+		};
+
+		// Claimed tokens go to a token account held by the manager if the schedule has one, the
+		// account chosen by the recipient for `ClaimTo`, otherwise to the recipient's ATA, paid
+		// for by the recipient or the cranker if missing
+		let destination_info = match manager_infos {
+			Some((_, _, manager_token_info)) => manager_token_info,
+			None if kind == ClaimKind::ClaimTo => recipient_ata_info,
+			None => {
+				create_ata_if_missing(
+					payer_info,
+					recipient_info,
+					mint_info,
+					recipient_ata_info,
+					system_program_info,
+					token_program_info,
+					associated_token_program_info,
+				)?;
+				recipient_ata_info
+			}
+		};
+		if destination_info.owner != token_program_info.key {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let destination = unpack_token_account(destination_info)?;
+		if destination.mint != vesting_schedule.mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		if destination.is_frozen() {
+			return Err(VestingError::TokenAccountFrozen.into());
+		}
+		if let Some((_, manager_info, _)) = manager_infos {
+			if destination.owner != *manager_info.key {
+				return Err(ProgramError::IllegalOwner);
+			}
+		}
+		if let COption::Some(allowed_destination) = vesting_account.allowed_destination {
+			if *destination_info.key != allowed_destination {
+				return Err(VestingError::DestinationNotAllowed.into());
+			}
+		}
+		let decimals = unpack_mint(mint_info)?.decimals;
+
+		// `claimed` is debited by the gross amount, with a transfer fee the destination receives
+		// the net amount and the fee is withheld in the destination account
+		let fee = transfer_fee(&mint_info.data.borrow(), Clock::get()?.epoch, amount)?;
+		let transfer = |authority: &Pubkey| match fee {
+			Some(fee) => transfer_checked_with_fee(
+				token_program_info.key,
+				source_info.key,
+				mint_info.key,
+				destination_info.key,
+				authority,
+				&[],
+				amount,
+				decimals,
+				fee,
+			),
+			None => spl_token_2022::instruction::transfer_checked(
+				token_program_info.key,
+				source_info.key,
+				mint_info.key,
+				destination_info.key,
+				authority,
+				&[],
+				amount,
+				decimals,
+			),
+		};
+
+		// Tokens are released either from the schedule's vault, signed for by the vesting
+		// schedule, or from the vesting account's pre-loaded ATA, signed for by the vesting account
+		match vesting_schedule.vault {
+			COption::Some(vault) => {
+				if *source_info.key != vault {
+					return Err(ProgramError::InvalidAccountData);
+				}
+				assert_vesting_schedule_address(
+					program_id,
+					vesting_schedule_info,
+					&vesting_schedule,
+				)?;
+				if unpack_token_account(source_info)?.amount < amount {
+					return Err(VestingError::InsufficientVaultBalance.into());
+				}
+				invoke_as_schedule(
+					&transfer(vesting_schedule_info.key)?,
+					&[
+						source_info.clone(),
+						mint_info.clone(),
+						destination_info.clone(),
+						vesting_schedule_info.clone(),
+						token_program_info.clone(),
+					],
+					&vesting_schedule,
+				)
+				.map_err(map_token_error)?;
+			}
+			COption::None => {
+				let (address, bump) = find_vesting_account_address(
+					program_id,
+					vesting_schedule_info.key,
+					&vesting_account.owner,
+				);
+				if address != *vesting_account_info.key {
+					return Err(ProgramError::InvalidSeeds);
+				}
+				if *source_info.key
+					!= get_associated_token_address_with_program_id(
+						vesting_account_info.key,
+						mint_info.key,
+						token_program_info.key,
+					) {
+					return Err(ProgramError::InvalidAccountData);
+				}
+				let source = unpack_token_account(source_info)?;
+				if source.is_frozen() {
+					return Err(VestingError::TokenAccountFrozen.into());
+				}
+				if source.amount < amount {
+					return Err(VestingError::InsufficientTokenBalance.into());
+				}
+				invoke_signed(
+					&transfer(vesting_account_info.key)?,
+					&[
+						source_info.clone(),
+						mint_info.clone(),
+						destination_info.clone(),
+						vesting_account_info.clone(),
+						token_program_info.clone(),
+					],
+					&[&[
+						VESTING_ACCOUNT_SEED,
+						vesting_schedule_info.key.as_ref(),
+						vesting_account.owner.as_ref(),
+						&[bump],
+					]],
+				)
+				.map_err(map_token_error)?;
+			}
+		}
+
+		// Closing the native mint ATA unwraps its balance, along with its rent, into the wallet
+		if kind == ClaimKind::ClaimAndUnwrap {
+			invoke(
+				&spl_token_2022::instruction::close_account(
+					token_program_info.key,
+					recipient_ata_info.key,
+					recipient_info.key,
+					recipient_info.key,
+					&[],
+				)?,
+				&[
+					recipient_ata_info.clone(),
+					recipient_info.clone(),
+					token_program_info.clone(),
+				],
+			)
+			.map_err(map_token_error)?;
+		}
+
+		if let Some((receipt_mint_info, receipt_ata_info)) = receipt_infos {
+			if COption::Some(*receipt_mint_info.key) != vesting_schedule.receipt_mint {
+				return Err(VestingError::InvalidMint.into());
+			}
+			let mint_authority = unpack_mint(receipt_mint_info)?.mint_authority;
+			if mint_authority != COption::Some(*vesting_schedule_info.key) {
+				return Err(ProgramError::IncorrectAuthority);
+			}
+			if *receipt_ata_info.key
+				!= get_associated_token_address_with_program_id(
+					recipient_info.key,
+					receipt_mint_info.key,
+					token_program_info.key,
+				) {
+				return Err(ProgramError::InvalidSeeds);
+			}
+			create_ata_if_missing(
+				payer_info,
+				recipient_info,
+				receipt_mint_info,
+				receipt_ata_info,
+				system_program_info,
+				token_program_info,
+				associated_token_program_info,
+			)?;
+			assert_vesting_schedule_address(program_id, vesting_schedule_info, &vesting_schedule)?;
+			invoke_as_schedule(
+				&spl_token_2022::instruction::mint_to(
+					token_program_info.key,
+					receipt_mint_info.key,
+					receipt_ata_info.key,
+					vesting_schedule_info.key,
+					&[],
+					1,
+				)?,
+				&[
+					receipt_mint_info.clone(),
+					receipt_ata_info.clone(),
+					vesting_schedule_info.clone(),
+					token_program_info.clone(),
+				],
+				&vesting_schedule,
+			)
+			.map_err(map_token_error)?;
+		}
+
+		if let Some(fee) = fee {
+			msg!("Transfer fee of {} withheld", fee);
+		}
+
+		vesting_account.claimed = vesting_account
+			.claimed
+			.checked_add(amount)
+			.ok_or(VestingError::Overflow)?;
+		vesting_account.record_window_claim(now, amount);
+		if !matches!(kind, ClaimKind::Crank | ClaimKind::Fallback) {
+			vesting_account.last_claim = now;
+		}
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())?;
+
+		// Tips are paid out of lamports held by the schedule above its rent-exempt minimum
+		if crank && vesting_schedule.tip_lamports > 0 {
+			let rent = Rent::get()?.minimum_balance(vesting_schedule_info.data_len());
+			let pool = vesting_schedule_info.lamports().saturating_sub(rent);
+			if pool >= vesting_schedule.tip_lamports {
+				**vesting_schedule_info.try_borrow_mut_lamports()? -= vesting_schedule.tip_lamports;
+				**payer_info.try_borrow_mut_lamports()? += vesting_schedule.tip_lamports;
+			} else {
+				msg!("Tip pool exhausted, no tip paid");
+			}
+		}
+
+		// The manager is only handed the accounts it needs and none of this program's signatures
+		if let Some((manager_program_info, manager_info, manager_token_info)) = manager_infos {
+			invoke(
+				&on_claim(
+					manager_program_info.key,
+					manager_info.key,
+					manager_token_info.key,
+					vesting_account_info.key,
+					recipient_info.key,
+					amount,
+				),
+				&[
+					manager_info.clone(),
+					manager_token_info.clone(),
+					vesting_account_info.clone(),
+					recipient_info.clone(),
+					manager_program_info.clone(),
+				],
+			)?;
+		}
+		Ok(())
+	}
+
+	/// Terminates a beneficiary, returning their unclaimed tokens to the authority. Tokens held
+	/// in the vesting account's ATA are returned and the ATA closed, with a vault only the
+	/// account's outstanding allocation is returned. The account's rent goes to the authority.
+	fn process_close_account(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		acknowledged_forfeit: u64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+		let mint_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+		let source_info = next_account_info(account_info_iter)?;
+		let destination_info = next_account_info(account_info_iter)?;
+		let token_program_info = next_required_account(account_info_iter, "token program")?;
+
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		assert_token_program(token_program_info)?;
+
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		if *mint_info.key != vesting_schedule.mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		let vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+		if vesting_account.mint != vesting_schedule.mint {
+			return Err(VestingError::AccountNotMigrated.into());
+		}
+		let rent_payer_info = match vesting_account.rent_payer == *authority_info.key {
+			true => authority_info,
+			false => next_required_account(account_info_iter, "rent payer")?,
+		};
+		if *rent_payer_info.key != vesting_account.rent_payer {
+			return Err(ProgramError::InvalidArgument);
+		}
+		let now = Clock::get()?.unix_timestamp;
+		vesting_schedule.verify_forfeit(&vesting_account, now, acknowledged_forfeit)?;
+		// Closing an account early returns its unvested tokens, renounced along with amending
+		if vesting_schedule.immutable
+			&& vesting_schedule.account_vested(&vesting_account, now)? < vesting_account.amount
+		{
+			return Err(VestingError::ScheduleImmutable.into());
+		}
+
+		if destination_info.owner != token_program_info.key {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let destination = unpack_token_account(destination_info)?;
+		if destination.mint != vesting_schedule.mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		if destination.owner != *authority_info.key {
+			return Err(ProgramError::IllegalOwner);
+		}
+		let decimals = unpack_mint(mint_info)?.decimals;
+
+		// Tokens swept to the authority, and the lamports of the ATA closed along the way
+		let (tokens_swept, ata_lamports) = match vesting_schedule.vault {
+			COption::Some(vault) => {
+				if *source_info.key != vault {
+					return Err(ProgramError::InvalidAccountData);
+				}
+				assert_vesting_schedule_address(
+					program_id,
+					vesting_schedule_info,
+					&vesting_schedule,
+				)?;
+				let outstanding = vesting_account
+					.amount
+					.saturating_sub(vesting_account.claimed)
+					.min(unpack_token_account(source_info)?.amount);
+				if outstanding > 0 {
+					invoke_as_schedule(
+						&spl_token_2022::instruction::transfer_checked(
+							token_program_info.key,
+							source_info.key,
+							mint_info.key,
+							destination_info.key,
+							vesting_schedule_info.key,
+							&[],
+							outstanding,
+							decimals,
+						)?,
+						&[
+							source_info.clone(),
+							mint_info.clone(),
+							destination_info.clone(),
+							vesting_schedule_info.clone(),
+							token_program_info.clone(),
+						],
+						&vesting_schedule,
+					)
+					.map_err(map_token_error)?;
+				}
+				(outstanding, 0)
+			}
+			COption::None => {
+				let (address, bump) = find_vesting_account_address(
+					program_id,
+					vesting_schedule_info.key,
+					&vesting_account.owner,
+				);
+				if address != *vesting_account_info.key {
+					return Err(ProgramError::InvalidSeeds);
+				}
+				if *source_info.key
+					!= get_associated_token_address_with_program_id(
+						vesting_account_info.key,
+						mint_info.key,
+						token_program_info.key,
+					) {
+					return Err(ProgramError::InvalidAccountData);
+				}
+				let seeds: &[&[u8]] = &[
+					VESTING_ACCOUNT_SEED,
+					vesting_schedule_info.key.as_ref(),
+					vesting_account.owner.as_ref(),
+					&[bump],
+				];
+				let balance = unpack_token_account(source_info)?.amount;
+				if balance > 0 {
+					invoke_signed(
+						&spl_token_2022::instruction::transfer_checked(
+							token_program_info.key,
+							source_info.key,
+							mint_info.key,
+							destination_info.key,
+							vesting_account_info.key,
+							&[],
+							balance,
+							decimals,
+						)?,
+						&[
+							source_info.clone(),
+							mint_info.clone(),
+							destination_info.clone(),
+							vesting_account_info.clone(),
+							token_program_info.clone(),
+						],
+						&[seeds],
+					)
+					.map_err(map_token_error)?;
+				}
+				let ata_lamports = source_info.lamports();
+				invoke_signed(
+					&spl_token_2022::instruction::close_account(
+						token_program_info.key,
+						source_info.key,
+						rent_payer_info.key,
+						vesting_account_info.key,
+						&[],
+					)?,
+					&[
+						source_info.clone(),
+						rent_payer_info.clone(),
+						vesting_account_info.clone(),
+						token_program_info.clone(),
+					],
+					&[seeds],
+				)
+				.map_err(map_token_error)?;
+				(balance, ata_lamports)
+			}
+		};
+
+		msg!(
+			"Closed account for {}: {} of {} claimed, {} forfeited",
+			vesting_account.owner,
+			vesting_account.claimed,
+			vesting_account.amount,
+			vesting_account
+				.amount
+				.saturating_sub(vesting_account.claimed)
+		);
+
+		let lamports = vesting_account_info.lamports();
+		**rent_payer_info.try_borrow_mut_lamports()? = rent_payer_info
+			.lamports()
+			.checked_add(lamports)
+			.ok_or(VestingError::Overflow)?;
+		**vesting_account_info.try_borrow_mut_lamports()? = 0;
+		vesting_account_info.data.borrow_mut().fill(0);
+		let receipt = CloseReceipt {
+			lamports_refunded: lamports.saturating_add(ata_lamports),
+			tokens_swept,
+		};
+		set_return_data(&receipt.pack());
+		Ok(())
+	}
+
+	/// Returns the unvested remainder of an account to the authority at the owner's request
+	fn process_forfeit(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let owner_info = next_account_info(account_info_iter)?;
+		let mint_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+		let source_info = next_account_info(account_info_iter)?;
+		let destination_info = next_account_info(account_info_iter)?;
+		let token_program_info = next_required_account(account_info_iter, "token program")?;
+
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		assert_token_program(token_program_info)?;
+
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if *mint_info.key != vesting_schedule.mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+		if vesting_account.mint != vesting_schedule.mint {
+			return Err(VestingError::AccountNotMigrated.into());
+		}
+		if !owner_info.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+		if *owner_info.key != vesting_account.owner {
+			return Err(ProgramError::IllegalOwner);
+		}
+		if vesting_account.locked {
+			return Err(VestingError::AccountLocked.into());
+		}
+
+		let now = Clock::get()?.unix_timestamp;
+		let vested = vesting_schedule.account_vested(&vesting_account, now)?;
+		let forfeited = vesting_account.amount.saturating_sub(vested);
+		if forfeited == 0 {
+			return Err(VestingError::NothingToForfeit.into());
+		}
+
+		if destination_info.owner != token_program_info.key {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let destination = unpack_token_account(destination_info)?;
+		if destination.mint != vesting_schedule.mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		if destination.owner != vesting_schedule.authority {
+			return Err(ProgramError::IllegalOwner);
+		}
+		let decimals = unpack_mint(mint_info)?.decimals;
+		let transfer = |authority: &Pubkey| {
+			spl_token_2022::instruction::transfer_checked(
+				token_program_info.key,
+				source_info.key,
+				mint_info.key,
+				destination_info.key,
+				authority,
+				&[],
+				forfeited,
+				decimals,
+			)
+		};
+
+		match vesting_schedule.vault {
+			COption::Some(vault) => {
+				if *source_info.key != vault {
+					return Err(ProgramError::InvalidAccountData);
+				}
+				if unpack_token_account(source_info)?.amount < forfeited {
+					return Err(VestingError::InsufficientVaultBalance.into());
+				}
+				assert_vesting_schedule_address(
+					program_id,
+					vesting_schedule_info,
+					&vesting_schedule,
+				)?;
+				invoke_as_schedule(
+					&transfer(vesting_schedule_info.key)?,
+					&[
+						source_info.clone(),
+						mint_info.clone(),
+						destination_info.clone(),
+						vesting_schedule_info.clone(),
+						token_program_info.clone(),
+					],
+					&vesting_schedule,
+				)
+				.map_err(map_token_error)?;
+			}
+			COption::None => {
+				let (address, bump) = find_vesting_account_address(
+					program_id,
+					vesting_schedule_info.key,
+					&vesting_account.owner,
+				);
+				if address != *vesting_account_info.key {
+					return Err(ProgramError::InvalidSeeds);
+				}
+				if *source_info.key
+					!= get_associated_token_address_with_program_id(
+						vesting_account_info.key,
+						mint_info.key,
+						token_program_info.key,
+					) {
+					return Err(ProgramError::InvalidAccountData);
+				}
+				if unpack_token_account(source_info)?.amount < forfeited {
+					return Err(VestingError::InsufficientTokenBalance.into());
+				}
+				invoke_signed(
+					&transfer(vesting_account_info.key)?,
+					&[
+						source_info.clone(),
+						mint_info.clone(),
+						destination_info.clone(),
+						vesting_account_info.clone(),
+						token_program_info.clone(),
+					],
+					&[&[
+						VESTING_ACCOUNT_SEED,
+						vesting_schedule_info.key.as_ref(),
+						vesting_account.owner.as_ref(),
+						&[bump],
+					]],
+				)
+				.map_err(map_token_error)?;
+			}
+		}
+
+		// Anchoring at everything vested leaves nothing further to emit
+		msg!(
+			"{} forfeited {} of {}",
+			vesting_account.owner,
+			forfeited,
+			vesting_account.amount
+		);
+		vesting_account.amount = vested;
+		vesting_account.vested_at_anchor = vested;
+		vesting_account.anchored_at = now;
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())?;
+		Ok(())
+	}
+
+	fn process_rotate_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+		let payer_info = next_account_info(account_info_iter)?;
+		let mint_info = next_account_info(account_info_iter)?;
+		let vault_info = next_account_info(account_info_iter)?;
+		let new_vault_info = next_account_info(account_info_iter)?;
+		let system_program_info = next_required_account(account_info_iter, "system program")?;
+		let token_program_info = next_required_account(account_info_iter, "token program")?;
+
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		assert_program_id(system_program_info, &system_program::id())?;
+		assert_token_program(token_program_info)?;
+		let mut vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		if *mint_info.key != vesting_schedule.mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		let COption::Some(vault) = vesting_schedule.vault else {
+			return Err(VestingError::NoVault.into());
+		};
+		if *vault_info.key != vault {
+			return Err(VestingError::InvalidVault.into());
+		}
+		if vault_info.owner != token_program_info.key {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let (new_vault, bump) = find_vault_address(program_id, vesting_schedule_info.key, &vault);
+		if *new_vault_info.key != new_vault {
+			return Err(VestingError::InvalidVault.into());
+		}
+		assert_vesting_schedule_address(program_id, vesting_schedule_info, &vesting_schedule)?;
+
+		// The new vault is a plain token account at a program address, the schedule's ATA being
+		// the one address an ATA of the schedule could have
+		let space = {
+			let mint_data = mint_info.data.borrow();
+			let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+			ExtensionType::try_calculate_account_len::<TokenAccount>(
+				&ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?),
+			)?
+		};
+		invoke_signed(
+			&system_instruction::create_account(
+				payer_info.key,
+				new_vault_info.key,
+				Rent::get()?.minimum_balance(space),
+				space as u64,
+				token_program_info.key,
+			),
+			&[
+				payer_info.clone(),
+				new_vault_info.clone(),
+				system_program_info.clone(),
+			],
+			&[&[
+				VAULT_SEED,
+				vesting_schedule_info.key.as_ref(),
+				vault.as_ref(),
+				&[bump],
+			]],
+		)?;
+		invoke(
+			&spl_token_2022::instruction::initialize_account3(
+				token_program_info.key,
+				new_vault_info.key,
+				mint_info.key,
+				vesting_schedule_info.key,
+			)?,
+			&[
+				new_vault_info.clone(),
+				mint_info.clone(),
+				token_program_info.clone(),
+			],
+		)?;
+
+		let balance = unpack_token_account(vault_info)?.amount;
+		invoke_as_schedule(
+			&spl_token_2022::instruction::transfer_checked(
+				token_program_info.key,
+				vault_info.key,
+				mint_info.key,
+				new_vault_info.key,
+				vesting_schedule_info.key,
+				&[],
+				balance,
+				unpack_mint(mint_info)?.decimals,
+			)?,
+			&[
+				vault_info.clone(),
+				mint_info.clone(),
+				new_vault_info.clone(),
+				vesting_schedule_info.clone(),
+				token_program_info.clone(),
+			],
+			&vesting_schedule,
+		)
+		.map_err(map_token_error)?;
+		invoke_as_schedule(
+			&spl_token_2022::instruction::close_account(
+				token_program_info.key,
+				vault_info.key,
+				authority_info.key,
+				vesting_schedule_info.key,
+				&[],
+			)?,
+			&[
+				vault_info.clone(),
+				authority_info.clone(),
+				vesting_schedule_info.clone(),
+				token_program_info.clone(),
+			],
+			&vesting_schedule,
+		)
+		.map_err(map_token_error)?;
+
+		msg!("Vault rotated from {} to {}", vault, new_vault);
+		vesting_schedule.vault = COption::Some(new_vault);
+		VestingSchedule::pack(
+			vesting_schedule,
+			&mut vesting_schedule_info.data.borrow_mut(),
+		)?;
+		Ok(())
+	}
+
+	fn process_migrate_mint(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		new_mint: Pubkey,
+		ratio_num: u64,
+		ratio_den: u64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+		let mint_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let mut vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		if vesting_schedule.immutable {
+			return Err(VestingError::ScheduleImmutable.into());
+		}
+		// Accounts can't be enumerated on-chain, so there's no telling whether any are still on
+		// the previous mint, and they could only be rebound from it by the latest ratio
+		if vesting_schedule.previous_mint.is_some() {
+			return Err(VestingError::ScheduleAlreadyMigrated.into());
+		}
+		if ratio_num == 0 || ratio_den == 0 {
+			return Err(VestingError::InvalidMigrationRatio.into());
+		}
+		if *mint_info.key != new_mint || new_mint == vesting_schedule.mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		if !is_token_program(mint_info.owner) {
+			return Err(ProgramError::IllegalOwner);
+		}
+
+		let old_mint = vesting_schedule.mint;
+		vesting_schedule.previous_mint = COption::Some(old_mint);
+		vesting_schedule.mint = new_mint;
+		vesting_schedule.migration_ratio_num = ratio_num;
+		vesting_schedule.migration_ratio_den = ratio_den;
+		vesting_schedule.decimals = unpack_mint(mint_info)?.decimals;
+		vesting_schedule.pool_total =
+			vesting_schedule.migrated_amount(vesting_schedule.pool_total)?;
+
+		if let COption::Some(old_vault) = vesting_schedule.vault {
+			let vault_info = next_required_account(account_info_iter, "vault")?;
+			let old_vault_info = next_required_account(account_info_iter, "previous vault")?;
+			let old_mint_info = next_required_account(account_info_iter, "previous mint")?;
+			let destination_info = next_required_account(account_info_iter, "destination")?;
+			let system_program_info = next_required_account(account_info_iter, "system program")?;
+			let token_program_info = next_required_account(account_info_iter, "token program")?;
+			let associated_token_program_info =
+				next_required_account(account_info_iter, "associated token program")?;
+
+			assert_program_id(system_program_info, &system_program::id())?;
+			assert_token_program(token_program_info)?;
+			assert_program_id(
+				associated_token_program_info,
+				&spl_associated_token_account::id(),
+			)?;
+			let vault = get_associated_token_address_with_program_id(
+				vesting_schedule_info.key,
+				&new_mint,
+				token_program_info.key,
+			);
+			if *vault_info.key != vault || *old_vault_info.key != old_vault {
+				return Err(VestingError::InvalidVault.into());
+			}
+			if *old_mint_info.key != old_mint {
+				return Err(VestingError::InvalidMint.into());
+			}
+			if old_vault_info.owner != token_program_info.key {
+				return Err(ProgramError::IncorrectProgramId);
+			}
+			assert_vesting_schedule_address(program_id, vesting_schedule_info, &vesting_schedule)?;
+			create_ata_if_missing(
+				authority_info,
+				vesting_schedule_info,
+				mint_info,
+				vault_info,
+				system_program_info,
+				token_program_info,
+				associated_token_program_info,
+			)?;
+
+			// The new vault has to be funded with the old vault's balance converted at the ratio
+			// before the old tokens are handed back, so claims carry on uninterrupted
+			let balance = unpack_token_account(old_vault_info)?.amount;
+			if unpack_token_account(vault_info)?.amount
+				< vesting_schedule.migrated_amount(balance)?
+			{
+				return Err(VestingError::InsufficientTokenBalance.into());
+			}
+			if balance > 0 {
+				invoke_as_schedule(
+					&spl_token_2022::instruction::transfer_checked(
+						token_program_info.key,
+						old_vault_info.key,
+						old_mint_info.key,
+						destination_info.key,
+						vesting_schedule_info.key,
+						&[],
+						balance,
+						unpack_mint(old_mint_info)?.decimals,
+					)?,
+					&[
+						old_vault_info.clone(),
+						old_mint_info.clone(),
+						destination_info.clone(),
+						vesting_schedule_info.clone(),
+						token_program_info.clone(),
+					],
+					&vesting_schedule,
+				)
+				.map_err(map_token_error)?;
+			}
+			invoke_as_schedule(
+				&spl_token_2022::instruction::close_account(
+					token_program_info.key,
+					old_vault_info.key,
+					authority_info.key,
+					vesting_schedule_info.key,
+					&[],
+				)?,
+				&[
+					old_vault_info.clone(),
+					authority_info.clone(),
+					vesting_schedule_info.clone(),
+					token_program_info.clone(),
+				],
+				&vesting_schedule,
+			)
+			.map_err(map_token_error)?;
+			vesting_schedule.vault = COption::Some(vault);
+		}
+
+		msg!(
+			"Migrated from {} to {} at {}:{}",
+			old_mint,
+			new_mint,
+			ratio_num,
+			ratio_den
+		);
+		VestingSchedule::pack(
+			vesting_schedule,
+			&mut vesting_schedule_info.data.borrow_mut(),
+		)
+	}
+
+	fn process_migrate_account_mint(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+		let mut vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+		let old_mint = vesting_account.mint;
+		vesting_schedule.migrate_account(&mut vesting_account)?;
+
+		// Pre-loaded tokens are replaced the same way as a vault's, the account's ATA of the new
+		// mint has to hold what remains to be claimed before the old ATA is swept and closed
+		if vesting_schedule.vault.is_none() {
+			let old_ata_info = next_required_account(account_info_iter, "previous ATA")?;
+			let old_mint_info = next_required_account(account_info_iter, "previous mint")?;
+			let ata_info = next_required_account(account_info_iter, "vesting account ATA")?;
+			let destination_info = next_required_account(account_info_iter, "destination")?;
+			let rent_payer_info = next_required_account(account_info_iter, "rent payer")?;
+			let token_program_info = next_required_account(account_info_iter, "token program")?;
+
+			assert_token_program(token_program_info)?;
+			if *old_mint_info.key != old_mint {
+				return Err(VestingError::InvalidMint.into());
+			}
+			if *rent_payer_info.key != vesting_account.rent_payer {
+				return Err(ProgramError::InvalidArgument);
+			}
+			let (address, bump) = find_vesting_account_address(
+				program_id,
+				vesting_schedule_info.key,
+				&vesting_account.owner,
+			);
+			if address != *vesting_account_info.key {
+				return Err(ProgramError::InvalidSeeds);
+			}
+			let ata_address = |mint: &Pubkey| {
+				get_associated_token_address_with_program_id(
+					vesting_account_info.key,
+					mint,
+					token_program_info.key,
+				)
+			};
+			if *old_ata_info.key != ata_address(&old_mint)
+				|| *ata_info.key != ata_address(&vesting_schedule.mint)
+			{
+				return Err(ProgramError::InvalidAccountData);
+			}
+			let ata = unpack_token_account(ata_info)?;
+			if ata.owner != *vesting_account_info.key
+				|| ata.delegate.is_some()
+				|| ata.close_authority.is_some()
+			{
+				return Err(VestingError::TokenOwnerMismatch.into());
+			}
+			if ata.amount < vesting_account.amount - vesting_account.claimed {
+				return Err(VestingError::InsufficientTokenBalance.into());
+			}
+
+			let seeds: &[&[u8]] = &[
+				VESTING_ACCOUNT_SEED,
+				vesting_schedule_info.key.as_ref(),
+				vesting_account.owner.as_ref(),
+				&[bump],
+			];
+			let balance = unpack_token_account(old_ata_info)?.amount;
+			if balance > 0 {
+				invoke_signed(
+					&spl_token_2022::instruction::transfer_checked(
+						token_program_info.key,
+						old_ata_info.key,
+						old_mint_info.key,
+						destination_info.key,
+						vesting_account_info.key,
+						&[],
+						balance,
+						unpack_mint(old_mint_info)?.decimals,
+					)?,
+					&[
+						old_ata_info.clone(),
+						old_mint_info.clone(),
+						destination_info.clone(),
+						vesting_account_info.clone(),
+						token_program_info.clone(),
+					],
+					&[seeds],
+				)
+				.map_err(map_token_error)?;
+			}
+			invoke_signed(
+				&spl_token_2022::instruction::close_account(
+					token_program_info.key,
+					old_ata_info.key,
+					rent_payer_info.key,
+					vesting_account_info.key,
+					&[],
+				)?,
+				&[
+					old_ata_info.clone(),
+					rent_payer_info.clone(),
+					vesting_account_info.clone(),
+					token_program_info.clone(),
+				],
+				&[seeds],
+			)
+			.map_err(map_token_error)?;
+		}
+		Account::pack(vesting_account, &mut vesting_account_info.data.borrow_mut())
+	}
+
+	fn process_close_vesting_schedule(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let authority_info = next_account_info(account_info_iter)?;
+		let system_program_info = next_required_account(account_info_iter, "system program")?;
+		let vault_info = next_account_info(account_info_iter)?;
+		let token_program_info = next_required_account(account_info_iter, "token program")?;
+
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		assert_program_id(system_program_info, &system_program::id())?;
+		assert_token_program(token_program_info)?;
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		if !authority_info.is_signer || *authority_info.key != vesting_schedule.authority {
+			return Err(VestingError::InvalidAuthority.into());
+		}
+
+		// Vesting accounts can't be enumerated on-chain, without a vault there's no way to tell
+		// whether any of them still hold tokens
+		let COption::Some(vault) = vesting_schedule.vault else {
+			return Err(VestingError::NoVault.into());
+		};
+		if *vault_info.key != vault {
+			return Err(ProgramError::InvalidAccountData);
+		}
+		if vault_info.owner != token_program_info.key {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let vault = unpack_token_account(vault_info)?;
+		if vault.mint != vesting_schedule.mint {
+			return Err(VestingError::InvalidMint.into());
+		}
+		if vault.amount != 0 {
+			return Err(VestingError::ScheduleNotEmpty.into());
+		}
+
+		assert_vesting_schedule_address(program_id, vesting_schedule_info, &vesting_schedule)?;
+		let vault_lamports = vault_info.lamports();
+		invoke_as_schedule(
+			&spl_token_2022::instruction::close_account(
+				token_program_info.key,
+				vault_info.key,
+				authority_info.key,
+				vesting_schedule_info.key,
+				&[],
+			)?,
+			&[
+				vault_info.clone(),
+				authority_info.clone(),
+				vesting_schedule_info.clone(),
+				token_program_info.clone(),
+			],
+			&vesting_schedule,
+		)
+		.map_err(map_token_error)?;
+
+		let lamports = vesting_schedule_info.lamports();
+		**authority_info.try_borrow_mut_lamports()? = authority_info
+			.lamports()
+			.checked_add(lamports)
+			.ok_or(VestingError::Overflow)?;
+		**vesting_schedule_info.try_borrow_mut_lamports()? = 0;
+		vesting_schedule_info.data.borrow_mut().fill(0);
+		let receipt = CloseReceipt {
+			lamports_refunded: lamports.saturating_add(vault_lamports),
+			tokens_swept: 0,
+		};
+		set_return_data(&receipt.pack());
+		Ok(())
+	}
+
+	fn process_get_claimable_many(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let vesting_account_infos = account_info_iter.as_slice();
+
+		if vesting_account_infos.len() > MAX_CLAIMABLE_QUERY_ACCOUNTS {
+			return Err(VestingError::TooManyAccounts.into());
+		}
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		let now = Clock::get()?.unix_timestamp;
+
+		let mut claimable = Vec::with_capacity(vesting_account_infos.len() * 8);
+		for vesting_account_info in vesting_account_infos {
+			if vesting_account_info.owner != program_id {
+				return Err(ProgramError::IncorrectProgramId);
+			}
+			let vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+			if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+				return Err(ProgramError::InvalidAccountData);
+			}
+			let amount = vesting_schedule.account_claimable(&vesting_account, now)?;
+			claimable.extend_from_slice(&amount.to_le_bytes());
+		}
+		set_return_data(&claimable);
+		Ok(())
+	}
+
+	fn process_get_claimable(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		let vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+		let now = Clock::get()?.unix_timestamp;
+		let amount = vesting_schedule.account_claimable(&vesting_account, now)?;
+		set_return_data(&amount.to_le_bytes());
+		Ok(())
+	}
+
+	fn process_sample_curve(
+		program_id: &Pubkey,
+		accounts: &[AccountInfo],
+		start_ts: UnixTimestamp,
+		end_ts: UnixTimestamp,
+		points: u16,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+		let vesting_account_info = next_account_info(account_info_iter)?;
+
+		if points > MAX_CURVE_POINTS {
+			return Err(ProgramError::InvalidArgument);
+		}
+		if vesting_schedule_info.owner != program_id || vesting_account_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		let vesting_account = unpack_initialized::<Account>(vesting_account_info)?;
+		if vesting_account.vesting_schedule != *vesting_schedule_info.key {
+			return Err(ProgramError::InvalidAccountData);
+		}
+
+		let curve = sample_curve(
+			&vesting_schedule,
+			&vesting_account,
+			start_ts,
+			end_ts,
+			points,
+		)?;
+		let mut data = Vec::with_capacity(curve.len() * 16);
+		for (at, vested) in curve {
+			data.extend_from_slice(&at.to_le_bytes());
+			data.extend_from_slice(&vested.to_le_bytes());
+		}
+		set_return_data(&data);
+		Ok(())
+	}
+
+	fn process_get_ui_context(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let vesting_schedule_info = next_account_info(account_info_iter)?;
+
+		if vesting_schedule_info.owner != program_id {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let vesting_schedule = unpack_initialized::<VestingSchedule>(vesting_schedule_info)?;
+		set_return_data(&[vesting_schedule.decimals]);
+		Ok(())
+	}
+
+	fn process_get_supported_instructions() -> ProgramResult {
+		set_return_data(&supported_instructions());
+		Ok(())
+	}
+}
+
+/// Checks the instruction before the current one is an ed25519 program instruction verifying
+/// `signature` over `message` by `signer`. The runtime has already rejected the transaction if
+/// the signature doesn't verify, so only that it covers what is expected needs checking.
+fn assert_ed25519_signed(
+	instructions_info: &AccountInfo,
+	signer: &Pubkey,
+	message: &[u8],
+	signature: &[u8; 64],
+) -> ProgramResult {
+	let current = load_current_index_checked(instructions_info)?;
+	let verify = match current.checked_sub(1) {
+		Some(index) => load_instruction_at_checked(usize::from(index), instructions_info)?,
+		None => return Err(VestingError::InvalidVoucher.into()),
+	};
+	if verify.program_id != ed25519_program::id() {
+		return Err(VestingError::InvalidVoucher.into());
+	}
+
+	// A single signature, its offsets following the count and a padding byte, all within the
+	// ed25519 instruction's own data rather than pointing into another instruction
+	let data = &verify.data;
+	if data.len() < 16 || data[0] != 1 {
+		return Err(VestingError::InvalidVoucher.into());
+	}
+	let offset = |at: usize| usize::from(u16::from_le_bytes([data[2 + at], data[3 + at]]));
+	if [offset(2), offset(6), offset(12)]
+		.iter()
+		.any(|&index| index != usize::from(u16::MAX))
+	{
+		return Err(VestingError::InvalidVoucher.into());
+	}
+	let field = |start: usize, len: usize| data.get(start..start.saturating_add(len));
+	if field(offset(4), PUBKEY_BYTES) != Some(signer.as_ref())
+		|| field(offset(0), signature.len()) != Some(&signature[..])
+		|| offset(10) != message.len()
+		|| field(offset(8), message.len()) != Some(message)
+	{
+		return Err(VestingError::InvalidVoucher.into());
+	}
+	Ok(())
+}
+
+/// Returns the next account, failing with `MissingRequiredAccount` and logging its `role` if the
+/// caller didn't pass it
+fn next_required_account<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
+	iter: &mut I,
+	role: &str,
+) -> Result<I::Item, ProgramError> {
+	iter.next().ok_or_else(|| {
+		msg!("Missing required account: {}", role);
+		VestingError::MissingRequiredAccount.into()
+	})
+}
+
+/// Checks that a program account passed for a CPI is the expected program, so a client can't
+/// substitute a program of its own
+fn assert_program_id(account: &AccountInfo, expected: &Pubkey) -> ProgramResult {
+	if account.key != expected {
+		return Err(VestingError::IncorrectProgramId.into());
+	}
+	Ok(())
+}
+
+/// Returns `true` for the legacy token program and Token-2022
+fn is_token_program(program_id: &Pubkey) -> bool {
+	*program_id == spl_token::id() || *program_id == spl_token_2022::id()
+}
+
+/// Checks the token program passed is one of the two known token programs, token CPIs are made
+/// against whichever of them the client passed so mints of either program are supported
+fn assert_token_program(account: &AccountInfo) -> ProgramResult {
+	if !is_token_program(account.key) {
+		return Err(VestingError::IncorrectProgramId.into());
+	}
+	Ok(())
+}
+
+/// Unpacks a program account of type `T`, failing with `NotInitialized` rather than the generic
+/// `UninitializedAccount` if it hasn't been initialised
+fn unpack_initialized<T: Pack + IsInitialized>(account: &AccountInfo) -> Result<T, ProgramError> {
+	let state = T::unpack_unchecked(&account.data.borrow())?;
+	if !state.is_initialized() {
+		return Err(VestingError::NotInitialized.into());
+	}
+	Ok(state)
+}
+
+/// Unpacks the base state of a token account of either token program, ignoring any Token-2022
+/// extensions
+fn unpack_token_account(account: &AccountInfo) -> Result<TokenAccount, ProgramError> {
+	Ok(StateWithExtensions::<TokenAccount>::unpack(&account.data.borrow())?.base)
+}
+
+/// Unpacks the base state of a mint of either token program, ignoring any Token-2022 extensions
+fn unpack_mint(account: &AccountInfo) -> Result<Mint, ProgramError> {
+	Ok(StateWithExtensions::<Mint>::unpack(&account.data.borrow())?.base)
+}
+
+/// Checks `amended` is a valid schedule to replace `previous` from `at`, emitting over a whole
+/// number of periods as at initialisation. Amendments that would
+/// release everything at once by accident must be `force`d, accounts anchored to their creation
+/// don't vest from the schedule's start so aren't affected. Forced or not, the start can't be
+/// moved back to more than the schedule's `past_start_tolerance` before `at`.
+fn validate_amendment(
+	previous: &VestingSchedule,
+	amended: &VestingSchedule,
+	at: UnixTimestamp,
+	force: bool,
+) -> ProgramResult {
+	amended.validate()?;
+	amended.total_periods()?;
+	if amended.start < previous.start
+		&& amended.start < at.saturating_sub(previous.past_start_tolerance)
+	{
+		msg!(
+			"Start is more than {}s in the past",
+			previous.past_start_tolerance
+		);
+		return Err(VestingError::StartTooFarInPast.into());
+	}
+	let was_complete = previous.completion_timestamp().is_none_or(|end| end <= at);
+	let is_complete = amended.completion_timestamp().is_some_and(|end| end <= at);
+	if !force && !amended.anchor_to_creation && !was_complete && is_complete {
+		return Err(VestingError::InvalidSchedule.into());
+	}
+	Ok(())
+}
+
+/// Verifies `vesting_schedule_info` is the address created from the schedule's seed mint,
+/// identifier and stored bump seed, i.e. the address [`invoke_as_schedule`] signs for
+fn assert_vesting_schedule_address(
+	program_id: &Pubkey,
+	vesting_schedule_info: &AccountInfo,
+	vesting_schedule: &VestingSchedule,
+) -> ProgramResult {
+	let address = create_vesting_schedule_address(
+		program_id,
+		&vesting_schedule.seed_mint,
+		&vesting_schedule.identifier,
+		vesting_schedule.bump,
+	)
+	.map_err(|_| ProgramError::InvalidSeeds)?;
+	if address != *vesting_schedule_info.key {
+		return Err(ProgramError::InvalidSeeds);
+	}
+	Ok(())
+}
+
+/// Invokes `instruction` signed by the vesting schedule, see [`schedule_signer_seeds`]
+fn invoke_as_schedule(
+	instruction: &Instruction,
+	account_infos: &[AccountInfo],
+	vesting_schedule: &VestingSchedule,
+) -> ProgramResult {
+	invoke_signed(
+		instruction,
+		account_infos,
+		&[&schedule_signer_seeds(vesting_schedule)],
+	)
+}
+
+/// Maps the token program's failures that a caller can act on to their [`VestingError`]
+/// counterparts, passing anything else through unchanged. This only takes effect where a failed
+/// CPI returns to its caller, as it does natively, on-chain the runtime aborts the transaction
+/// instead, which is why claims also check their token accounts before transferring
+fn map_token_error(error: ProgramError) -> ProgramError {
+	let ProgramError::Custom(code) = error else {
+		return error;
+	};
+	match TokenError::from_u32(code) {
+		Some(TokenError::InsufficientFunds) => VestingError::InsufficientTokenBalance.into(),
+		Some(TokenError::AccountFrozen) => VestingError::TokenAccountFrozen.into(),
+		Some(TokenError::OwnerMismatch) => VestingError::TokenOwnerMismatch.into(),
+		_ => error,
+	}
+}
+
+/// Creates `wallet`'s ATA for `mint`, paid for by `payer`, unless it already exists
+fn create_ata_if_missing<'a>(
+	payer_info: &AccountInfo<'a>,
+	wallet_info: &AccountInfo<'a>,
+	mint_info: &AccountInfo<'a>,
+	ata_info: &AccountInfo<'a>,
+	system_program_info: &AccountInfo<'a>,
+	token_program_info: &AccountInfo<'a>,
+	associated_token_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+	if !ata_info.data_is_empty() {
+		return Ok(());
 	}
+	invoke(
+		&create_associated_token_account(
+			payer_info.key,
+			wallet_info.key,
+			mint_info.key,
+			token_program_info.key,
+		),
+		&[
+			payer_info.clone(),
+			ata_info.clone(),
+			wallet_info.clone(),
+			mint_info.clone(),
+			system_program_info.clone(),
+			token_program_info.clone(),
+			associated_token_program_info.clone(),
+		],
+	)
 }
@@ -0,0 +1,65 @@
+//! Serde and Borsh helpers for client-side representations of program state. Neither is the
+//! on-chain encoding, which is defined by each state struct's `Pack` implementation.
+
+/// (De)serializes a `Pubkey` as its base58 string
+#[cfg(feature = "serde")]
+pub mod pubkey_string {
+	use {
+		serde::{de::Error, Deserialize, Deserializer, Serializer},
+		solana_program::pubkey::Pubkey,
+		std::str::FromStr,
+	};
+
+	pub fn serialize<S: Serializer>(key: &Pubkey, s: S) -> Result<S::Ok, S::Error> {
+		s.serialize_str(&key.to_string())
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Pubkey, D::Error> {
+		let s = String::deserialize(d)?;
+		Pubkey::from_str(&s).map_err(D::Error::custom)
+	}
+}
+
+/// (De)serializes a `COption<Pubkey>` as `null` or its base58 string
+#[cfg(feature = "serde")]
+pub mod coption_pubkey {
+	use {
+		serde::{de::Error, Deserialize, Deserializer, Serializer},
+		solana_program::{program_option::COption, pubkey::Pubkey},
+		std::str::FromStr,
+	};
+
+	pub fn serialize<S: Serializer>(key: &COption<Pubkey>, s: S) -> Result<S::Ok, S::Error> {
+		match key {
+			COption::Some(key) => s.serialize_some(&key.to_string()),
+			COption::None => s.serialize_none(),
+		}
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<COption<Pubkey>, D::Error> {
+		Option::<String>::deserialize(d)?
+			.map(|s| Pubkey::from_str(&s).map_err(D::Error::custom))
+			.transpose()
+			.map(COption::from)
+	}
+}
+
+/// Borsh (de)serializes a `COption<Pubkey>` as an `Option<Pubkey>`
+#[cfg(feature = "borsh")]
+pub mod borsh_coption_pubkey {
+	use {
+		borsh::{
+			io::{Read, Result, Write},
+			BorshDeserialize, BorshSerialize,
+		},
+		solana_program::{program_option::COption, pubkey::Pubkey},
+	};
+
+	pub fn serialize<W: Write>(key: &COption<Pubkey>, writer: &mut W) -> Result<()> {
+		Option::<Pubkey>::from(*key).serialize(writer)
+	}
+
+	pub fn deserialize<R: Read>(reader: &mut R) -> Result<COption<Pubkey>> {
+		Option::<Pubkey>::deserialize_reader(reader).map(COption::from)
+	}
+}
@@ -1,36 +1,45 @@
 use {
+	crate::{error::VestingError, IDENTIFIER_LEN, LABEL_LEN},
 	arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
 	num_enum::{TryFromPrimitive, IntoPrimitive},
+	num_traits::{CheckedDiv, CheckedMul},
 	solana_program::{
-		clock::UnixTimestamp,
+		clock::{Epoch, UnixTimestamp},
 		program_error::ProgramError,
 		program_option::COption,
 		program_pack::{IsInitialized, Pack, Sealed},
 		pubkey::Pubkey,
 	},
+	spl_token_2022::{
+		extension::{
+			transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+		},
+		state::Mint,
+	},
 };
 
 // Some projects may have a `token generation event`, logic for this is not handled explicitly in
-// this program - you may instead choose one of two approaches if your project has a TGE.
+// this program - you may instead choose one of three approaches if your project has a TGE.
 // 1. Create a pre-sale with no purchase price for whitelisted accounts utilising the whitelist
 //    program [`https://github.com/serfrae/fsp-whitelist`], it is however, not advisable to utilise
 //    this appraoch as the logic for the pre-sale assumes that there is only one pre-sale for a
 //    particular token and utilises the mint to derive program addresses.
 // 2. Create a vesting schedule with this program that has no duration and a frequency of
 //    `Once` therefore emitting all the tokens in the vesting account upon commencement.
+// 3. Set `immediate_bps` on each vesting account, releasing that share of its amount at the
+//    start and vesting the remainder over the schedule, e.g. 20% at TGE and the rest over 2 years.
 //
 // This program also expects you to pre-load the token account of the vesting account to enable
 // parallel execution and will calculate the amount claimable at a certain timestamp as the total
 // amount a user should be able to claim - the amount already claimed (balance) - total amount a
 // user should be able to claim - tokens emitted since the start of vesting.
 // The formula used is:
-// amount / (duration / frequency) = emissions_per_period
 // current_timestamp - starting_timestamp / frequency = number_of_elapsed_periods
-// emissions_per_period * number_of_elapsed_periods = emitted_tokens
+// amount * number_of_elapsed_periods / (duration / frequency) = emitted_tokens
 // claimed_tokens = total_amount - current_balance
 // claimable_amount = emitted_tokens - claimed_tokens
 // or
-// c = ((tc - ts) / f) * (a / (d/f)) - (a - b)
+// c = a * ((tc - ts) / f) / (d/f) - (a - b)
 //
 // Since there may exist multiple vesting schedules for a single token, there isn't a
 // solution utilising account data that will allow for a deterministic address as nearly all
@@ -39,6 +48,11 @@ use {
 // hashed and the first 8 bytes of the hash is used as the identifier
 #[repr(u8)]
 #[derive(Clone, Copy, Default, Debug, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "borsh",
+	derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
 pub enum Frequency {
 	Once,
 	#[default]
@@ -51,17 +65,108 @@ pub enum Frequency {
 	Month,
 	Quarter,
 	Year,
+	/// Accrues continuously in proportion to the time elapsed rather than in whole periods
+	Linear,
+}
+
+/// Approximate slot length in milliseconds used for [`Frequency::Slot`], the network's target
+/// slot time. Actual slot times vary, so slot schedules are timed against the clock's unix
+/// timestamp at this rate rather than counting produced slots. The timestamp has one-second
+/// resolution, so slot periods are released in bursts of two or three per second (five every two
+/// seconds) and a slot schedule's duration must be a multiple of two seconds.
+pub const SLOT_MILLISECONDS: i64 = 400;
+
+/// Basis points making up a whole
+pub const MAX_BPS: u16 = 10_000;
+
+impl Frequency {
+	/// Length of a single emission period in seconds, `None` for `Once` which has no period.
+	///
+	/// Months, quarters and years are fixed approximations based on the mean Gregorian year of
+	/// 365.2425 days. `Linear` counts periods in seconds, although it accrues without flooring
+	/// to them. `Slot` periods are shorter than a second and round up to one here, see
+	/// [`Self::as_millis`] for the length emissions are actually counted in.
+	pub fn as_seconds(&self) -> Option<i64> {
+		match self {
+			Self::Once => None,
+			Self::Slot => Some(1),
+			Self::Second => Some(1),
+			Self::Minute => Some(60),
+			Self::Hour => Some(3_600),
+			Self::Day => Some(86_400),
+			Self::Week => Some(604_800),
+			Self::Month => Some(2_629_746),
+			Self::Quarter => Some(7_889_238),
+			Self::Year => Some(31_556_952),
+			Self::Linear => Some(1),
+		}
+	}
+
+	/// Length of a single emission period in milliseconds, `None` for `Once`. This is the exact
+	/// period periods are counted in, `Slot` periods are [`SLOT_MILLISECONDS`] long.
+	pub fn as_millis(&self) -> Option<i64> {
+		match self {
+			Self::Slot => Some(SLOT_MILLISECONDS),
+			_ => self.as_seconds().map(|seconds| seconds * 1_000),
+		}
+	}
+
+	/// Duration in seconds of `emissions` whole periods, for schedules expressed as a number of
+	/// equal emissions. Fails with `InvalidSchedule` for `Once` and `Linear`, which have no
+	/// discrete emissions, if `emissions` is zero, or if the periods don't add up to whole seconds.
+	pub fn duration_of(&self, emissions: u32) -> Result<i64, ProgramError> {
+		let period = match self {
+			Self::Once | Self::Linear => None,
+			_ => self.as_millis(),
+		}
+		.filter(|_| emissions > 0)
+		.ok_or(VestingError::InvalidSchedule)?;
+		let duration = checked_mul(i64::from(emissions), period)?;
+		if duration % 1_000 != 0 {
+			return Err(VestingError::InvalidSchedule.into());
+		}
+		Ok(duration / 1_000)
+	}
+}
+
+/// Type of a program-owned account, stored in the first byte of its data so that accounts of one
+/// type can't be mistaken for another
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive, IntoPrimitive)]
+pub enum AccountType {
+	Uninitialized,
+	VestingSchedule,
+	Account,
+}
+
+impl AccountType {
+	/// Reads the account type from raw account data
+	pub fn of(data: &[u8]) -> Result<Self, ProgramError> {
+		data.first()
+			.and_then(|tag| Self::try_from_primitive(*tag).ok())
+			.ok_or(ProgramError::InvalidAccountData)
+	}
 }
 
 /// Veesting schedule data
+///
+/// The optional Serde and Borsh derives are for client convenience, the on-chain layout is the
+/// [`Pack`] implementation.
 #[repr(C)]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "borsh",
+	derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
 pub struct VestingSchedule {
 	/// Is `true` if this structure has been initialised
 	pub is_initialized: bool, // 1
 	/// Authority used to amend vesting details and close vesting accounts.
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialization::pubkey_string"))]
 	pub authority: Pubkey, // 33
 	/// The mint of vesting token
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialization::pubkey_string"))]
 	pub mint: Pubkey, // 65
 	/// Frequency of token emissions
 	pub frequency: Frequency, // 66
@@ -70,7 +175,585 @@ pub struct VestingSchedule {
 	/// Duration of the total vesting length in seconds
 	pub duration: i64, // 82
 	/// Optional vault used if tokens are not pre-loaded into vesting accounts
+	#[cfg_attr(
+		feature = "serde",
+		serde(with = "crate::serialization::coption_pubkey")
+	)]
+	#[cfg_attr(
+		feature = "borsh",
+		borsh(
+			serialize_with = "crate::serialization::borsh_coption_pubkey::serialize",
+			deserialize_with = "crate::serialization::borsh_coption_pubkey::deserialize"
+		)
+	)]
 	pub vault: COption<Pubkey>, // 118
+	/// If `true` each account vests from its own creation time rather than `start`
+	pub anchor_to_creation: bool, // 119
+	/// Hashed string identifier used as a seed for this schedule's program address, see
+	/// [`crate::hash_identifier`]
+	pub identifier: [u8; IDENTIFIER_LEN], // 127
+	/// Seconds after the vesting start during which nothing is claimable, once passed everything
+	/// accrued since the start becomes claimable
+	pub cliff: i64, // 135
+	/// Amendment scheduled by the authority to take effect at a later date, see
+	/// [`Self::effective`]
+	pub pending_amendment: Option<PendingAmendment>, // 164
+	/// Optional mint of which one token is minted to the recipient on each claim as a receipt,
+	/// the vesting schedule must be its mint authority
+	#[cfg_attr(
+		feature = "serde",
+		serde(with = "crate::serialization::coption_pubkey")
+	)]
+	#[cfg_attr(
+		feature = "borsh",
+		borsh(
+			serialize_with = "crate::serialization::borsh_coption_pubkey::serialize",
+			deserialize_with = "crate::serialization::borsh_coption_pubkey::deserialize"
+		)
+	)]
+	pub receipt_mint: COption<Pubkey>, // 200
+	/// Lamports paid to whoever cranks a claim on a recipient's behalf, out of lamports deposited
+	/// in this account above its rent-exempt minimum
+	pub tip_lamports: u64, // 208
+	/// Program notified via an `on_claim` callback when tokens are claimed, claimed tokens are
+	/// sent to an account it controls rather than to the recipient
+	#[cfg_attr(
+		feature = "serde",
+		serde(with = "crate::serialization::coption_pubkey")
+	)]
+	#[cfg_attr(
+		feature = "borsh",
+		borsh(
+			serialize_with = "crate::serialization::borsh_coption_pubkey::serialize",
+			deserialize_with = "crate::serialization::borsh_coption_pubkey::deserialize"
+		)
+	)]
+	pub manager_program: COption<Pubkey>, // 244
+	/// Bump seed of this schedule's program address, stored so the program can recreate the
+	/// address with [`crate::create_vesting_schedule_address`] rather than searching for it
+	pub bump: u8, // 245
+	/// Seconds `start` could lie in the past when the schedule was initialised, chosen by its
+	/// creator
+	pub past_start_tolerance: i64, // 253
+	/// Total tokens shared out by basis points with `CreateAccountByBps`, zero if the schedule
+	/// has no pool
+	pub pool_total: u64, // 261
+	/// Basis points of `pool_total` allocated so far
+	pub allocated_bps: u16, // 263
+	/// Human readable UTF-8 label padded with spaces, purely descriptive and unlike `identifier`
+	/// not part of the schedule's address, see [`crate::pad_label`]
+	pub label: [u8; LABEL_LEN], // 295
+	/// The mint's decimals when the schedule was initialised, so clients render amounts
+	/// consistently even if the mint they fetch has since been reconfigured
+	pub decimals: u8, // 296
+	/// Once `true` the authority has renounced amending the schedule and its accounts, which
+	/// can't be undone
+	pub immutable: bool, // 297
+	/// Mint this schedule's address was derived from, its `mint` when it was initialised. Stays
+	/// the same when the schedule migrates to a new mint so the schedule can still sign.
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialization::pubkey_string"))]
+	pub seed_mint: Pubkey, // 329
+	/// Mint the schedule last migrated from, accounts still on it are rebound to `mint` by
+	/// scaling their amounts by `migration_ratio_num / migration_ratio_den`
+	#[cfg_attr(
+		feature = "serde",
+		serde(with = "crate::serialization::coption_pubkey")
+	)]
+	#[cfg_attr(
+		feature = "borsh",
+		borsh(
+			serialize_with = "crate::serialization::borsh_coption_pubkey::serialize",
+			deserialize_with = "crate::serialization::borsh_coption_pubkey::deserialize"
+		)
+	)]
+	pub previous_mint: COption<Pubkey>, // 365
+	/// Tokens of `mint` given for `migration_ratio_den` tokens of `previous_mint`
+	pub migration_ratio_num: u64, // 373
+	/// See `migration_ratio_num`
+	pub migration_ratio_den: u64, // 381
+	/// Basis points of each account's amount released in a lump at `start + cliff`, at most
+	/// `MAX_BPS` together with the account's `immediate_bps`. The rest vests from the cliff on.
+	pub cliff_bps: u16, // 383
+}
+
+/// An amendment to a vesting schedule's timing that takes effect at `effective_at`, fields left
+/// as `None` are unchanged
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "borsh",
+	derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+pub struct PendingAmendment {
+	/// Timestamp from which the amendment applies
+	pub effective_at: UnixTimestamp,
+	/// New vesting start
+	pub start: Option<UnixTimestamp>,
+	/// New emission frequency
+	pub frequency: Option<Frequency>,
+	/// New vesting duration
+	pub duration: Option<i64>,
+}
+
+impl VestingSchedule {
+	/// Timestamp at which the final tokens are emitted, `None` if it overflows an `i64`
+	pub fn completion_timestamp(&self) -> Option<UnixTimestamp> {
+		match self.frequency {
+			Frequency::Once => Some(self.start),
+			_ => self.start.checked_add(self.duration),
+		}
+	}
+
+	/// Timestamp at which the cliff ends, `None` if it overflows an `i64`
+	pub fn cliff_timestamp(&self) -> Option<UnixTimestamp> {
+		self.start.checked_add(self.cliff)
+	}
+
+	/// Number of periods tokens are emitted over, `Once` schedules have a single period. Fails
+	/// with `InvalidSchedule` if the duration isn't a whole number of periods, since the remainder
+	/// would otherwise be left over until the schedule completes.
+	pub fn total_periods(&self) -> Result<u64, ProgramError> {
+		self.check_duration()?;
+		match self.frequency.as_millis() {
+			Some(period) => {
+				let duration = checked_mul(self.duration, 1_000)?;
+				if duration % period != 0 {
+					return Err(VestingError::InvalidSchedule.into());
+				}
+				u64::try_from(duration / period).map_err(|_| VestingError::InvalidSchedule.into())
+			}
+			None => Ok(1),
+		}
+	}
+
+	/// Number of whole periods elapsed since `start` at `now`, at most the schedule's total number
+	/// of periods. `Once` schedules count a single period from `start`.
+	pub fn elapsed_periods(&self, now: UnixTimestamp) -> u64 {
+		if now < self.start {
+			return 0;
+		}
+		match self.frequency.as_millis() {
+			Some(period) => {
+				let total_periods = whole_periods(self.duration, period);
+				let elapsed_periods = whole_periods(now.saturating_sub(self.start), period);
+				elapsed_periods.min(total_periods)
+			}
+			None => 1,
+		}
+	}
+
+	/// Stage of the [`Self::effective`] schedule at `now`, schedules whose end overflows an `i64`
+	/// never complete
+	pub fn phase(&self, now: UnixTimestamp) -> Phase {
+		let schedule = self.effective(now);
+		if now < schedule.start {
+			Phase::NotStarted
+		} else if schedule
+			.cliff_timestamp()
+			.is_none_or(|cliff_end| now < cliff_end)
+		{
+			Phase::Cliff
+		} else if schedule.completion_timestamp().is_none_or(|end| now < end) {
+			Phase::Vesting
+		} else {
+			Phase::Completed
+		}
+	}
+
+	/// Share of each allocation emitted at `now` by the [`Self::effective`] schedule in basis
+	/// points, counted in whole periods as in [`Self::claimable_amount`] but without the
+	/// per-period rounding of a concrete amount
+	pub fn vested_bps(&self, now: UnixTimestamp) -> u16 {
+		let schedule = self.effective(now);
+		match (schedule.phase(now), schedule.frequency.as_millis()) {
+			(Phase::NotStarted | Phase::Cliff, _) => 0,
+			(Phase::Completed, _) | (Phase::Vesting, None) => MAX_BPS,
+			(Phase::Vesting, Some(period)) => {
+				let cliff_bps = schedule.cliff_bps.min(MAX_BPS);
+				let remainder = schedule.after_cliff();
+				let total_periods = whole_periods(remainder.duration, period);
+				match total_periods {
+					0 => cliff_bps,
+					_ => {
+						cliff_bps
+							+ (u128::from(remainder.elapsed_periods(now))
+								* u128::from(MAX_BPS - cliff_bps)
+								/ u128::from(total_periods)) as u16
+					}
+				}
+			}
+		}
+	}
+
+	/// Checks the schedule parameters are consistent, periodic schedules need a positive duration,
+	/// the cliff may not be negative or extend beyond the vesting duration and the cliff lump may
+	/// not exceed `MAX_BPS`
+	pub fn validate(&self) -> Result<(), ProgramError> {
+		if self.frequency != Frequency::Once && self.duration <= 0 {
+			return Err(VestingError::InvalidSchedule.into());
+		}
+		if self.cliff < 0 || self.cliff > self.duration {
+			return Err(VestingError::InvalidSchedule.into());
+		}
+		if self.cliff_bps > MAX_BPS {
+			return Err(VestingError::InvalidSchedule.into());
+		}
+		Ok(())
+	}
+
+	/// Share of `account`'s amount released in a lump at the cliff, see [`Self::cliff_bps`]
+	pub fn cliff_amount(&self, account: &Account) -> u64 {
+		let bps = self.cliff_bps.min(MAX_BPS);
+		(u128::from(account.amount) * u128::from(bps) / u128::from(MAX_BPS)) as u64
+	}
+
+	/// Schedule the rest of an allocation vests over once the cliff lump is released, running
+	/// from the cliff to completion with no cliff of its own. Unchanged if there is no lump, so
+	/// everything accrued since `start` becomes claimable at the cliff as before.
+	fn after_cliff(&self) -> VestingSchedule {
+		match self.cliff_bps {
+			0 => self.clone(),
+			_ => VestingSchedule {
+				start: self.start.saturating_add(self.cliff),
+				duration: self.duration.saturating_sub(self.cliff),
+				cliff: 0,
+				..self.clone()
+			},
+		}
+	}
+
+	/// Fails with `InvalidDuration` if a periodic schedule's duration is negative, which would
+	/// put its end before its start and release every token at once
+	fn check_duration(&self) -> Result<(), ProgramError> {
+		if self.frequency != Frequency::Once && self.duration < 0 {
+			return Err(VestingError::InvalidDuration.into());
+		}
+		Ok(())
+	}
+
+	/// Checks that an allocation of `amount` emits tokens every period, rejecting amounts that
+	/// round down to zero per period so nothing would vest until the schedule completes.
+	/// Increase the amount or use a coarser frequency if this fails.
+	pub fn validate_amount(&self, amount: u64) -> Result<(), ProgramError> {
+		self.check_duration()?;
+		if self.frequency == Frequency::Linear {
+			return Ok(());
+		}
+		if let Some(period) = self.frequency.as_millis() {
+			let total_periods = to_u64(checked_div(checked_mul(self.duration, 1_000)?, period)?)?;
+			if total_periods > 0 && checked_div(amount, total_periods)? == 0 {
+				return Err(VestingError::EmissionsRoundToZero.into());
+			}
+		}
+		Ok(())
+	}
+
+	/// Applies the `Some` fields of an amendment to the schedule
+	pub fn amend(
+		&mut self,
+		start: Option<UnixTimestamp>,
+		frequency: Option<Frequency>,
+		duration: Option<i64>,
+	) {
+		if let Some(start) = start {
+			self.start = start;
+		}
+		if let Some(frequency) = frequency {
+			self.frequency = frequency;
+		}
+		if let Some(duration) = duration {
+			self.duration = duration;
+		}
+	}
+
+	/// The schedule as it stands at `now`, with any pending amendment that has taken effect
+	/// applied and cleared
+	pub fn effective(&self, now: UnixTimestamp) -> VestingSchedule {
+		let mut schedule = self.clone();
+		if let Some(pending) = self.pending_amendment {
+			if now >= pending.effective_at {
+				schedule.amend(pending.start, pending.frequency, pending.duration);
+				schedule.pending_amendment = None;
+			}
+		}
+		schedule
+	}
+
+	/// Amount claimable at `now` from an allocation of `total_amount` of which `claimed` has
+	/// already been claimed, as described by the emission formula at the top of this module.
+	///
+	/// Tokens are emitted in whole periods from `start`, each period's exact share accruing so
+	/// only the fraction of a single token is ever held back. `Once` schedules release everything
+	/// at `start`, `Linear` schedules emit `amount * (now - start) / duration` without flooring to
+	/// whole periods. Nothing is claimable before `start + cliff`. Fails with `InvalidDuration`
+	/// if a periodic schedule's duration is negative.
+	pub fn claimable_amount(
+		&self,
+		now: UnixTimestamp,
+		total_amount: u64,
+		claimed: u64,
+	) -> Result<u64, ProgramError> {
+		self.check_duration()?;
+		let cliff_end = self.cliff_timestamp().ok_or(VestingError::Overflow)?;
+		if now < self.start || now < cliff_end {
+			return Ok(0);
+		}
+		let end = self.completion_timestamp().ok_or(VestingError::Overflow)?;
+		let emitted = match self.frequency.as_millis() {
+			Some(_) if now < end && self.frequency == Frequency::Linear => {
+				let emitted = checked_div(
+					checked_mul(
+						u128::from(total_amount),
+						u128::from(to_u64(
+							now.checked_sub(self.start).ok_or(VestingError::Overflow)?,
+						)?),
+					)?,
+					u128::from(to_u64(self.duration)?),
+				)?;
+				u64::try_from(emitted).map_err(|_| VestingError::Overflow)?
+			}
+			Some(period) if now < end => {
+				let total_periods =
+					to_u64(checked_div(checked_mul(self.duration, 1_000)?, period)?)?;
+				match total_periods {
+					0 => 0,
+					_ => {
+						let elapsed = self.elapsed_periods(now);
+						accrue(total_amount, elapsed, total_periods)?.0
+					}
+				}
+			}
+			_ => total_amount,
+		};
+		Ok(emitted
+			.saturating_sub(claimed)
+			.min(total_amount.saturating_sub(claimed)))
+	}
+
+	/// Timestamp from which `account` vests, its creation time if the schedule is anchored to
+	/// account creation, otherwise the schedule's `start`
+	pub fn account_start(&self, account: &Account) -> UnixTimestamp {
+		match self.anchor_to_creation {
+			true => account.created_at,
+			false => self.start,
+		}
+	}
+
+	/// Whether an account created at `now` would have vested in full straight away, never the
+	/// case for schedules anchored to account creation
+	pub fn is_fully_vested(&self, now: UnixTimestamp) -> bool {
+		!self.anchor_to_creation
+			&& self
+				.effective(now)
+				.completion_timestamp()
+				.is_some_and(|end| now >= end)
+	}
+
+	/// Amount `account` has vested at `now` regardless of what it has claimed, vesting from
+	/// [`Self::account_start`] of the [`Self::effective`] schedule. The account's immediate share
+	/// is released in full at its start, ahead of any cliff, and its [`Self::cliff_amount`] at the
+	/// cliff. Only the remainder is emitted over the schedule, from the cliff on if there is a
+	/// cliff lump. Once an account's amount has been amended, emissions continue from its anchor
+	/// instead, see [`Account::anchored_at`].
+	pub fn account_vested(
+		&self,
+		account: &Account,
+		now: UnixTimestamp,
+	) -> Result<u64, ProgramError> {
+		let effective = self.effective(now);
+		let schedule = VestingSchedule {
+			start: effective.account_start(account),
+			..effective
+		};
+		if account.anchored_at != 0 {
+			return schedule.vested_from_anchor(account, now);
+		}
+		if now < schedule.start {
+			return Ok(0);
+		}
+		let cliff_end = schedule.cliff_timestamp().ok_or(VestingError::Overflow)?;
+		let immediate = account.immediate_amount();
+		let lump = schedule.cliff_amount(account);
+		let released = match now < cliff_end {
+			true => immediate,
+			false => immediate + lump,
+		};
+		schedule
+			.after_cliff()
+			.claimable_amount(now, account.amount.saturating_sub(immediate + lump), 0)?
+			.checked_add(released)
+			.ok_or(VestingError::Overflow.into())
+	}
+
+	/// Fraction of a token `account` has accrued at `now` beyond what [`Self::account_vested`]
+	/// releases, as a numerator and denominator. Both are zero when nothing is pending, before
+	/// the cliff, once vesting completes or for accounts whose amount has been amended.
+	pub fn account_accrual(
+		&self,
+		account: &Account,
+		now: UnixTimestamp,
+	) -> Result<(u64, u64), ProgramError> {
+		let effective = self.effective(now);
+		let schedule = VestingSchedule {
+			start: effective.account_start(account),
+			..effective
+		};
+		let lump = schedule.cliff_amount(account);
+		let schedule = schedule.after_cliff();
+		schedule.check_duration()?;
+		let end = schedule
+			.completion_timestamp()
+			.ok_or(VestingError::Overflow)?;
+		let cliff_end = schedule.cliff_timestamp().ok_or(VestingError::Overflow)?;
+		if account.anchored_at != 0 || now < schedule.start || now < cliff_end || now >= end {
+			return Ok((0, 0));
+		}
+		let amount = account
+			.amount
+			.saturating_sub(account.immediate_amount() + lump);
+		let (elapsed, total) = match schedule.frequency.as_millis() {
+			Some(_) if schedule.frequency == Frequency::Linear => {
+				(to_u64(now - schedule.start)?, to_u64(schedule.duration)?)
+			}
+			Some(period) => (
+				schedule.elapsed_periods(now),
+				to_u64(checked_div(checked_mul(schedule.duration, 1_000)?, period)?)?,
+			),
+			None => return Ok((0, 0)),
+		};
+		match accrue(amount, elapsed, total.max(1))?.1 {
+			0 => Ok((0, 0)),
+			numerator => Ok((numerator, total)),
+		}
+	}
+
+	/// `amount` of `previous_mint` in tokens of `mint` at the migration ratio, rounding down
+	pub fn migrated_amount(&self, amount: u64) -> Result<u64, ProgramError> {
+		let scaled = checked_div(
+			checked_mul(u128::from(amount), u128::from(self.migration_ratio_num))?,
+			u128::from(self.migration_ratio_den),
+		)?;
+		u64::try_from(scaled).map_err(|_| VestingError::Overflow.into())
+	}
+
+	/// Rebinds `account` from `previous_mint` to `mint`, scaling its amounts by the migration
+	/// ratio. Amounts round down but `claimed` rounds up, so what remains to be claimed never
+	/// exceeds what the account's remaining tokens convert to. Fails with
+	/// `AccountAlreadyMigrated` if it is already on `mint`, and with `InvalidMint` if it is on
+	/// neither.
+	pub fn migrate_account(&self, account: &mut Account) -> Result<(), ProgramError> {
+		if account.mint == self.mint {
+			return Err(VestingError::AccountAlreadyMigrated.into());
+		}
+		if self.previous_mint != COption::Some(account.mint) {
+			return Err(VestingError::InvalidMint.into());
+		}
+		let claimed = self.migrated_amount(account.claimed)?;
+		let claimed = match u128::from(account.claimed) * u128::from(self.migration_ratio_num)
+			% u128::from(self.migration_ratio_den)
+		{
+			0 => claimed,
+			_ => claimed + 1,
+		};
+		account.amount = self.migrated_amount(account.amount)?;
+		account.claimed = claimed.min(account.amount);
+		account.vested_at_anchor = self.migrated_amount(account.vested_at_anchor)?;
+		account.max_claim_per_window = self.migrated_amount(account.max_claim_per_window)?;
+		account.claimed_in_window = self.migrated_amount(account.claimed_in_window)?;
+		account.mint = self.mint;
+		Ok(())
+	}
+
+	/// Amount claimable by `account` at `now`, see [`Self::account_vested`]
+	pub fn account_claimable(
+		&self,
+		account: &Account,
+		now: UnixTimestamp,
+	) -> Result<u64, ProgramError> {
+		Ok(self
+			.account_vested(account, now)?
+			.saturating_sub(account.claimed))
+	}
+
+	/// Amount vested by an anchored `account` at `now`, what had vested at the anchor plus the
+	/// rest of its amount emitted over the schedule's progress remaining after the anchor
+	fn vested_from_anchor(
+		&self,
+		account: &Account,
+		now: UnixTimestamp,
+	) -> Result<u64, ProgramError> {
+		self.check_duration()?;
+		let vested_at_anchor = account.vested_at_anchor.min(account.amount);
+		let end = self.completion_timestamp().ok_or(VestingError::Overflow)?;
+		let cliff_end = self.cliff_timestamp().ok_or(VestingError::Overflow)?;
+		if now >= end {
+			return Ok(account.amount);
+		}
+		if now < self.start || now < cliff_end {
+			return Ok(vested_at_anchor);
+		}
+		let (at_anchor, total) = self.progress(account.anchored_at);
+		let (at_now, _) = self.progress(now);
+		if at_anchor >= total {
+			return Ok(account.amount);
+		}
+		let emitted = checked_div(
+			checked_mul(
+				u128::from(account.amount - vested_at_anchor),
+				u128::from(at_now.saturating_sub(at_anchor)),
+			)?,
+			u128::from(total - at_anchor),
+		)?;
+		Ok(vested_at_anchor + emitted as u64)
+	}
+
+	/// Progress through the schedule at `now` as a count out of a total, whole periods or seconds
+	/// for `Linear` schedules
+	fn progress(&self, now: UnixTimestamp) -> (u64, u64) {
+		match self.frequency.as_millis() {
+			Some(_) if self.frequency == Frequency::Linear => {
+				let duration = u64::try_from(self.duration).unwrap_or(0);
+				let elapsed = u64::try_from(now.saturating_sub(self.start)).unwrap_or(0);
+				(elapsed.min(duration), duration)
+			}
+			Some(period) => (
+				self.elapsed_periods(now),
+				whole_periods(self.duration, period),
+			),
+			None => (self.elapsed_periods(now), 1),
+		}
+	}
+
+	/// Checks `acknowledged_forfeit` matches the amount `account` could claim at `now`, so that
+	/// closing an account can't forfeit vested tokens the caller hasn't confirmed giving up
+	pub fn verify_forfeit(
+		&self,
+		account: &Account,
+		now: UnixTimestamp,
+		acknowledged_forfeit: u64,
+	) -> Result<(), ProgramError> {
+		if self.account_claimable(account, now)? != acknowledged_forfeit {
+			return Err(VestingError::ForfeitAcknowledgementMismatch.into());
+		}
+		Ok(())
+	}
+
+	/// Allocates `bps` basis points of the schedule's pool, returning the amount of tokens they
+	/// are worth. Fails with `AllocationExceedsPool` if the schedule has no pool or the
+	/// allocations would exceed it.
+	pub fn allocate_bps(&mut self, bps: u16) -> Result<u64, ProgramError> {
+		let allocated_bps = self
+			.allocated_bps
+			.checked_add(bps)
+			.filter(|&allocated_bps| allocated_bps <= MAX_BPS)
+			.ok_or(VestingError::AllocationExceedsPool)?;
+		if self.pool_total == 0 {
+			return Err(VestingError::AllocationExceedsPool.into());
+		}
+		let amount = u128::from(self.pool_total) * u128::from(bps) / u128::from(MAX_BPS);
+		self.allocated_bps = allocated_bps;
+		Ok(amount as u64)
+	}
 }
 
 impl Sealed for VestingSchedule {}
@@ -80,22 +763,56 @@ impl IsInitialized for VestingSchedule {
 	}
 }
 impl Pack for VestingSchedule {
-	const LEN: usize = 118;
+	const LEN: usize = 383;
+	fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+		unpack_account_type(input, AccountType::VestingSchedule)?;
+		if input.len() != Self::LEN {
+			return Err(ProgramError::InvalidAccountData);
+		}
+		Self::unpack_from_slice(input)
+	}
+
 	fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-		let src = array_ref![src, 0, 118];
-		let (is_initialized, authority, mint, frequency, start, duration, vault) =
-			array_refs![src, 1, 32, 32, 1, 8, 8, 36];
-		let is_initialized = match is_initialized {
-			[0] => false,
-			[1] => true,
-			_ => return Err(ProgramError::InvalidAccountData),
-		};
+		let src = array_ref![src, 0, 383];
+		let (
+			is_initialized,
+			authority,
+			mint,
+			frequency,
+			start,
+			duration,
+			vault,
+			anchor_to_creation,
+			identifier,
+			cliff,
+			pending_amendment,
+			receipt_mint,
+			tip_lamports,
+			manager_program,
+			bump,
+			past_start_tolerance,
+			pool_total,
+			allocated_bps,
+			label,
+			decimals,
+			immutable,
+			seed_mint,
+			previous_mint,
+			migration_ratio_num,
+			migration_ratio_den,
+			cliff_bps,
+		) = array_refs![
+			src, 1, 32, 32, 1, 8, 8, 36, 1, 8, 8, 29, 36, 8, 36, 1, 8, 8, 2, 32, 1, 1, 32, 36, 8,
+			8, 2
+		];
+		let is_initialized = unpack_account_type(is_initialized, AccountType::VestingSchedule)?;
 		let authority = Pubkey::new_from_array(*authority);
 		let mint = Pubkey::new_from_array(*mint);
 		let frequency = Frequency::try_from_primitive(frequency[0]).or(Err(ProgramError::InvalidAccountData))?;
 		let start = i64::from_le_bytes(*start);
 		let duration = i64::from_le_bytes(*duration);
 		let vault = unpack_coption_key(vault)?;
+		let anchor_to_creation = unpack_bool(anchor_to_creation)?;
 		Ok(VestingSchedule {
 			is_initialized,
 			authority,
@@ -104,11 +821,30 @@ impl Pack for VestingSchedule {
 			start,
 			duration,
 			vault,
+			anchor_to_creation,
+			identifier: *identifier,
+			cliff: i64::from_le_bytes(*cliff),
+			pending_amendment: unpack_pending_amendment(pending_amendment)?,
+			receipt_mint: unpack_coption_key(receipt_mint)?,
+			tip_lamports: u64::from_le_bytes(*tip_lamports),
+			manager_program: unpack_coption_key(manager_program)?,
+			bump: bump[0],
+			past_start_tolerance: i64::from_le_bytes(*past_start_tolerance),
+			pool_total: u64::from_le_bytes(*pool_total),
+			allocated_bps: u16::from_le_bytes(*allocated_bps),
+			label: *label,
+			decimals: decimals[0],
+			immutable: unpack_bool(immutable)?,
+			seed_mint: Pubkey::new_from_array(*seed_mint),
+			previous_mint: unpack_coption_key(previous_mint)?,
+			migration_ratio_num: u64::from_le_bytes(*migration_ratio_num),
+			migration_ratio_den: u64::from_le_bytes(*migration_ratio_den),
+			cliff_bps: u16::from_le_bytes(*cliff_bps),
 		})
 	}
 
 	fn pack_into_slice(&self, dst: &mut [u8]) {
-		let dst = array_mut_ref![dst, 0, 118];
+		let dst = array_mut_ref![dst, 0, 383];
 		let (
 			is_initialized_dst,
 			authority_dst,
@@ -117,7 +853,29 @@ impl Pack for VestingSchedule {
 			start_dst,
 			duration_dst,
 			vault_dst,
-		) = mut_array_refs![dst, 1, 32, 32, 1, 8, 8, 36];
+			anchor_to_creation_dst,
+			identifier_dst,
+			cliff_dst,
+			pending_amendment_dst,
+			receipt_mint_dst,
+			tip_lamports_dst,
+			manager_program_dst,
+			bump_dst,
+			past_start_tolerance_dst,
+			pool_total_dst,
+			allocated_bps_dst,
+			label_dst,
+			decimals_dst,
+			immutable_dst,
+			seed_mint_dst,
+			previous_mint_dst,
+			migration_ratio_num_dst,
+			migration_ratio_den_dst,
+			cliff_bps_dst,
+		) = mut_array_refs![
+			dst, 1, 32, 32, 1, 8, 8, 36, 1, 8, 8, 29, 36, 8, 36, 1, 8, 8, 2, 32, 1, 1, 32, 36, 8,
+			8, 2
+		];
 		let &VestingSchedule {
 			is_initialized,
 			ref authority,
@@ -126,26 +884,177 @@ impl Pack for VestingSchedule {
 			start,
 			duration,
 			ref vault,
+			anchor_to_creation,
+			ref identifier,
+			cliff,
+			pending_amendment,
+			ref receipt_mint,
+			tip_lamports,
+			ref manager_program,
+			bump,
+			past_start_tolerance,
+			pool_total,
+			allocated_bps,
+			label,
+			decimals,
+			immutable,
+			ref seed_mint,
+			ref previous_mint,
+			migration_ratio_num,
+			migration_ratio_den,
+			cliff_bps,
 		} = self;
-		is_initialized_dst[0] = is_initialized as u8;
+		is_initialized_dst[0] = pack_account_type(is_initialized, AccountType::VestingSchedule);
 		authority_dst.copy_from_slice(authority.as_ref());
 		mint_dst.copy_from_slice(mint.as_ref());
 		frequency_dst[0] = frequency as u8;
 		*start_dst = start.to_le_bytes();
 		*duration_dst = duration.to_le_bytes();
 		pack_coption_key(vault, vault_dst);
+		anchor_to_creation_dst[0] = anchor_to_creation as u8;
+		identifier_dst.copy_from_slice(identifier);
+		*cliff_dst = cliff.to_le_bytes();
+		pack_pending_amendment(&pending_amendment, pending_amendment_dst);
+		pack_coption_key(receipt_mint, receipt_mint_dst);
+		*tip_lamports_dst = tip_lamports.to_le_bytes();
+		pack_coption_key(manager_program, manager_program_dst);
+		bump_dst[0] = bump;
+		*past_start_tolerance_dst = past_start_tolerance.to_le_bytes();
+		*pool_total_dst = pool_total.to_le_bytes();
+		*allocated_bps_dst = allocated_bps.to_le_bytes();
+		*label_dst = label;
+		decimals_dst[0] = decimals;
+		immutable_dst[0] = immutable as u8;
+		seed_mint_dst.copy_from_slice(seed_mint.as_ref());
+		pack_coption_key(previous_mint, previous_mint_dst);
+		*migration_ratio_num_dst = migration_ratio_num.to_le_bytes();
+		*migration_ratio_den_dst = migration_ratio_den.to_le_bytes();
+		*cliff_bps_dst = cliff_bps.to_le_bytes();
 	}
 }
 
+/// Vesting account data, see [`VestingSchedule`] for the optional Serde and Borsh derives
 #[repr(C)]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "borsh",
+	derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
 pub struct Account {
-	pub is_initialized: bool,     // 1
+	pub is_initialized: bool, // 1
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialization::pubkey_string"))]
 	pub vesting_schedule: Pubkey, // 33
-	pub owner: Pubkey,            // 65
-	pub mint: Pubkey,             // 97
-	pub amount: u64,              // 105
-	pub claimed: u64,             // 113
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialization::pubkey_string"))]
+	pub owner: Pubkey, // 65
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialization::pubkey_string"))]
+	pub mint: Pubkey, // 97
+	pub amount: u64,          // 105
+	pub claimed: u64,         // 113
+	pub created_at: UnixTimestamp, // 121
+	/// Only token account claims may be sent to, set by the authority
+	#[cfg_attr(
+		feature = "serde",
+		serde(with = "crate::serialization::coption_pubkey")
+	)]
+	#[cfg_attr(
+		feature = "borsh",
+		borsh(
+			serialize_with = "crate::serialization::borsh_coption_pubkey::serialize",
+			deserialize_with = "crate::serialization::borsh_coption_pubkey::deserialize"
+		)
+	)]
+	pub allowed_destination: COption<Pubkey>, // 157
+	/// Who paid this account's rent and is refunded it when the account is closed, the authority
+	/// unless reassigned
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialization::pubkey_string"))]
+	pub rent_payer: Pubkey, // 189
+	/// Basis points of `amount` released at the start rather than vested, at most `MAX_BPS`
+	pub immediate_bps: u16, // 191
+	/// When the account's amount was last amended, zero if it never has. Emissions continue from
+	/// here so an amendment doesn't rewrite what had already vested.
+	pub anchored_at: UnixTimestamp, // 199
+	/// Amount that had vested at `anchored_at`
+	pub vested_at_anchor: u64, // 207
+	/// Most the owner allows to be claimed per window, so claims can be spread across tax
+	/// periods. Zero means no limit.
+	pub max_claim_per_window: u64, // 215
+	/// Length of a claim window in seconds, windows are aligned to the Unix epoch
+	pub window_seconds: i64, // 223
+	/// Start of the window `claimed_in_window` was counted in
+	pub window_start: UnixTimestamp, // 231
+	/// Amount claimed since `window_start`
+	pub claimed_in_window: u64, // 239
+	/// Whether the authority has frozen the account, e.g. for a legal hold, blocking claims and
+	/// amendments until it is unlocked, unlike `claims_paused` which only blocks claims
+	pub locked: bool, // 240
+	/// Who may claim in the owner's place once they have gone `inactivity_seconds` without
+	/// claiming, e.g. an heir, set by the owner
+	#[cfg_attr(
+		feature = "serde",
+		serde(with = "crate::serialization::coption_pubkey")
+	)]
+	#[cfg_attr(
+		feature = "borsh",
+		borsh(
+			serialize_with = "crate::serialization::borsh_coption_pubkey::serialize",
+			deserialize_with = "crate::serialization::borsh_coption_pubkey::deserialize"
+		)
+	)]
+	pub fallback_owner: COption<Pubkey>, // 276
+	/// Seconds without a claim by the owner after which `fallback_owner` may claim
+	pub inactivity_seconds: i64, // 284
+	/// When the owner last claimed or set their fallback, zero if they never have
+	pub last_claim: UnixTimestamp, // 292
+	/// Whether the authority has paused claims from the account, e.g. during a dispute. Unlike
+	/// `locked` it can still be amended, and it keeps vesting in the meantime.
+	pub claims_paused: bool, // 293
+}
+impl Account {
+	/// Share of `amount` released at the start, see [`Self::immediate_bps`]
+	pub fn immediate_amount(&self) -> u64 {
+		let bps = self.immediate_bps.min(MAX_BPS);
+		(u128::from(self.amount) * u128::from(bps) / u128::from(MAX_BPS)) as u64
+	}
+
+	/// Start of the claim window containing `now`, `None` if the owner hasn't set a limit
+	pub fn current_window(&self, now: UnixTimestamp) -> Option<UnixTimestamp> {
+		if self.max_claim_per_window == 0 || self.window_seconds <= 0 {
+			return None;
+		}
+		Some(now - now.rem_euclid(self.window_seconds))
+	}
+
+	/// Amount still claimable in the window containing `now`, `u64::MAX` if there is no limit
+	pub fn window_remaining(&self, now: UnixTimestamp) -> u64 {
+		match self.current_window(now) {
+			None => u64::MAX,
+			Some(start) if start != self.window_start => self.max_claim_per_window,
+			Some(_) => self
+				.max_claim_per_window
+				.saturating_sub(self.claimed_in_window),
+		}
+	}
+
+	/// Counts `amount` claimed at `now` against the window containing it
+	pub fn record_window_claim(&mut self, now: UnixTimestamp, amount: u64) {
+		if let Some(start) = self.current_window(now) {
+			if start != self.window_start {
+				self.window_start = start;
+				self.claimed_in_window = 0;
+			}
+			self.claimed_in_window = self.claimed_in_window.saturating_add(amount);
+		}
+	}
+
+	/// Whether `fallback_owner` may claim at `now`, once `inactivity_seconds` have passed since
+	/// the owner was last active, or since the account was created if they never have been
+	pub fn fallback_active(&self, now: UnixTimestamp) -> bool {
+		let last_active = self.last_claim.max(self.created_at);
+		self.fallback_owner.is_some()
+			&& self.inactivity_seconds > 0
+			&& now >= last_active.saturating_add(self.inactivity_seconds)
+	}
 }
 impl Sealed for Account {}
 impl IsInitialized for Account {
@@ -154,22 +1063,48 @@ impl IsInitialized for Account {
 	}
 }
 impl Pack for Account {
-	const LEN: usize = 113;
+	const LEN: usize = 293;
+
+	fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+		unpack_account_type(input, AccountType::Account)?;
+		if input.len() != Self::LEN {
+			return Err(ProgramError::InvalidAccountData);
+		}
+		Self::unpack_from_slice(input)
+	}
 
 	fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-		let src = array_ref![src, 0, 113];
-		let (is_initialized, vesting_schedule, owner, mint, amount, claimed) =
-			array_refs![src, 1, 32, 32, 32, 8, 8];
-		let is_initialized = match is_initialized {
-			[0] => false,
-			[1] => true,
-			_ => return Err(ProgramError::InvalidAccountData),
-		};
+		let src = array_ref![src, 0, 293];
+		let (
+			is_initialized,
+			vesting_schedule,
+			owner,
+			mint,
+			amount,
+			claimed,
+			created_at,
+			allowed_destination,
+			rent_payer,
+			immediate_bps,
+			anchored_at,
+			vested_at_anchor,
+			max_claim_per_window,
+			window_seconds,
+			window_start,
+			claimed_in_window,
+			locked,
+			fallback_owner,
+			inactivity_seconds,
+			last_claim,
+			claims_paused,
+		) = array_refs![src, 1, 32, 32, 32, 8, 8, 8, 36, 32, 2, 8, 8, 8, 8, 8, 8, 1, 36, 8, 8, 1];
+		let is_initialized = unpack_account_type(is_initialized, AccountType::Account)?;
 		let vesting_schedule = Pubkey::new_from_array(*vesting_schedule);
 		let owner = Pubkey::new_from_array(*owner);
 		let mint = Pubkey::new_from_array(*mint);
 		let amount = u64::from_le_bytes(*amount);
 		let claimed = u64::from_le_bytes(*claimed);
+		let created_at = i64::from_le_bytes(*created_at);
 		Ok(Self {
 			is_initialized,
 			vesting_schedule,
@@ -177,11 +1112,26 @@ impl Pack for Account {
 			mint,
 			amount,
 			claimed,
+			created_at,
+			allowed_destination: unpack_coption_key(allowed_destination)?,
+			rent_payer: Pubkey::new_from_array(*rent_payer),
+			immediate_bps: u16::from_le_bytes(*immediate_bps),
+			anchored_at: i64::from_le_bytes(*anchored_at),
+			vested_at_anchor: u64::from_le_bytes(*vested_at_anchor),
+			max_claim_per_window: u64::from_le_bytes(*max_claim_per_window),
+			window_seconds: i64::from_le_bytes(*window_seconds),
+			window_start: i64::from_le_bytes(*window_start),
+			claimed_in_window: u64::from_le_bytes(*claimed_in_window),
+			locked: unpack_bool(locked)?,
+			fallback_owner: unpack_coption_key(fallback_owner)?,
+			inactivity_seconds: i64::from_le_bytes(*inactivity_seconds),
+			last_claim: i64::from_le_bytes(*last_claim),
+			claims_paused: unpack_bool(claims_paused)?,
 		})
 	}
 
 	fn pack_into_slice(&self, dst: &mut [u8]) {
-		let dst = array_mut_ref![dst, 0, 113];
+		let dst = array_mut_ref![dst, 0, 293];
 		let (
 			is_initialized_dst,
 			vesting_schedule_dst,
@@ -189,7 +1139,22 @@ impl Pack for Account {
 			mint_dst,
 			amount_dst,
 			claimed_dst,
-		) = mut_array_refs![dst, 1, 32, 32, 32, 8, 8];
+			created_at_dst,
+			allowed_destination_dst,
+			rent_payer_dst,
+			immediate_bps_dst,
+			anchored_at_dst,
+			vested_at_anchor_dst,
+			max_claim_per_window_dst,
+			window_seconds_dst,
+			window_start_dst,
+			claimed_in_window_dst,
+			locked_dst,
+			fallback_owner_dst,
+			inactivity_seconds_dst,
+			last_claim_dst,
+			claims_paused_dst,
+		) = mut_array_refs![dst, 1, 32, 32, 32, 8, 8, 8, 36, 32, 2, 8, 8, 8, 8, 8, 8, 1, 36, 8, 8, 1];
 		let &Account {
 			is_initialized,
 			ref vesting_schedule,
@@ -197,14 +1162,510 @@ impl Pack for Account {
 			ref mint,
 			amount,
 			claimed,
+			created_at,
+			ref allowed_destination,
+			ref rent_payer,
+			immediate_bps,
+			anchored_at,
+			vested_at_anchor,
+			max_claim_per_window,
+			window_seconds,
+			window_start,
+			claimed_in_window,
+			locked,
+			ref fallback_owner,
+			inactivity_seconds,
+			last_claim,
+			claims_paused,
 		} = self;
-		is_initialized_dst[0] = is_initialized as u8;
+		is_initialized_dst[0] = pack_account_type(is_initialized, AccountType::Account);
 		vesting_schedule_dst.copy_from_slice(vesting_schedule.as_ref());
 		owner_dst.copy_from_slice(owner.as_ref());
 		mint_dst.copy_from_slice(mint.as_ref());
 		*amount_dst = amount.to_le_bytes();
 		*claimed_dst = claimed.to_le_bytes();
+		*created_at_dst = created_at.to_le_bytes();
+		pack_coption_key(allowed_destination, allowed_destination_dst);
+		rent_payer_dst.copy_from_slice(rent_payer.as_ref());
+		*immediate_bps_dst = immediate_bps.to_le_bytes();
+		*anchored_at_dst = anchored_at.to_le_bytes();
+		*vested_at_anchor_dst = vested_at_anchor.to_le_bytes();
+		*max_claim_per_window_dst = max_claim_per_window.to_le_bytes();
+		*window_seconds_dst = window_seconds.to_le_bytes();
+		*window_start_dst = window_start.to_le_bytes();
+		*claimed_in_window_dst = claimed_in_window.to_le_bytes();
+		locked_dst[0] = locked as u8;
+		pack_coption_key(fallback_owner, fallback_owner_dst);
+		*inactivity_seconds_dst = inactivity_seconds.to_le_bytes();
+		*last_claim_dst = last_claim.to_le_bytes();
+		claims_paused_dst[0] = claims_paused as u8;
+	}
+}
+
+/// Unpacks a batch of raw vesting account buffers, e.g. the result of `getProgramAccounts`.
+///
+/// Each entry is unpacked independently, buffers that are uninitialised or of the wrong length
+/// yield an error for that entry only instead of failing the whole batch.
+pub fn unpack_accounts(raw: &[(Pubkey, Vec<u8>)]) -> Vec<(Pubkey, Result<Account, ProgramError>)> {
+	raw.iter()
+		.map(|(key, data)| (*key, Account::unpack(data)))
+		.collect()
+}
+
+/// An invariant violation found by [`audit_consistency`], accounts are identified by their index
+/// in the audited slice
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConsistencyError {
+	/// The vesting schedule has not been initialised
+	ScheduleNotInitialized,
+	/// The account has not been initialised
+	AccountNotInitialized(usize),
+	/// The account's mint differs from the schedule's mint
+	MintMismatch(usize),
+	/// The account has claimed more than its allocation
+	ClaimedExceedsAmount(usize),
+	/// The sum of all account allocations does not fit in a `u64`
+	TotalAllocationOverflow,
+}
+
+/// Checks a vesting schedule and its accounts for internal consistency, returning every violation
+/// found rather than stopping at the first.
+pub fn audit_consistency(
+	schedule: &VestingSchedule,
+	accounts: &[Account],
+) -> Vec<ConsistencyError> {
+	let mut errors = Vec::new();
+	if !schedule.is_initialized {
+		errors.push(ConsistencyError::ScheduleNotInitialized);
+	}
+	let mut total: Option<u64> = Some(0);
+	for (i, account) in accounts.iter().enumerate() {
+		if !account.is_initialized {
+			errors.push(ConsistencyError::AccountNotInitialized(i));
+		}
+		if account.mint != schedule.mint {
+			errors.push(ConsistencyError::MintMismatch(i));
+		}
+		if account.claimed > account.amount {
+			errors.push(ConsistencyError::ClaimedExceedsAmount(i));
+		}
+		total = total.and_then(|t| t.checked_add(account.amount));
+	}
+	if total.is_none() {
+		errors.push(ConsistencyError::TotalAllocationOverflow);
+	}
+	errors
+}
+
+/// Sums the outstanding obligation (`amount - claimed`) of every account across all of an
+/// authority's schedules, regardless of mint. Fails with `Overflow` if the total exceeds a `u64`.
+pub fn total_liability(
+	schedules_and_accounts: &[(VestingSchedule, Vec<Account>)],
+) -> Result<u64, ProgramError> {
+	schedules_and_accounts
+		.iter()
+		.try_fold(0u64, |total, (_, accounts)| {
+			total.checked_add(outstanding(accounts)?)
+		})
+		.ok_or(VestingError::Overflow.into())
+}
+
+/// Like [`total_liability`] but totalled per schedule mint, in the order each mint is first seen
+pub fn liability_by_mint(
+	schedules_and_accounts: &[(VestingSchedule, Vec<Account>)],
+) -> Result<Vec<(Pubkey, u64)>, ProgramError> {
+	let mut totals: Vec<(Pubkey, u64)> = Vec::new();
+	for (schedule, accounts) in schedules_and_accounts {
+		let liability = outstanding(accounts).ok_or(VestingError::Overflow)?;
+		match totals.iter_mut().find(|(mint, _)| *mint == schedule.mint) {
+			Some((_, total)) => {
+				*total = total.checked_add(liability).ok_or(VestingError::Overflow)?;
+			}
+			None => totals.push((schedule.mint, liability)),
+		}
+	}
+	Ok(totals)
+}
+
+/// Amount each of `accounts` has vested at `now` regardless of what it has already claimed, in
+/// the same order, for reconciling what recipients should have received by a point in time
+pub fn group_vested(
+	schedule: &VestingSchedule,
+	accounts: &[Account],
+	now: UnixTimestamp,
+) -> Result<Vec<u64>, ProgramError> {
+	accounts
+		.iter()
+		.map(|account| schedule.account_vested(account, now))
+		.collect()
+}
+
+/// Minimum balance a vault must hold at `now` to pay every claim `accounts` could make over
+/// the next `horizon` seconds, what they can claim by `now + horizon`, so operators can top it
+/// up before it runs short. Fails with `Overflow` if the total exceeds a `u64`.
+pub fn solvency_threshold(
+	schedule: &VestingSchedule,
+	accounts: &[Account],
+	now: UnixTimestamp,
+	horizon: i64,
+) -> Result<u64, ProgramError> {
+	if horizon < 0 {
+		return Err(ProgramError::InvalidArgument);
+	}
+	let until = now.checked_add(horizon).ok_or(VestingError::Overflow)?;
+	accounts.iter().try_fold(0u64, |total, account| {
+		total
+			.checked_add(schedule.account_claimable(account, until)?)
+			.ok_or(VestingError::Overflow.into())
+	})
+}
+
+/// Amount `account` has vested at `points` timestamps spread evenly from `start` to `end`
+/// inclusive, for drawing its unlock curve
+pub fn sample_curve(
+	schedule: &VestingSchedule,
+	account: &Account,
+	start: UnixTimestamp,
+	end: UnixTimestamp,
+	points: u16,
+) -> Result<Vec<(UnixTimestamp, u64)>, ProgramError> {
+	if points == 0 || end < start {
+		return Err(ProgramError::InvalidArgument);
+	}
+	let span = i128::from(end) - i128::from(start);
+	let intervals = i128::from(points.saturating_sub(1).max(1));
+	(0..points)
+		.map(|point| {
+			let at = (i128::from(start) + span * i128::from(point) / intervals) as i64;
+			Ok((at, schedule.account_vested(account, at)?))
+		})
+		.collect()
+}
+
+/// Effect an `AmendSchedule` would have on what an account can claim, see [`amend_impact`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmendImpact {
+	/// Amount claimable at `now` under the schedule as it stands
+	pub old_claimable: u64,
+	/// Amount claimable at `now` were the amendment applied
+	pub new_claimable: u64,
+	/// `new_claimable - old_claimable`, negative if the amendment slows emissions
+	pub delta: i128,
+}
+
+/// Previews an `AmendSchedule` of `start`, `frequency` and `duration` against `account` at
+/// `now`, applying it on top of any amendment already in effect as the program does
+pub fn amend_impact(
+	current: &VestingSchedule,
+	start: Option<UnixTimestamp>,
+	frequency: Option<Frequency>,
+	duration: Option<i64>,
+	account: &Account,
+	now: UnixTimestamp,
+) -> Result<AmendImpact, ProgramError> {
+	let previous = current.effective(now);
+	let mut proposed = previous.clone();
+	proposed.amend(start, frequency, duration);
+	let old_claimable = previous.account_claimable(account, now)?;
+	let new_claimable = proposed.account_claimable(account, now)?;
+	Ok(AmendImpact {
+		old_claimable,
+		new_claimable,
+		delta: i128::from(new_claimable) - i128::from(old_claimable),
+	})
+}
+
+/// Returns `true` if the schedule, with any amendment in effect at `now` applied, has not yet
+/// completed at `now` but will complete within the next `window` seconds. Schedules that have
+/// already completed return `false`.
+pub fn completing_within(schedule: &VestingSchedule, now: UnixTimestamp, window: i64) -> bool {
+	match schedule.effective(now).completion_timestamp() {
+		Some(end) => end > now && end <= now.saturating_add(window),
+		None => false,
+	}
+}
+
+/// Stage of a vesting schedule at a point in time
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+	/// Vesting has not yet started
+	NotStarted,
+	/// Vesting has started but the cliff has not yet passed
+	Cliff,
+	/// Tokens are being emitted
+	Vesting,
+	/// Every token has been emitted
+	Completed,
+}
+
+/// Compact metadata describing a single vesting schedule, see [`summarize_portfolio`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScheduleSummary {
+	/// Address of the vesting schedule
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialization::pubkey_string"))]
+	pub pubkey: Pubkey,
+	/// The mint of vesting token
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialization::pubkey_string"))]
+	pub mint: Pubkey,
+	/// Stage of the schedule
+	pub phase: Phase,
+	/// Share of each allocation emitted so far, in basis points
+	pub vested_bps: u16,
+	/// Timestamp at which the final tokens are emitted, `None` if it overflows an `i64`
+	pub completion_ts: Option<UnixTimestamp>,
+}
+
+/// Summarises a batch of raw vesting schedule buffers at `now`, applying any amendment that has
+/// taken effect, e.g. every schedule returned by `getProgramAccounts` filtered on an authority.
+/// Buffers that fail to unpack are skipped.
+pub fn summarize_portfolio(schedules: &[(Pubkey, Vec<u8>)], now: i64) -> Vec<ScheduleSummary> {
+	schedules
+		.iter()
+		.filter_map(|(pubkey, data)| {
+			let schedule = VestingSchedule::unpack(data).ok()?.effective(now);
+			Some(ScheduleSummary {
+				pubkey: *pubkey,
+				mint: schedule.mint,
+				phase: schedule.phase(now),
+				vested_bps: schedule.vested_bps(now),
+				completion_ts: schedule.completion_timestamp(),
+			})
+		})
+		.collect()
+}
+
+/// What an account can claim at a point in time along with its progress, see [`claim_preview`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClaimPreview {
+	/// Amount the account can claim
+	pub claimable: u64,
+	/// Share of the account's amount vested so far, in basis points
+	pub vested_bps: u16,
+	/// Earliest timestamp at which the account vests more, `None` once it has vested in full
+	pub next_unlock: Option<UnixTimestamp>,
+	/// Stage of the schedule as it applies to the account
+	pub phase: Phase,
+}
+
+/// Previews `account`'s claim at `now`, vesting from its own start as in
+/// [`VestingSchedule::account_vested`]. The next unlock is found by bisecting the time up to the
+/// account's completion, so it is exact for every frequency and for amended accounts.
+pub fn claim_preview(
+	schedule: &VestingSchedule,
+	account: &Account,
+	now: UnixTimestamp,
+) -> Result<ClaimPreview, ProgramError> {
+	let effective = schedule.effective(now);
+	let own = VestingSchedule {
+		start: effective.account_start(account),
+		..effective
+	};
+	let vested = schedule.account_vested(account, now)?;
+	let vested_bps = match account.amount {
+		0 => MAX_BPS,
+		amount => (u128::from(vested) * u128::from(MAX_BPS) / u128::from(amount)) as u16,
+	};
+	let mut next_unlock = None;
+	if let Some(end) = own.completion_timestamp() {
+		if vested < account.amount && end > now {
+			let (mut low, mut high) = (now, end);
+			if schedule.account_vested(account, high)? > vested {
+				// `low` never vests more than `now` and `high` always does
+				while high - low > 1 {
+					let mid = low + (high - low) / 2;
+					match schedule.account_vested(account, mid)? > vested {
+						true => high = mid,
+						false => low = mid,
+					}
+				}
+				next_unlock = Some(high);
+			}
+		}
+	}
+	Ok(ClaimPreview {
+		claimable: vested.saturating_sub(account.claimed),
+		vested_bps,
+		next_unlock,
+		phase: own.phase(now),
+	})
+}
+
+/// [`claim_preview`] of raw vesting schedule and account buffers as returned by RPC, serialized as
+/// a JSON object for frontends
+#[cfg(feature = "serde")]
+pub fn claim_preview_json(
+	schedule_bytes: &[u8],
+	account_bytes: &[u8],
+	now: i64,
+) -> Result<String, ProgramError> {
+	let schedule = VestingSchedule::unpack(schedule_bytes)?;
+	let account = Account::unpack(account_bytes)?;
+	let preview = claim_preview(&schedule, &account, now)?;
+	serde_json::to_string(&preview).map_err(|_| ProgramError::InvalidArgument)
+}
+
+/// Rounds `start` down to the nearest multiple of `interval` seconds, so every schedule initialised
+/// within the same interval starts at the same time. Periods are counted from the quantized start,
+/// so the first emission lands up to `interval` seconds earlier than it would have from `start`.
+pub fn quantize_start(start: UnixTimestamp, interval: i64) -> Result<UnixTimestamp, ProgramError> {
+	if interval <= 0 {
+		return Err(VestingError::InvalidSchedule.into());
+	}
+	start
+		.checked_sub(start.rem_euclid(interval))
+		.ok_or(VestingError::Overflow.into())
+}
+
+/// Floors a claimable base-unit amount of a mint with `decimals` decimals to `round_to` decimal
+/// places for display, e.g. `1_234_567` with 6 decimals rounded to 2 places is `1_230_000`.
+///
+/// This only affects presentation, claims always release the full base-unit amount.
+pub fn claimable_display(base: u64, decimals: u8, round_to: u8) -> u64 {
+	if round_to >= decimals {
+		return base;
+	}
+	match 10u64.checked_pow(u32::from(decimals - round_to)) {
+		Some(unit) => base - base % unit,
+		None => 0,
+	}
+}
+
+/// Amount received when transferring `gross` tokens of the mint in `mint_data` during `epoch`,
+/// less any Token-2022 transfer fee. Mints without a transfer fee receive the full amount.
+pub fn calculate_net_after_fee(
+	mint_data: &[u8],
+	epoch: Epoch,
+	gross: u64,
+) -> Result<u64, ProgramError> {
+	let fee = transfer_fee(mint_data, epoch, gross)?.unwrap_or(0);
+	Ok(gross.saturating_sub(fee))
+}
+
+/// Transfer fee withheld when transferring `gross` tokens of the mint in `mint_data` during
+/// `epoch`, `None` if the mint has no transfer fee extension
+pub fn transfer_fee(
+	mint_data: &[u8],
+	epoch: Epoch,
+	gross: u64,
+) -> Result<Option<u64>, ProgramError> {
+	let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
+	match mint.get_extension::<TransferFeeConfig>() {
+		Ok(config) => config
+			.calculate_epoch_fee(epoch, gross)
+			.map(Some)
+			.ok_or(VestingError::Overflow.into()),
+		Err(_) => Ok(None),
+	}
+}
+
+/// `a * b`, failing with `Overflow` rather than wrapping or panicking
+fn checked_mul<T: CheckedMul>(a: T, b: T) -> Result<T, ProgramError> {
+	a.checked_mul(&b).ok_or(VestingError::Overflow.into())
+}
+
+/// `a / b`, failing with `Overflow` on division by zero or `i64::MIN / -1`
+fn checked_div<T: CheckedDiv>(a: T, b: T) -> Result<T, ProgramError> {
+	a.checked_div(&b).ok_or(VestingError::Overflow.into())
+}
+
+/// Converts a period count to a `u64`, failing with `Overflow` if it is negative
+fn to_u64(value: i64) -> Result<u64, ProgramError> {
+	u64::try_from(value).map_err(|_| VestingError::Overflow.into())
+}
+
+/// Share of `amount` accrued over `elapsed` of `total` periods, as whole tokens and the numerator
+/// of the fraction of a token over `total` left over. Accruing the exact fraction rather than
+/// flooring each period's share means no dust is held back until the schedule completes.
+fn accrue(amount: u64, elapsed: u64, total: u64) -> Result<(u64, u64), ProgramError> {
+	let accrued = checked_mul(u128::from(amount), u128::from(elapsed))?;
+	let whole = checked_div(accrued, u128::from(total))?;
+	Ok((
+		u64::try_from(whole).map_err(|_| VestingError::Overflow)?,
+		(accrued % u128::from(total)) as u64,
+	))
+}
+
+/// Whole periods of `period` milliseconds in `seconds`, saturating instead of overflowing and zero
+/// for negative spans
+fn whole_periods(seconds: i64, period: i64) -> u64 {
+	u64::try_from(seconds.saturating_mul(1_000) / period).unwrap_or(0)
+}
+
+/// Total unclaimed allocation of `accounts`, `None` on overflow
+fn outstanding(accounts: &[Account]) -> Option<u64> {
+	accounts.iter().try_fold(0u64, |total, account| {
+		total.checked_add(account.amount.saturating_sub(account.claimed))
+	})
+}
+
+/// Unpacks the account type byte, returning whether the account is initialised as `expected`
+fn unpack_account_type(src: &[u8], expected: AccountType) -> Result<bool, ProgramError> {
+	match AccountType::of(src)? {
+		AccountType::Uninitialized => Ok(false),
+		account_type if account_type == expected => Ok(true),
+		_ => Err(VestingError::WrongAccountType.into()),
+	}
+}
+
+fn pack_account_type(is_initialized: bool, account_type: AccountType) -> u8 {
+	match is_initialized {
+		true => account_type.into(),
+		false => AccountType::Uninitialized.into(),
+	}
+}
+
+fn unpack_bool(src: &[u8; 1]) -> Result<bool, ProgramError> {
+	match src {
+		[0] => Ok(false),
+		[1] => Ok(true),
+		_ => Err(ProgramError::InvalidAccountData),
+	}
+}
+
+/// Packs an optional pending amendment as a presence byte followed by `effective_at` and each
+/// amended field as a presence byte and value, zeroed when absent
+fn pack_pending_amendment(src: &Option<PendingAmendment>, dst: &mut [u8; 29]) {
+	let (tag, effective_at, start_tag, start, frequency_tag, frequency, duration_tag, duration) =
+		mut_array_refs![dst, 1, 8, 1, 8, 1, 1, 1, 8];
+	let pending = src.unwrap_or(PendingAmendment {
+		effective_at: 0,
+		start: None,
+		frequency: None,
+		duration: None,
+	});
+	tag[0] = src.is_some() as u8;
+	*effective_at = pending.effective_at.to_le_bytes();
+	start_tag[0] = pending.start.is_some() as u8;
+	*start = pending.start.unwrap_or(0).to_le_bytes();
+	frequency_tag[0] = pending.frequency.is_some() as u8;
+	frequency[0] = pending.frequency.map_or(0, u8::from);
+	duration_tag[0] = pending.duration.is_some() as u8;
+	*duration = pending.duration.unwrap_or(0).to_le_bytes();
+}
+
+fn unpack_pending_amendment(src: &[u8; 29]) -> Result<Option<PendingAmendment>, ProgramError> {
+	let (tag, effective_at, start_tag, start, frequency_tag, frequency, duration_tag, duration) =
+		array_refs![src, 1, 8, 1, 8, 1, 1, 1, 8];
+	if !unpack_bool(tag)? {
+		return Ok(None);
 	}
+	let frequency = match unpack_bool(frequency_tag)? {
+		true => Some(
+			Frequency::try_from_primitive(frequency[0])
+				.or(Err(ProgramError::InvalidAccountData))?,
+		),
+		false => None,
+	};
+	Ok(Some(PendingAmendment {
+		effective_at: i64::from_le_bytes(*effective_at),
+		start: unpack_bool(start_tag)?.then(|| i64::from_le_bytes(*start)),
+		frequency,
+		duration: unpack_bool(duration_tag)?.then(|| i64::from_le_bytes(*duration)),
+	}))
 }
 
 pub(crate) fn pack_coption_key(src: &COption<Pubkey>, dst: &mut [u8; 36]) {
@@ -0,0 +1,92 @@
+use {
+	fsp_vesting::{
+		create_vesting_schedule_address, find_vesting_schedule_address, hash_identifier, pad_label,
+		schedule_signer_seeds,
+		state::{Frequency, VestingSchedule},
+	},
+	solana_program::{hash::hash, program_option::COption, pubkey::Pubkey},
+};
+
+#[test]
+fn test_hash_identifier() {
+	assert_eq!(
+		hash_identifier("seed-round"),
+		hash(b"seed-round").to_bytes()[..8]
+	);
+	assert_ne!(hash_identifier("seed-round"), hash_identifier("team"));
+}
+
+#[test]
+fn test_pad_label() {
+	let label = pad_label("Seed round").unwrap();
+	assert_eq!(&label[..10], b"Seed round");
+	assert_eq!(&label[10..], &[b' '; 22]);
+	assert_eq!(pad_label(""), Some([b' '; 32]));
+	assert!(pad_label(&"a".repeat(32)).is_some());
+	assert_eq!(pad_label(&"a".repeat(33)), None);
+}
+
+#[test]
+fn test_vesting_schedule_address() {
+	let program_id = Pubkey::new_unique();
+	let mint = Pubkey::new_unique();
+
+	let (address, bump) = find_vesting_schedule_address(&program_id, &mint, "seed-round");
+	assert_eq!(
+		create_vesting_schedule_address(&program_id, &mint, &hash_identifier("seed-round"), bump),
+		Ok(address)
+	);
+	assert!(!address.is_on_curve());
+
+	let (other, _) = find_vesting_schedule_address(&program_id, &mint, "team");
+	assert_ne!(address, other);
+	let (other, _) =
+		find_vesting_schedule_address(&program_id, &Pubkey::new_unique(), "seed-round");
+	assert_ne!(address, other);
+}
+
+#[test]
+fn test_schedule_signer_seeds() {
+	let program_id = Pubkey::new_unique();
+	let mint = Pubkey::new_unique();
+	let (address, bump) = find_vesting_schedule_address(&program_id, &mint, "seed-round");
+	let mut schedule = VestingSchedule {
+		is_initialized: true,
+		authority: Pubkey::new_unique(),
+		mint,
+		frequency: Frequency::Day,
+		start: 1_700_000_000,
+		duration: 86_400,
+		vault: COption::None,
+		anchor_to_creation: false,
+		identifier: hash_identifier("seed-round"),
+		cliff: 0,
+		pending_amendment: None,
+		receipt_mint: COption::None,
+		tip_lamports: 0,
+		manager_program: COption::None,
+		bump,
+		past_start_tolerance: 0,
+		pool_total: 0,
+		allocated_bps: 0,
+		label: [b' '; 32],
+		decimals: 0,
+		immutable: false,
+		seed_mint: mint,
+		previous_mint: COption::None,
+		migration_ratio_num: 0,
+		migration_ratio_den: 0,
+		cliff_bps: 0,
+	};
+	assert_eq!(
+		Pubkey::create_program_address(&schedule_signer_seeds(&schedule), &program_id),
+		Ok(address)
+	);
+
+	// A stale bump no longer signs for the schedule
+	schedule.bump = bump.wrapping_sub(1);
+	assert_ne!(
+		Pubkey::create_program_address(&schedule_signer_seeds(&schedule), &program_id),
+		Ok(address)
+	);
+}
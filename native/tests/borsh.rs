@@ -0,0 +1,102 @@
+#![cfg(feature = "borsh")]
+
+use {
+	borsh::{BorshDeserialize, BorshSerialize},
+	fsp_vesting::{
+		hash_identifier,
+		state::{Account, Frequency, VestingSchedule},
+	},
+	solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey},
+};
+
+fn round_trip<T: BorshSerialize + BorshDeserialize>(value: &T) -> T {
+	let bytes = borsh::to_vec(value).unwrap();
+	T::try_from_slice(&bytes).unwrap()
+}
+
+fn packed<T: Pack>(state: T) -> Vec<u8> {
+	let mut data = vec![0; T::LEN];
+	T::pack(state, &mut data).unwrap();
+	data
+}
+
+#[test]
+fn test_vesting_schedule_round_trip() {
+	for vault in [COption::None, COption::Some(Pubkey::new_unique())] {
+		let schedule = VestingSchedule {
+			is_initialized: true,
+			authority: Pubkey::new_unique(),
+			mint: Pubkey::new_unique(),
+			frequency: Frequency::Quarter,
+			start: 1_700_000_000,
+			duration: 4 * 7_889_238,
+			vault,
+			anchor_to_creation: true,
+			identifier: hash_identifier("borsh"),
+			cliff: 7_889_238,
+			pending_amendment: None,
+			receipt_mint: COption::Some(Pubkey::new_unique()),
+			tip_lamports: 5_000,
+			manager_program: vault,
+			bump: 0,
+			past_start_tolerance: 0,
+			pool_total: 0,
+			allocated_bps: 0,
+			label: [b' '; 32],
+			decimals: 0,
+			immutable: false,
+			seed_mint: Pubkey::new_unique(),
+			previous_mint: COption::Some(Pubkey::new_unique()),
+			migration_ratio_num: 2,
+			migration_ratio_den: 3,
+			cliff_bps: 0,
+		};
+		let decoded = round_trip(&schedule);
+		assert_eq!(decoded.authority, schedule.authority);
+		assert_eq!(decoded.frequency, Frequency::Quarter);
+		assert_eq!(decoded.vault, vault);
+		assert_eq!(decoded.receipt_mint, schedule.receipt_mint);
+		assert_eq!(decoded.manager_program, vault);
+		assert_eq!(packed(decoded), packed(schedule));
+	}
+}
+
+#[test]
+fn test_account_round_trip() {
+	let account = Account {
+		is_initialized: true,
+		vesting_schedule: Pubkey::new_unique(),
+		owner: Pubkey::new_unique(),
+		mint: Pubkey::new_unique(),
+		amount: u64::MAX,
+		claimed: 1,
+		created_at: -1,
+		allowed_destination: COption::Some(Pubkey::new_unique()),
+		rent_payer: Pubkey::new_unique(),
+		immediate_bps: 0,
+		anchored_at: 0,
+		vested_at_anchor: 0,
+		max_claim_per_window: 0,
+		window_seconds: 0,
+		window_start: 0,
+		claimed_in_window: 0,
+		locked: false,
+		fallback_owner: COption::None,
+		inactivity_seconds: 0,
+		last_claim: 0,
+		claims_paused: false,
+	};
+	let decoded = round_trip(&account);
+	assert_eq!(decoded.amount, u64::MAX);
+	assert_eq!(decoded.created_at, -1);
+	assert_eq!(decoded.allowed_destination, account.allowed_destination);
+	assert_eq!(decoded.rent_payer, account.rent_payer);
+	assert_eq!(packed(decoded), packed(account));
+}
+
+#[test]
+fn test_frequency_round_trip() {
+	for frequency in [Frequency::Once, Frequency::Slot, Frequency::Year] {
+		assert_eq!(round_trip(&frequency), frequency);
+	}
+}
@@ -0,0 +1,180 @@
+#![cfg(feature = "client")]
+
+use {
+	base64::{prelude::BASE64_STANDARD, Engine},
+	fsp_vesting::{
+		client::{self, ClientError},
+		hash_identifier,
+		state::{Account, Frequency, VestingSchedule},
+	},
+	serde_json::{json, Value},
+	solana_program::{clock::Clock, program_option::COption, program_pack::Pack, pubkey::Pubkey},
+	solana_rpc_client::{mock_sender::Mocks, rpc_client::RpcClient},
+	solana_rpc_client_api::request::RpcRequest,
+};
+
+const START: i64 = 1_700_000_000;
+const DAY: i64 = 86_400;
+
+fn schedule() -> VestingSchedule {
+	let mint = Pubkey::new_unique();
+	VestingSchedule {
+		is_initialized: true,
+		authority: Pubkey::new_unique(),
+		mint,
+		frequency: Frequency::Day,
+		start: START,
+		duration: 10 * DAY,
+		vault: COption::None,
+		anchor_to_creation: false,
+		identifier: hash_identifier("client"),
+		cliff: 0,
+		pending_amendment: None,
+		receipt_mint: COption::None,
+		tip_lamports: 0,
+		manager_program: COption::None,
+		bump: 0,
+		past_start_tolerance: 0,
+		pool_total: 0,
+		allocated_bps: 0,
+		label: [b' '; 32],
+		decimals: 0,
+		immutable: false,
+		seed_mint: mint,
+		previous_mint: COption::None,
+		migration_ratio_num: 0,
+		migration_ratio_den: 0,
+		cliff_bps: 0,
+	}
+}
+
+fn account(vesting_schedule: Pubkey) -> Account {
+	Account {
+		is_initialized: true,
+		vesting_schedule,
+		owner: Pubkey::new_unique(),
+		mint: Pubkey::new_unique(),
+		amount: 1_000,
+		claimed: 100,
+		created_at: START,
+		allowed_destination: COption::None,
+		rent_payer: Pubkey::new_unique(),
+		immediate_bps: 0,
+		anchored_at: 0,
+		vested_at_anchor: 0,
+		max_claim_per_window: 0,
+		window_seconds: 0,
+		window_start: 0,
+		claimed_in_window: 0,
+		locked: false,
+		fallback_owner: COption::None,
+		inactivity_seconds: 0,
+		last_claim: 0,
+		claims_paused: false,
+	}
+}
+
+fn packed<T: Pack>(state: T) -> Vec<u8> {
+	let mut data = vec![0; T::LEN];
+	T::pack(state, &mut data).unwrap();
+	data
+}
+
+/// An account as returned in an RPC response
+fn ui_account(data: &[u8]) -> Value {
+	json!({
+		"lamports": 1_000_000,
+		"data": [BASE64_STANDARD.encode(data), "base64"],
+		"owner": Pubkey::new_unique().to_string(),
+		"executable": false,
+		"rentEpoch": 0,
+		"space": data.len(),
+	})
+}
+
+fn response(value: Value) -> Value {
+	json!({ "context": { "slot": 1 }, "value": value })
+}
+
+fn mock_rpc(request: RpcRequest, value: Value) -> RpcClient {
+	let mut mocks = Mocks::new();
+	mocks.insert(request, response(value));
+	RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+}
+
+#[test]
+fn test_get_vesting_schedule() {
+	let schedule = schedule();
+	let rpc = mock_rpc(
+		RpcRequest::GetAccountInfo,
+		ui_account(&packed(schedule.clone())),
+	);
+	let fetched = client::get_vesting_schedule(&rpc, &Pubkey::new_unique()).unwrap();
+	assert_eq!(fetched.authority, schedule.authority);
+	assert_eq!(fetched.duration, schedule.duration);
+
+	// Data that isn't a vesting schedule is rejected
+	let rpc = mock_rpc(
+		RpcRequest::GetAccountInfo,
+		ui_account(&packed(account(Pubkey::new_unique()))),
+	);
+	assert!(matches!(
+		client::get_vesting_schedule(&rpc, &Pubkey::new_unique()),
+		Err(ClientError::Program(_))
+	));
+}
+
+#[test]
+fn test_get_account() {
+	let account = account(Pubkey::new_unique());
+	let rpc = mock_rpc(
+		RpcRequest::GetAccountInfo,
+		ui_account(&packed(account.clone())),
+	);
+	let fetched = client::get_account(&rpc, &Pubkey::new_unique()).unwrap();
+	assert_eq!(fetched.owner, account.owner);
+	assert_eq!(fetched.amount, account.amount);
+	assert_eq!(fetched.claimed, account.claimed);
+}
+
+#[test]
+fn test_get_claimable_now() {
+	let schedule_address = Pubkey::new_unique();
+	let account_address = Pubkey::new_unique();
+	let clock = Clock {
+		unix_timestamp: START + 3 * DAY,
+		..Clock::default()
+	};
+	let accounts = |schedule_address: Pubkey| {
+		json!([
+			ui_account(&packed(schedule())),
+			ui_account(&packed(account(schedule_address))),
+			ui_account(&bincode::serialize(&clock).unwrap()),
+		])
+	};
+
+	let rpc = mock_rpc(RpcRequest::GetMultipleAccounts, accounts(schedule_address));
+	assert_eq!(
+		client::get_claimable_now(&rpc, &schedule_address, &account_address).unwrap(),
+		200
+	);
+
+	// The account must belong to the schedule
+	let rpc = mock_rpc(
+		RpcRequest::GetMultipleAccounts,
+		accounts(Pubkey::new_unique()),
+	);
+	assert!(matches!(
+		client::get_claimable_now(&rpc, &schedule_address, &account_address),
+		Err(ClientError::Program(_))
+	));
+
+	let rpc = mock_rpc(
+		RpcRequest::GetMultipleAccounts,
+		json!([Value::Null, Value::Null, Value::Null]),
+	);
+	assert!(matches!(
+		client::get_claimable_now(&rpc, &schedule_address, &account_address),
+		Err(ClientError::AccountNotFound(address)) if address == schedule_address
+	));
+}
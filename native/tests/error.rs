@@ -0,0 +1,61 @@
+use {
+	fsp_vesting::error::VestingError, num_traits::FromPrimitive,
+	solana_program::program_error::ProgramError,
+};
+
+const ERRORS: [VestingError; 42] = [
+	VestingError::InvalidInstruction,
+	VestingError::WrongAccountType,
+	VestingError::Overflow,
+	VestingError::TooManyAccounts,
+	VestingError::NotInitialized,
+	VestingError::AlreadyInitialized,
+	VestingError::InvalidAuthority,
+	VestingError::InvalidMint,
+	VestingError::NothingToClaim,
+	VestingError::VestingNotStarted,
+	VestingError::InvalidSchedule,
+	VestingError::InsufficientVaultBalance,
+	VestingError::IncorrectProgramId,
+	VestingError::EmissionsRoundToZero,
+	VestingError::ForfeitAcknowledgementMismatch,
+	VestingError::ScheduleNotEmpty,
+	VestingError::DestinationNotAllowed,
+	VestingError::InvalidVault,
+	VestingError::MissingRequiredAccount,
+	VestingError::NoVault,
+	VestingError::NotNativeMint,
+	VestingError::AllocationExceedsPool,
+	VestingError::StartMismatch,
+	VestingError::AdjustmentOutOfRange,
+	VestingError::WindowLimitReached,
+	VestingError::ScheduleAlreadyCompleted,
+	VestingError::AccountLocked,
+	VestingError::VoucherExpired,
+	VestingError::InvalidVoucher,
+	VestingError::ScheduleImmutable,
+	VestingError::InvalidDuration,
+	VestingError::FallbackNotYetActive,
+	VestingError::InsufficientTokenBalance,
+	VestingError::TokenAccountFrozen,
+	VestingError::TokenOwnerMismatch,
+	VestingError::StartTooFarInPast,
+	VestingError::NothingToForfeit,
+	VestingError::InvalidMigrationRatio,
+	VestingError::AccountAlreadyMigrated,
+	VestingError::AccountNotMigrated,
+	VestingError::AccountClaimsPaused,
+	VestingError::ScheduleAlreadyMigrated,
+];
+
+#[test]
+fn test_error_codes_are_stable() {
+	for (code, error) in ERRORS.iter().enumerate() {
+		assert_eq!(
+			ProgramError::from(error.clone()),
+			ProgramError::Custom(code as u32)
+		);
+		assert_eq!(VestingError::from_u32(code as u32).as_ref(), Some(error));
+		assert!(!error.to_string().is_empty());
+	}
+}
@@ -0,0 +1,4948 @@
+#![cfg(feature = "test-sbf")]
+
+use {
+	fsp_vesting::{
+		create_vesting_schedule_address,
+		entrypoint::process_instruction,
+		error::VestingError,
+		find_vault_address, find_vesting_account_address, find_vesting_schedule_address,
+		hash_identifier,
+		instruction::{
+			self, supported_instructions, CloseReceipt, InstructionTag,
+			DEFAULT_PAST_START_TOLERANCE, MAX_CURVE_POINTS, ON_CLAIM_DISCRIMINATOR,
+		},
+		pad_label,
+		state::{calculate_net_after_fee, Account, Frequency, PendingAmendment, VestingSchedule},
+	},
+	solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext},
+	solana_sdk::{
+		account::Account as SolanaAccount,
+		account_info::AccountInfo,
+		clock::Clock,
+		entrypoint::ProgramResult,
+		instruction::{Instruction, InstructionError},
+		program_option::COption,
+		program_pack::Pack,
+		pubkey::Pubkey,
+		rent::Rent,
+		signature::{Keypair, Signer},
+		transaction::{Transaction, TransactionError},
+	},
+	spl_associated_token_account::{
+		get_associated_token_address, get_associated_token_address_with_program_id,
+	},
+	spl_token::{
+		error::TokenError,
+		state::{Account as TokenAccount, AccountState, Mint},
+	},
+	spl_token_2022::{
+		extension::{
+			transfer_fee::{instruction::initialize_transfer_fee_config, TransferFeeAmount},
+			BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+		},
+		state::Mint as Mint2022,
+	},
+};
+
+#[allow(deprecated)]
+use solana_sdk::ed25519_instruction::new_ed25519_instruction_with_signature;
+
+const START: i64 = 1_700_000_000;
+const DAY: i64 = 86_400;
+
+fn program_test(program_id: &Pubkey) -> ProgramTest {
+	ProgramTest::new("fsp_vesting", *program_id, processor!(process_instruction))
+}
+
+fn add_packed<T: Pack>(program_test: &mut ProgramTest, program_id: &Pubkey, state: T) -> Pubkey {
+	let address = Pubkey::new_unique();
+	add_packed_at(program_test, &address, program_id, state);
+	address
+}
+
+fn add_packed_at<T: Pack>(
+	program_test: &mut ProgramTest,
+	address: &Pubkey,
+	owner: &Pubkey,
+	state: T,
+) {
+	let mut data = vec![0; T::LEN];
+	T::pack(state, &mut data).unwrap();
+	program_test.add_account(
+		*address,
+		SolanaAccount {
+			lamports: Rent::default().minimum_balance(T::LEN),
+			data,
+			owner: *owner,
+			..SolanaAccount::default()
+		},
+	);
+}
+
+fn add_mint(program_test: &mut ProgramTest, mint: &Pubkey) {
+	let state = Mint {
+		decimals: 6,
+		is_initialized: true,
+		..Mint::default()
+	};
+	add_packed_at(program_test, mint, &spl_token::id(), state);
+}
+
+/// Adds `owner`'s ATA for `mint` holding `amount` tokens
+fn add_ata(program_test: &mut ProgramTest, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+	let address = get_associated_token_address(owner, mint);
+	let state = TokenAccount {
+		mint: *mint,
+		owner: *owner,
+		amount,
+		state: AccountState::Initialized,
+		..TokenAccount::default()
+	};
+	add_packed_at(program_test, &address, &spl_token::id(), state);
+	address
+}
+
+fn daily_schedule(days: i64) -> VestingSchedule {
+	let mint = Pubkey::new_unique();
+	VestingSchedule {
+		is_initialized: true,
+		authority: Pubkey::new_unique(),
+		mint,
+		frequency: Frequency::Day,
+		start: START,
+		duration: days * DAY,
+		vault: COption::None,
+		anchor_to_creation: false,
+		identifier: hash_identifier("daily"),
+		cliff: 0,
+		pending_amendment: None,
+		receipt_mint: COption::None,
+		tip_lamports: 0,
+		manager_program: COption::None,
+		bump: 0,
+		past_start_tolerance: 0,
+		pool_total: 0,
+		allocated_bps: 0,
+		label: [b' '; 32],
+		decimals: 0,
+		immutable: false,
+		seed_mint: mint,
+		previous_mint: COption::None,
+		migration_ratio_num: 0,
+		migration_ratio_den: 0,
+		cliff_bps: 0,
+	}
+}
+
+fn vesting_account(schedule: &Pubkey, mint: &Pubkey, amount: u64, claimed: u64) -> Account {
+	Account {
+		is_initialized: true,
+		vesting_schedule: *schedule,
+		owner: Pubkey::new_unique(),
+		mint: *mint,
+		amount,
+		claimed,
+		created_at: START,
+		allowed_destination: COption::None,
+		rent_payer: Pubkey::new_unique(),
+		immediate_bps: 0,
+		anchored_at: 0,
+		vested_at_anchor: 0,
+		max_claim_per_window: 0,
+		window_seconds: 0,
+		window_start: 0,
+		claimed_in_window: 0,
+		locked: false,
+		fallback_owner: COption::None,
+		inactivity_seconds: 0,
+		last_claim: 0,
+		claims_paused: false,
+	}
+}
+
+async fn set_clock(context: &mut ProgramTestContext, unix_timestamp: i64) {
+	let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+	clock.unix_timestamp = unix_timestamp;
+	context.set_sysvar(&clock);
+}
+
+async fn token_balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+	let account = context
+		.banks_client
+		.get_account(*address)
+		.await
+		.unwrap()
+		.unwrap();
+	TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+async fn set_token_balance(context: &mut ProgramTestContext, address: &Pubkey, amount: u64) {
+	let mut account = context
+		.banks_client
+		.get_account(*address)
+		.await
+		.unwrap()
+		.unwrap();
+	let mut token_account = TokenAccount::unpack(&account.data).unwrap();
+	token_account.amount = amount;
+	TokenAccount::pack(token_account, &mut account.data).unwrap();
+	context.set_account(address, &account.into());
+}
+
+async fn vesting_schedule_state(
+	context: &mut ProgramTestContext,
+	address: &Pubkey,
+) -> VestingSchedule {
+	let account = context
+		.banks_client
+		.get_account(*address)
+		.await
+		.unwrap()
+		.unwrap();
+	VestingSchedule::unpack(&account.data).unwrap()
+}
+
+async fn lamports(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+	context.banks_client.get_balance(*address).await.unwrap()
+}
+
+async fn vesting_account_state(context: &mut ProgramTestContext, address: &Pubkey) -> Account {
+	let account = context
+		.banks_client
+		.get_account(*address)
+		.await
+		.unwrap()
+		.unwrap();
+	Account::unpack(&account.data).unwrap()
+}
+
+async fn process(
+	context: &mut ProgramTestContext,
+	instruction: Instruction,
+	signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+	process_all(context, &[instruction], signers).await
+}
+
+/// Processes `instructions` in a single transaction
+async fn process_all(
+	context: &mut ProgramTestContext,
+	instructions: &[Instruction],
+	signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+	let blockhash = context.get_new_latest_blockhash().await.unwrap();
+	let mut all_signers = vec![&context.payer];
+	all_signers.extend_from_slice(signers);
+	let transaction = Transaction::new_signed_with_payer(
+		instructions,
+		Some(&context.payer.pubkey()),
+		&all_signers,
+		blockhash,
+	);
+	context.banks_client.process_transaction(transaction).await
+}
+
+/// Processes `instruction` and returns the return data it set
+async fn process_return_data(
+	context: &mut ProgramTestContext,
+	instruction: Instruction,
+	signers: &[&Keypair],
+) -> Result<Vec<u8>, BanksClientError> {
+	let blockhash = context.get_new_latest_blockhash().await.unwrap();
+	let mut all_signers = vec![&context.payer];
+	all_signers.extend_from_slice(signers);
+	let transaction = Transaction::new_signed_with_payer(
+		&[instruction],
+		Some(&context.payer.pubkey()),
+		&all_signers,
+		blockhash,
+	);
+	let processed = context
+		.banks_client
+		.process_transaction_with_metadata(transaction)
+		.await?;
+	processed.result?;
+	Ok(processed
+		.metadata
+		.and_then(|metadata| metadata.return_data)
+		.map(|return_data| return_data.data)
+		.unwrap_or_default())
+}
+
+fn custom_error(error: VestingError) -> TransactionError {
+	TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+async fn simulate_return_data(
+	context: &mut ProgramTestContext,
+	instruction: Instruction,
+) -> Result<Vec<u8>, BanksClientError> {
+	let transaction = Transaction::new_signed_with_payer(
+		&[instruction],
+		Some(&context.payer.pubkey()),
+		&[&context.payer],
+		context.last_blockhash,
+	);
+	let simulation = context
+		.banks_client
+		.simulate_transaction(transaction)
+		.await?;
+	simulation.result.unwrap()?;
+	Ok(simulation
+		.simulation_details
+		.and_then(|details| details.return_data)
+		.map(|return_data| return_data.data)
+		.unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_get_claimable_many() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let accounts: Vec<Pubkey> = [(1_000, 0), (2_000, 500), (3_000, 0)]
+		.into_iter()
+		.map(|(amount, claimed)| {
+			let account = vesting_account(&schedule_address, &mint, amount, claimed);
+			add_packed(&mut program_test, &program_id, account)
+		})
+		.collect();
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	let instruction =
+		instruction::get_claimable_many(&program_id, &schedule_address, &accounts).unwrap();
+	let return_data = simulate_return_data(&mut context, instruction)
+		.await
+		.unwrap();
+	let claimable: Vec<u64> = return_data
+		.chunks_exact(8)
+		.map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+		.collect();
+	assert_eq!(claimable, vec![300, 100, 900]);
+}
+
+#[tokio::test]
+async fn test_get_claimable() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let account = vesting_account(&schedule_address, &mint, 2_000, 500);
+	let account = add_packed(&mut program_test, &program_id, account);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	let instruction = instruction::get_claimable(&program_id, &schedule_address, &account);
+	let return_data = simulate_return_data(&mut context, instruction)
+		.await
+		.unwrap();
+	assert_eq!(return_data, 100u64.to_le_bytes());
+}
+
+#[tokio::test]
+async fn test_get_claimable_not_initialized() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let mut account = vesting_account(&schedule_address, &mint, 2_000, 0);
+	account.is_initialized = false;
+	let account = add_packed(&mut program_test, &program_id, account);
+	let mut context = program_test.start_with_context().await;
+
+	let instruction = instruction::get_claimable(&program_id, &schedule_address, &account);
+	let error = simulate_return_data(&mut context, instruction)
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::NotInitialized));
+}
+
+/// A funded recipient with a vesting account under `schedule_address` and an empty ATA for
+/// `mint`
+struct Recipient {
+	keypair: Keypair,
+	vesting_account: Pubkey,
+	ata: Pubkey,
+}
+
+fn add_recipient(
+	program_test: &mut ProgramTest,
+	program_id: &Pubkey,
+	schedule_address: &Pubkey,
+	mint: &Pubkey,
+	amount: u64,
+) -> Recipient {
+	let keypair = Keypair::new();
+	program_test.add_account(
+		keypair.pubkey(),
+		SolanaAccount {
+			lamports: 1_000_000_000,
+			..SolanaAccount::default()
+		},
+	);
+	let (vesting_account, _) =
+		find_vesting_account_address(program_id, schedule_address, &keypair.pubkey());
+	let mut state = self::vesting_account(schedule_address, mint, amount, 0);
+	state.owner = keypair.pubkey();
+	add_packed_at(program_test, &vesting_account, program_id, state);
+	let ata = add_ata(program_test, &keypair.pubkey(), mint, 0);
+	Recipient {
+		keypair,
+		vesting_account,
+		ata,
+	}
+}
+
+fn claim(
+	program_id: &Pubkey,
+	schedule_address: &Pubkey,
+	mint: &Pubkey,
+	source: &Pubkey,
+	recipient: &Recipient,
+) -> Instruction {
+	instruction::claim(
+		program_id,
+		schedule_address,
+		mint,
+		&recipient.vesting_account,
+		source,
+		&recipient.keypair.pubkey(),
+		&recipient.ata,
+		&spl_token::id(),
+	)
+}
+
+#[tokio::test]
+async fn test_claim_from_vesting_account_ata() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let mut context = program_test.start_with_context().await;
+	let ix = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+
+	// Nothing is claimable before the schedule starts
+	set_clock(&mut context, START - 1).await;
+	let error = process(&mut context, ix.clone(), &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::VestingNotStarted)
+	);
+
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+	process(&mut context, ix.clone(), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 300);
+	assert_eq!(token_balance(&mut context, &source).await, 700);
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.claimed, 300);
+
+	// Claiming again in the same period has nothing left to release
+	let error = process(&mut context, ix.clone(), &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::NothingToClaim));
+
+	set_clock(&mut context, START + 10 * DAY).await;
+	process(&mut context, ix, &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 1_000);
+	assert_eq!(token_balance(&mut context, &source).await, 0);
+}
+
+#[tokio::test]
+async fn test_claim_token_account_errors() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let underfunded = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let underfunded_source = add_ata(&mut program_test, &underfunded.vesting_account, &mint, 100);
+	let frozen = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let frozen_source = add_ata(&mut program_test, &frozen.vesting_account, &mint, 1_000);
+	let state = TokenAccount {
+		mint,
+		owner: frozen.keypair.pubkey(),
+		state: AccountState::Frozen,
+		..TokenAccount::default()
+	};
+	add_packed_at(&mut program_test, &frozen.ata, &spl_token::id(), state);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY).await;
+
+	let ix = claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&underfunded_source,
+		&underfunded,
+	);
+	let error = process(&mut context, ix, &[&underfunded.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::InsufficientTokenBalance)
+	);
+
+	let ix = claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&frozen_source,
+		&frozen,
+	);
+	let error = process(&mut context, ix, &[&frozen.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::TokenAccountFrozen)
+	);
+}
+
+#[tokio::test]
+async fn test_claim_expecting_start() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY).await;
+	let claim = |expected_start| {
+		instruction::claim_expecting_start(
+			&program_id,
+			&schedule_address,
+			&mint,
+			&recipient.vesting_account,
+			&source,
+			&recipient.keypair.pubkey(),
+			&recipient.ata,
+			expected_start,
+			&spl_token::id(),
+		)
+	};
+
+	let error = process(&mut context, claim(START - DAY), &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::StartMismatch));
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 0);
+
+	process(&mut context, claim(START), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 300);
+}
+
+#[tokio::test]
+async fn test_claim_window_limit() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let mut context = program_test.start_with_context().await;
+	let claim = || {
+		instruction::claim(
+			&program_id,
+			&schedule_address,
+			&mint,
+			&recipient.vesting_account,
+			&source,
+			&recipient.keypair.pubkey(),
+			&recipient.ata,
+			&spl_token::id(),
+		)
+	};
+
+	// Only the owner may limit their claims
+	let other = Keypair::new();
+	let ix = instruction::set_claim_window(
+		&program_id,
+		&other.pubkey(),
+		&recipient.vesting_account,
+		250,
+		DAY,
+	);
+	let error = process(&mut context, ix, &[&other]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+	);
+	let ix = instruction::set_claim_window(
+		&program_id,
+		&recipient.keypair.pubkey(),
+		&recipient.vesting_account,
+		250,
+		DAY,
+	);
+	process(&mut context, ix, &[&recipient.keypair])
+		.await
+		.unwrap();
+
+	// 300 has vested but only 250 may be claimed this window
+	set_clock(&mut context, START + 3 * DAY).await;
+	process(&mut context, claim(), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 250);
+	let error = process(&mut context, claim(), &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::WindowLimitReached)
+	);
+
+	// The next window allows the rest
+	set_clock(&mut context, START + 4 * DAY).await;
+	process(&mut context, claim(), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 400);
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.claimed_in_window, 150);
+}
+
+#[tokio::test]
+async fn test_lock_account() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY).await;
+	let claim = || claim(&program_id, &schedule_address, &mint, &source, &recipient);
+
+	let impostor = Keypair::new();
+	let ix = instruction::lock_account(
+		&program_id,
+		&schedule_address,
+		&impostor.pubkey(),
+		&recipient.vesting_account,
+	);
+	let error = process(&mut context, ix, &[&impostor]).await.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	let ix = instruction::lock_account(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&recipient.vesting_account,
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	let error = process(&mut context, claim(), &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::AccountLocked));
+	let ix = instruction::amend_amount(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&recipient.vesting_account,
+		&source,
+		500,
+		&spl_token::id(),
+	);
+	let error = process(&mut context, ix, &[&authority]).await.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::AccountLocked));
+
+	// The account kept vesting while locked
+	set_clock(&mut context, START + 4 * DAY).await;
+	let ix = instruction::unlock_account(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&recipient.vesting_account,
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	process(&mut context, claim(), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 400);
+}
+
+#[tokio::test]
+async fn test_pause_account() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let paused = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let paused_source = add_ata(&mut program_test, &paused.vesting_account, &mint, 1_000);
+	let other = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let other_source = add_ata(&mut program_test, &other.vesting_account, &mint, 1_000);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY).await;
+
+	let impostor = Keypair::new();
+	let ix = instruction::pause_account(
+		&program_id,
+		&schedule_address,
+		&impostor.pubkey(),
+		&paused.vesting_account,
+	);
+	let error = process(&mut context, ix, &[&impostor]).await.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	let ix = instruction::pause_account(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&paused.vesting_account,
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	let state = vesting_account_state(&mut context, &paused.vesting_account).await;
+	assert!(state.claims_paused);
+	let ix = claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&paused_source,
+		&paused,
+	);
+	let error = process(&mut context, ix, &[&paused.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::AccountClaimsPaused)
+	);
+
+	// Other accounts under the schedule keep claiming
+	let ix = claim(&program_id, &schedule_address, &mint, &other_source, &other);
+	process(&mut context, ix, &[&other.keypair]).await.unwrap();
+	assert_eq!(token_balance(&mut context, &other.ata).await, 300);
+
+	// Nothing is lost to the pause, the account kept vesting throughout
+	set_clock(&mut context, START + 4 * DAY).await;
+	let ix = instruction::unpause_account(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&paused.vesting_account,
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	let ix = claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&paused_source,
+		&paused,
+	);
+	process(&mut context, ix, &[&paused.keypair]).await.unwrap();
+	assert_eq!(token_balance(&mut context, &paused.ata).await, 400);
+}
+
+#[tokio::test]
+async fn test_claim_fallback() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let heir = Keypair::new();
+	let heir_ata = add_ata(&mut program_test, &heir.pubkey(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	let claim_fallback = |fallback_owner: &Pubkey, fallback_owner_ata: &Pubkey| {
+		instruction::claim_fallback(
+			&program_id,
+			&schedule_address,
+			&mint,
+			&recipient.vesting_account,
+			&source,
+			fallback_owner,
+			fallback_owner_ata,
+			&spl_token::id(),
+		)
+	};
+
+	// Only the owner may set a fallback
+	set_clock(&mut context, START + 2 * DAY).await;
+	let set_fallback = |owner: &Pubkey| {
+		instruction::set_fallback(
+			&program_id,
+			owner,
+			&recipient.vesting_account,
+			COption::Some(heir.pubkey()),
+			3 * DAY,
+		)
+	};
+	let error = process(&mut context, set_fallback(&heir.pubkey()), &[&heir])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+	);
+	process(
+		&mut context,
+		set_fallback(&recipient.keypair.pubkey()),
+		&[&recipient.keypair],
+	)
+	.await
+	.unwrap();
+
+	set_clock(&mut context, START + 4 * DAY).await;
+	let error = process(
+		&mut context,
+		claim_fallback(&heir.pubkey(), &heir_ata),
+		&[&heir],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::FallbackNotYetActive)
+	);
+
+	// Claiming restarts the owner's inactivity clock
+	let claim = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+	process(&mut context, claim, &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 400);
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.last_claim, START + 4 * DAY);
+	set_clock(&mut context, START + 7 * DAY - 1).await;
+	let error = process(
+		&mut context,
+		claim_fallback(&heir.pubkey(), &heir_ata),
+		&[&heir],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::FallbackNotYetActive)
+	);
+
+	// Once the owner has been inactive long enough only the fallback owner may claim for them
+	set_clock(&mut context, START + 7 * DAY).await;
+	let stranger = Keypair::new();
+	let stranger_ata = get_associated_token_address(&stranger.pubkey(), &mint);
+	let error = process(
+		&mut context,
+		claim_fallback(&stranger.pubkey(), &stranger_ata),
+		&[&stranger],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+	);
+	process(
+		&mut context,
+		claim_fallback(&heir.pubkey(), &heir_ata),
+		&[&heir],
+	)
+	.await
+	.unwrap();
+	assert_eq!(token_balance(&mut context, &heir_ata).await, 300);
+
+	// The fallback owner's claims don't restart the clock
+	set_clock(&mut context, START + 8 * DAY).await;
+	process(
+		&mut context,
+		claim_fallback(&heir.pubkey(), &heir_ata),
+		&[&heir],
+	)
+	.await
+	.unwrap();
+	assert_eq!(token_balance(&mut context, &heir_ata).await, 400);
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.last_claim, START + 4 * DAY);
+	assert_eq!(state.claimed, 800);
+}
+
+#[tokio::test]
+async fn test_renounce_amend_authority() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let keypair = Keypair::new();
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &keypair.pubkey());
+	let mut state = self::vesting_account(&schedule_address, &mint, 1_000, 0);
+	state.owner = keypair.pubkey();
+	state.rent_payer = authority.pubkey();
+	add_packed_at(&mut program_test, &vesting_account, &program_id, state);
+	let recipient = Recipient {
+		ata: add_ata(&mut program_test, &keypair.pubkey(), &mint, 0),
+		keypair,
+		vesting_account,
+	};
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let destination = add_ata(&mut program_test, &authority.pubkey(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY).await;
+	let renounce = |authority: &Pubkey| {
+		instruction::renounce_amend_authority(&program_id, &schedule_address, authority)
+	};
+	let close = |acknowledged_forfeit: u64| {
+		instruction::close_account(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&mint,
+			&recipient.vesting_account,
+			&source,
+			&destination,
+			acknowledged_forfeit,
+			&spl_token::id(),
+		)
+	};
+
+	let impostor = Keypair::new();
+	let error = process(&mut context, renounce(&impostor.pubkey()), &[&impostor])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	process(&mut context, renounce(&authority.pubkey()), &[&authority])
+		.await
+		.unwrap();
+	assert!(
+		vesting_schedule_state(&mut context, &schedule_address)
+			.await
+			.immutable
+	);
+
+	// Renouncing can't be repeated, nor undone by any amendment
+	let amendments = [
+		renounce(&authority.pubkey()),
+		instruction::amend_schedule(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			None,
+			None,
+			Some(20 * DAY),
+			false,
+		),
+		instruction::schedule_amend(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			START + 5 * DAY,
+			None,
+			None,
+			Some(20 * DAY),
+		),
+		instruction::amend_amount(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&recipient.vesting_account,
+			&source,
+			500,
+			&spl_token::id(),
+		),
+		instruction::adjust_claimed(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&recipient.vesting_account,
+			100,
+		),
+		close(300),
+	];
+	for ix in amendments {
+		let error = process(&mut context, ix, &[&authority]).await.unwrap_err();
+		assert_eq!(
+			error.unwrap(),
+			custom_error(VestingError::ScheduleImmutable)
+		);
+	}
+
+	// Claims go on as before, and the account can be closed once fully vested
+	let claim = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+	process(&mut context, claim, &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 300);
+	set_clock(&mut context, START + 10 * DAY).await;
+	process(&mut context, close(700), &[&authority])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &destination).await, 700);
+}
+
+#[tokio::test]
+async fn test_claim_to_allowed_destination() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let custodian = add_ata(&mut program_test, &Pubkey::new_unique(), &mint, 0);
+	let other = add_ata(&mut program_test, &Pubkey::new_unique(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	let allow = |destination: &Pubkey| {
+		instruction::set_allowed_destination(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&recipient.vesting_account,
+			COption::Some(*destination),
+		)
+	};
+	let claim_to = |destination: &Pubkey| {
+		instruction::claim_to(
+			&program_id,
+			&schedule_address,
+			&mint,
+			&recipient.vesting_account,
+			&source,
+			&recipient.keypair.pubkey(),
+			destination,
+			&spl_token::id(),
+		)
+	};
+
+	// Only the authority may restrict the destination
+	let mut ix_unauthorised = allow(&custodian);
+	ix_unauthorised.accounts[1].pubkey = recipient.keypair.pubkey();
+	let error = process(&mut context, ix_unauthorised, &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	process(&mut context, allow(&custodian), &[&authority])
+		.await
+		.unwrap();
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.allowed_destination, COption::Some(custodian));
+
+	// Claims to the recipient's own ATA or any other token account are rejected
+	let ix = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+	let error = process(&mut context, ix, &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::DestinationNotAllowed)
+	);
+	let error = process(&mut context, claim_to(&other), &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::DestinationNotAllowed)
+	);
+
+	process(&mut context, claim_to(&custodian), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &custodian).await, 300);
+	assert_eq!(token_balance(&mut context, &other).await, 0);
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.claimed, 300);
+}
+
+#[tokio::test]
+async fn test_claim_to_multisig_vault() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	// A multisig's vault is a PDA of the multisig program, its ATA has an off-curve owner
+	let (vault, _) = Pubkey::find_program_address(
+		&[b"vault", recipient.keypair.pubkey().as_ref()],
+		&Pubkey::new_unique(),
+	);
+	assert!(!vault.is_on_curve());
+	let vault_ata = add_ata(&mut program_test, &vault, &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+	let claim_to = instruction::claim_to(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&recipient.vesting_account,
+		&source,
+		&recipient.keypair.pubkey(),
+		&vault_ata,
+		&spl_token::id(),
+	);
+
+	// The recipient still has to sign, the vault's owner never does
+	let mut unsigned = claim_to.clone();
+	unsigned.accounts[4].is_signer = false;
+	let error = process(&mut context, unsigned, &[]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+	);
+
+	process(&mut context, claim_to, &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &vault_ata).await, 300);
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 0);
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.claimed, 300);
+}
+
+#[tokio::test]
+async fn test_claim_and_unwrap() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mint = spl_token::native_mint::id();
+	let mut schedule = daily_schedule(10);
+	schedule.mint = mint;
+	schedule.seed_mint = mint;
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+
+	// The recipient has no native mint ATA yet
+	let recipient = Keypair::new();
+	let wallet = recipient.pubkey();
+	program_test.add_account(
+		wallet,
+		SolanaAccount {
+			lamports: 1_000_000_000,
+			..SolanaAccount::default()
+		},
+	);
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &wallet);
+	let mut state = self::vesting_account(&schedule_address, &mint, 1_000, 0);
+	state.owner = wallet;
+	add_packed_at(&mut program_test, &vesting_account, &program_id, state);
+
+	// The vesting account's ATA holds wrapped SOL, backed by its lamports
+	let rent = Rent::default().minimum_balance(TokenAccount::LEN);
+	let source = get_associated_token_address(&vesting_account, &mint);
+	let mut data = vec![0; TokenAccount::LEN];
+	TokenAccount {
+		mint,
+		owner: vesting_account,
+		amount: 1_000,
+		state: AccountState::Initialized,
+		is_native: COption::Some(rent),
+		..TokenAccount::default()
+	}
+	.pack_into_slice(&mut data);
+	program_test.add_account(
+		source,
+		SolanaAccount {
+			lamports: rent + 1_000,
+			data,
+			owner: spl_token::id(),
+			..SolanaAccount::default()
+		},
+	);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	let recipient_ata = get_associated_token_address(&wallet, &mint);
+	let ix = instruction::claim_and_unwrap(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&vesting_account,
+		&source,
+		&wallet,
+		&recipient_ata,
+		&spl_token::id(),
+	);
+	let before = lamports(&mut context, &wallet).await;
+	process(&mut context, ix, &[&recipient]).await.unwrap();
+
+	// The wrapped SOL lands as lamports in the wallet, and the ATA's rent is returned on closing
+	assert_eq!(lamports(&mut context, &wallet).await, before + 300);
+	assert!(context
+		.banks_client
+		.get_account(recipient_ata)
+		.await
+		.unwrap()
+		.is_none());
+	assert_eq!(lamports(&mut context, &source).await, rent + 700);
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.claimed, 300);
+}
+
+#[tokio::test]
+async fn test_claim_and_unwrap_rejects_other_mints() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	let ix = instruction::claim_and_unwrap(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&recipient.vesting_account,
+		&source,
+		&recipient.keypair.pubkey(),
+		&recipient.ata,
+		&spl_token::id(),
+	);
+	let error = process(&mut context, ix, &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::NotNativeMint));
+}
+
+#[tokio::test]
+async fn test_claim_from_vault() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mut schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let (schedule_address, bump) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	schedule.bump = bump;
+	let vault = add_ata(&mut program_test, &schedule_address, &mint, 5_000);
+	schedule.vault = COption::Some(vault);
+	add_packed_at(&mut program_test, &schedule_address, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	// The vault is the only valid source for a vault-backed schedule
+	let wrong_source = get_associated_token_address(&recipient.vesting_account, &mint);
+	let ix = claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&wrong_source,
+		&recipient,
+	);
+	let error = process(&mut context, ix, &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+	);
+
+	let ix = claim(&program_id, &schedule_address, &mint, &vault, &recipient);
+	process(&mut context, ix, &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 300);
+	assert_eq!(token_balance(&mut context, &vault).await, 4_700);
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.claimed, 300);
+}
+
+#[tokio::test]
+async fn test_claim_rejects_non_canonical_recipient_ata() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	let other_mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	add_mint(&mut program_test, &other_mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let mut recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let canonical_ata = recipient.ata;
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	// Another token account of the right mint owned by the recipient
+	let other_account = Keypair::new();
+	let rent = context.banks_client.get_rent().await.unwrap();
+	let create = [
+		solana_sdk::system_instruction::create_account(
+			&context.payer.pubkey(),
+			&other_account.pubkey(),
+			rent.minimum_balance(TokenAccount::LEN),
+			TokenAccount::LEN as u64,
+			&spl_token::id(),
+		),
+		spl_token::instruction::initialize_account3(
+			&spl_token::id(),
+			&other_account.pubkey(),
+			&mint,
+			&recipient.keypair.pubkey(),
+		)
+		.unwrap(),
+	];
+	let transaction = Transaction::new_signed_with_payer(
+		&create,
+		Some(&context.payer.pubkey()),
+		&[&context.payer, &other_account],
+		context.last_blockhash,
+	);
+	context
+		.banks_client
+		.process_transaction(transaction)
+		.await
+		.unwrap();
+
+	for ata in [
+		other_account.pubkey(),
+		get_associated_token_address(&recipient.keypair.pubkey(), &other_mint),
+	] {
+		recipient.ata = ata;
+		let ix = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+		let error = process(&mut context, ix, &[&recipient.keypair])
+			.await
+			.unwrap_err();
+		assert_eq!(
+			error.unwrap(),
+			TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+		);
+	}
+	assert_eq!(token_balance(&mut context, &canonical_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_claim_creates_missing_recipient_ata() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let keypair = Keypair::new();
+	program_test.add_account(
+		keypair.pubkey(),
+		SolanaAccount {
+			lamports: 1_000_000_000,
+			..SolanaAccount::default()
+		},
+	);
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &keypair.pubkey());
+	let mut state = self::vesting_account(&schedule_address, &mint, 1_000, 0);
+	state.owner = keypair.pubkey();
+	add_packed_at(&mut program_test, &vesting_account, &program_id, state);
+	let source = add_ata(&mut program_test, &vesting_account, &mint, 1_000);
+	let recipient = Recipient {
+		ata: get_associated_token_address(&keypair.pubkey(), &mint),
+		keypair,
+		vesting_account,
+	};
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+	assert!(context
+		.banks_client
+		.get_account(recipient.ata)
+		.await
+		.unwrap()
+		.is_none());
+
+	let ix = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+	process(&mut context, ix.clone(), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 300);
+
+	// An existing ATA is reused
+	set_clock(&mut context, START + 5 * DAY).await;
+	process(&mut context, ix, &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 500);
+}
+
+#[tokio::test]
+async fn test_schedule_amend() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let account = vesting_account(&schedule_address, &mint, 1_000, 0);
+	let account = add_packed(&mut program_test, &program_id, account);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + DAY).await;
+
+	let schedule_amend = |effective_at: i64| {
+		instruction::schedule_amend(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			effective_at,
+			None,
+			None,
+			Some(5 * DAY),
+		)
+	};
+
+	// Amendments must give notice
+	let error = process(&mut context, schedule_amend(START + DAY), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+
+	// As is one that doesn't emit over a whole number of periods
+	let error = process(
+		&mut context,
+		instruction::schedule_amend(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			START + 3 * DAY,
+			None,
+			None,
+			Some(5 * DAY + 1),
+		),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+
+	// An amendment that would have vested everything by the time it takes effect is rejected
+	let error = process(
+		&mut context,
+		instruction::schedule_amend(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			START + 3 * DAY,
+			None,
+			None,
+			Some(2 * DAY),
+		),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+
+	let effective_at = START + 3 * DAY;
+	process(&mut context, schedule_amend(effective_at), &[&authority])
+		.await
+		.unwrap();
+	let state = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(state.duration, 10 * DAY);
+	assert_eq!(
+		state.pending_amendment,
+		Some(PendingAmendment {
+			effective_at,
+			start: None,
+			frequency: None,
+			duration: Some(5 * DAY),
+		})
+	);
+
+	let get_claimable =
+		instruction::get_claimable_many(&program_id, &schedule_address, &[account]).unwrap();
+	set_clock(&mut context, effective_at - 1).await;
+	let return_data = simulate_return_data(&mut context, get_claimable.clone())
+		.await
+		.unwrap();
+	assert_eq!(return_data, 200u64.to_le_bytes());
+
+	set_clock(&mut context, effective_at).await;
+	let return_data = simulate_return_data(&mut context, get_claimable)
+		.await
+		.unwrap();
+	assert_eq!(return_data, 600u64.to_le_bytes());
+}
+
+#[tokio::test]
+async fn test_claim_drains_vault_to_zero() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let (schedule_address, bump) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	schedule.bump = bump;
+	let vault = add_ata(&mut program_test, &schedule_address, &mint, 1_500);
+	schedule.vault = COption::Some(vault);
+	add_packed_at(&mut program_test, &schedule_address, &program_id, schedule);
+	let first = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let second = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		500,
+	);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 10 * DAY).await;
+
+	let ix = claim(&program_id, &schedule_address, &mint, &vault, &first);
+	process(&mut context, ix, &[&first.keypair]).await.unwrap();
+	assert_eq!(token_balance(&mut context, &vault).await, 500);
+
+	// The final claim matches the remaining balance exactly
+	let ix = claim(&program_id, &schedule_address, &mint, &vault, &second);
+	process(&mut context, ix.clone(), &[&second.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &second.ata).await, 500);
+
+	// The emptied vault holds no tokens
+	let vault_account = context
+		.banks_client
+		.get_account(vault)
+		.await
+		.unwrap()
+		.unwrap();
+	let vault_state = TokenAccount::unpack(&vault_account.data).unwrap();
+	assert_eq!(vault_state.amount, 0);
+	assert_eq!(vault_state.owner, schedule_address);
+	assert!(vault_state.is_native.is_none());
+
+	let error = process(&mut context, ix, &[&second.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::NothingToClaim));
+
+	// So the authority can close it along with the schedule
+	let ix = instruction::close_vesting_schedule(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&vault,
+		&spl_token::id(),
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	assert!(context
+		.banks_client
+		.get_account(vault)
+		.await
+		.unwrap()
+		.is_none());
+}
+
+#[tokio::test]
+async fn test_claim_rejects_spoofed_programs() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	// System, token and associated token program accounts
+	for index in [6, 7, 8] {
+		let mut ix = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+		ix.accounts[index].pubkey = Pubkey::new_unique();
+		let error = process(&mut context, ix, &[&recipient.keypair])
+			.await
+			.unwrap_err();
+		assert_eq!(
+			error.unwrap(),
+			custom_error(VestingError::IncorrectProgramId)
+		);
+	}
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_claim_rejects_missing_programs() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	// Omitting the system, token or associated token program, and any that follow it
+	for len in [6, 7, 8] {
+		let mut ix = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+		ix.accounts.truncate(len);
+		let error = process(&mut context, ix, &[&recipient.keypair])
+			.await
+			.unwrap_err();
+		assert_eq!(
+			error.unwrap(),
+			custom_error(VestingError::MissingRequiredAccount)
+		);
+	}
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 0);
+}
+
+fn init(
+	program_id: &Pubkey,
+	payer: &Pubkey,
+	mint: &Pubkey,
+	duration: i64,
+	vault: COption<Pubkey>,
+) -> Instruction {
+	instruction::init_vesting_schedule(
+		program_id,
+		"daily",
+		payer,
+		&Pubkey::new_unique(),
+		mint,
+		Frequency::Day,
+		START,
+		duration,
+		DAY,
+		vault,
+		false,
+		COption::None,
+		0,
+		COption::None,
+		None,
+		false,
+		DEFAULT_PAST_START_TOLERANCE,
+		0,
+		0,
+		&spl_token::id(),
+	)
+}
+
+#[tokio::test]
+async fn test_init_vesting_schedule() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START).await;
+	let payer = context.payer.pubkey();
+	let (schedule_address, bump) = find_vesting_schedule_address(&program_id, &mint, "daily");
+
+	// The schedule must live at the address derived from its mint and identifier
+	let mut ix = init(&program_id, &payer, &mint, 10 * DAY, COption::None);
+	ix.accounts[0].pubkey = Pubkey::new_unique();
+	let error = process(&mut context, ix, &[]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+	);
+
+	let ix = init(&program_id, &payer, &mint, 0, COption::None);
+	let error = process(&mut context, ix, &[]).await.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+
+	// The duration must be a whole number of periods
+	let ix = init(&program_id, &payer, &mint, 10 * DAY + 1, COption::None);
+	let error = process(&mut context, ix, &[]).await.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+
+	// The mint is always needed for its decimals
+	let mut ix = init(&program_id, &payer, &mint, 10 * DAY, COption::None);
+	ix.accounts.truncate(3);
+	let error = process(&mut context, ix, &[]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::MissingRequiredAccount)
+	);
+
+	// Without a vault the token accounts and programs are omitted
+	let ix = init(&program_id, &payer, &mint, 10 * DAY, COption::None);
+	assert_eq!(ix.accounts.len(), 4);
+	process(&mut context, ix.clone(), &[]).await.unwrap();
+	let account = context
+		.banks_client
+		.get_account(schedule_address)
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(account.owner, program_id);
+	assert_eq!(
+		account.lamports,
+		Rent::default().minimum_balance(VestingSchedule::LEN)
+	);
+	let schedule = VestingSchedule::unpack(&account.data).unwrap();
+	assert!(schedule.is_initialized);
+	assert_eq!(schedule.mint, mint);
+	assert_eq!(schedule.frequency, Frequency::Day);
+	assert_eq!(schedule.start, START);
+	assert_eq!(schedule.duration, 10 * DAY);
+	assert_eq!(schedule.cliff, DAY);
+	assert_eq!(schedule.vault, COption::None);
+	assert_eq!(schedule.identifier, hash_identifier("daily"));
+	assert_eq!(schedule.bump, bump);
+	assert_eq!(schedule.decimals, 6);
+
+	let query = instruction::get_ui_context(&program_id, &schedule_address);
+	let return_data = simulate_return_data(&mut context, query).await.unwrap();
+	assert_eq!(return_data, [6]);
+
+	let error = process(&mut context, ix, &[]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::AlreadyInitialized)
+	);
+}
+
+#[tokio::test]
+async fn test_init_by_count() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START).await;
+	let payer = context.payer.pubkey();
+	let init_by_count = |identifier: &str, schedule: Frequency, emissions: u32| {
+		instruction::init_by_count(
+			&program_id,
+			identifier,
+			&payer,
+			&Pubkey::new_unique(),
+			&mint,
+			schedule,
+			START,
+			emissions,
+			0,
+			COption::None,
+			false,
+			COption::None,
+			0,
+			COption::None,
+			None,
+			false,
+			DEFAULT_PAST_START_TOLERANCE,
+			0,
+			0,
+			&spl_token::id(),
+		)
+	};
+
+	for (schedule, emissions) in [(Frequency::Month, 0), (Frequency::Linear, 36)] {
+		let ix = init_by_count("monthly", schedule, emissions);
+		let error = process(&mut context, ix, &[]).await.unwrap_err();
+		assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+	}
+
+	// 36 monthly unlocks are the same schedule as a duration of 36 months
+	let ix = init_by_count("monthly", Frequency::Month, 36);
+	process(&mut context, ix, &[]).await.unwrap();
+	let ix = instruction::init_vesting_schedule(
+		&program_id,
+		"by-duration",
+		&payer,
+		&Pubkey::new_unique(),
+		&mint,
+		Frequency::Month,
+		START,
+		36 * 2_629_746,
+		0,
+		COption::None,
+		false,
+		COption::None,
+		0,
+		COption::None,
+		None,
+		false,
+		DEFAULT_PAST_START_TOLERANCE,
+		0,
+		0,
+		&spl_token::id(),
+	);
+	process(&mut context, ix, &[]).await.unwrap();
+
+	let (by_count, _) = find_vesting_schedule_address(&program_id, &mint, "monthly");
+	let (by_duration, _) = find_vesting_schedule_address(&program_id, &mint, "by-duration");
+	let by_count = vesting_schedule_state(&mut context, &by_count).await;
+	let by_duration = vesting_schedule_state(&mut context, &by_duration).await;
+	assert_eq!(by_count.duration, 36 * 2_629_746);
+	assert_eq!(by_count.total_periods(), Ok(36));
+	for schedule in [&by_count, &by_duration] {
+		assert_eq!(schedule.frequency, Frequency::Month);
+		assert_eq!(schedule.start, START);
+		assert_eq!(schedule.cliff, 0);
+	}
+	for now in [
+		START,
+		START + 2_629_746,
+		START + 35 * 2_629_746,
+		START + 36 * 2_629_746,
+	] {
+		assert_eq!(
+			by_count.claimable_amount(now, 36_000, 0),
+			by_duration.claimable_amount(now, 36_000, 0)
+		);
+	}
+	assert_eq!(
+		by_count.claimable_amount(START + 2_629_746, 36_000, 0),
+		Ok(1_000)
+	);
+}
+
+#[tokio::test]
+async fn test_set_label() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START).await;
+	let payer = context.payer.pubkey();
+	let (schedule_address, bump) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	let ix = instruction::init_vesting_schedule(
+		&program_id,
+		"daily",
+		&payer,
+		&payer,
+		&mint,
+		Frequency::Day,
+		START,
+		10 * DAY,
+		0,
+		COption::None,
+		false,
+		COption::None,
+		0,
+		COption::None,
+		None,
+		false,
+		DEFAULT_PAST_START_TOLERANCE,
+		0,
+		0,
+		&spl_token::id(),
+	);
+	process(&mut context, ix, &[]).await.unwrap();
+	let schedule = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(schedule.label, pad_label("").unwrap());
+
+	let label = pad_label("Seed round").unwrap();
+	let impostor = Keypair::new();
+	let ix = instruction::set_label(&program_id, &schedule_address, &impostor.pubkey(), label);
+	let error = process(&mut context, ix, &[&impostor]).await.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	let ix = instruction::set_label(&program_id, &schedule_address, &payer, label);
+	process(&mut context, ix, &[]).await.unwrap();
+	let schedule = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(schedule.label, label);
+
+	// The label plays no part in the schedule's address
+	assert_eq!(schedule.identifier, hash_identifier("daily"));
+	assert_eq!(schedule.bump, bump);
+	assert_eq!(
+		create_vesting_schedule_address(&program_id, &mint, &schedule.identifier, schedule.bump),
+		Ok(schedule_address)
+	);
+}
+
+#[tokio::test]
+async fn test_init_vesting_schedule_past_start() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + DAY).await;
+	let payer = context.payer.pubkey();
+	let init = |identifier: &str, start: i64, allow_past_start: bool, tolerance: i64| {
+		instruction::init_vesting_schedule(
+			&program_id,
+			identifier,
+			&payer,
+			&Pubkey::new_unique(),
+			&mint,
+			Frequency::Day,
+			start,
+			10 * DAY,
+			0,
+			COption::None,
+			false,
+			COption::None,
+			0,
+			COption::None,
+			None,
+			allow_past_start,
+			tolerance,
+			0,
+			0,
+			&spl_token::id(),
+		)
+	};
+
+	// Starts within the tolerance are allowed for the time taken to land the transaction
+	let tolerance = DEFAULT_PAST_START_TOLERANCE;
+	let ix = init("past", START + DAY - tolerance - 1, false, tolerance);
+	let error = process(&mut context, ix, &[]).await.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+	let ix = init("recent", START + DAY - tolerance, false, tolerance);
+	process(&mut context, ix, &[]).await.unwrap();
+
+	// The tolerance is chosen by the caller and kept on the schedule
+	let ix = init("backdated", START, false, DAY);
+	process(&mut context, ix, &[]).await.unwrap();
+	let (schedule_address, _) = find_vesting_schedule_address(&program_id, &mint, "backdated");
+	let schedule = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(schedule.past_start_tolerance, DAY);
+	let ix = init("negative", START + DAY, false, -1);
+	let error = process(&mut context, ix, &[]).await.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+
+	let ix = init("past", START, true, tolerance);
+	process(&mut context, ix, &[]).await.unwrap();
+
+	let ix = init("future", START + 100 * DAY, false, 0);
+	process(&mut context, ix, &[]).await.unwrap();
+	let (schedule_address, _) = find_vesting_schedule_address(&program_id, &mint, "future");
+	let schedule = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(schedule.start, START + 100 * DAY);
+}
+
+#[tokio::test]
+async fn test_init_vesting_schedule_quantizes_start() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START).await;
+	let payer = context.payer.pubkey();
+	let (schedule_address, _) = find_vesting_schedule_address(&program_id, &mint, "daily");
+
+	let ix = instruction::init_vesting_schedule(
+		&program_id,
+		"daily",
+		&payer,
+		&Pubkey::new_unique(),
+		&mint,
+		Frequency::Day,
+		START,
+		10 * DAY,
+		0,
+		COption::None,
+		false,
+		COption::None,
+		0,
+		COption::None,
+		Some(DAY),
+		false,
+		DEFAULT_PAST_START_TOLERANCE,
+		0,
+		0,
+		&spl_token::id(),
+	);
+	process(&mut context, ix, &[]).await.unwrap();
+	let schedule = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(schedule.start, START - START % DAY);
+	assert_eq!(schedule.start % DAY, 0);
+}
+
+#[tokio::test]
+async fn test_init_vesting_schedule_with_vault() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START).await;
+	let payer = context.payer.pubkey();
+	let (schedule_address, _) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	let vault = get_associated_token_address(&schedule_address, &mint);
+
+	// A vault needs its token accounts and programs to be passed
+	for len in [4, 6] {
+		let mut ix = init(&program_id, &payer, &mint, 10 * DAY, COption::Some(vault));
+		ix.accounts.truncate(len);
+		let error = process(&mut context, ix, &[]).await.unwrap_err();
+		assert_eq!(
+			error.unwrap(),
+			custom_error(VestingError::MissingRequiredAccount)
+		);
+	}
+
+	// Only the schedule's own ATA may serve as its vault
+	let ix = init(
+		&program_id,
+		&payer,
+		&mint,
+		10 * DAY,
+		COption::Some(Pubkey::new_unique()),
+	);
+	let error = process(&mut context, ix, &[]).await.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidVault));
+
+	let ix = init(&program_id, &payer, &mint, 10 * DAY, COption::Some(vault));
+	process(&mut context, ix, &[]).await.unwrap();
+	let account = context
+		.banks_client
+		.get_account(schedule_address)
+		.await
+		.unwrap()
+		.unwrap();
+	let schedule = VestingSchedule::unpack(&account.data).unwrap();
+	assert_eq!(schedule.vault, COption::Some(vault));
+
+	let vault_account = context
+		.banks_client
+		.get_account(vault)
+		.await
+		.unwrap()
+		.unwrap();
+	let vault_state = TokenAccount::unpack(&vault_account.data).unwrap();
+	assert_eq!(vault_state.owner, schedule_address);
+	assert_eq!(vault_state.mint, mint);
+	assert_eq!(vault_state.amount, 0);
+}
+
+#[tokio::test]
+async fn test_claim_mints_receipt() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mut schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let (schedule_address, bump) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	schedule.bump = bump;
+
+	// Receipt mints whose authority is the schedule and one whose authority isn't
+	let receipt_mint = Pubkey::new_unique();
+	let foreign_receipt_mint = Pubkey::new_unique();
+	for (address, authority) in [
+		(receipt_mint, schedule_address),
+		(foreign_receipt_mint, Pubkey::new_unique()),
+	] {
+		let state = Mint {
+			mint_authority: COption::Some(authority),
+			is_initialized: true,
+			..Mint::default()
+		};
+		add_packed_at(&mut program_test, &address, &spl_token::id(), state);
+	}
+	schedule.receipt_mint = COption::Some(foreign_receipt_mint);
+	add_packed_at(
+		&mut program_test,
+		&schedule_address,
+		&program_id,
+		schedule.clone(),
+	);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	let claim = |receipt_mint: &Pubkey| {
+		instruction::claim_with_receipt(
+			&program_id,
+			&schedule_address,
+			&mint,
+			&recipient.vesting_account,
+			&source,
+			&recipient.keypair.pubkey(),
+			&recipient.ata,
+			receipt_mint,
+			&spl_token::id(),
+		)
+	};
+	let error = process(
+		&mut context,
+		claim(&foreign_receipt_mint),
+		&[&recipient.keypair],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::IncorrectAuthority)
+	);
+
+	schedule.receipt_mint = COption::Some(receipt_mint);
+	let mut data = vec![0; VestingSchedule::LEN];
+	VestingSchedule::pack(schedule, &mut data).unwrap();
+	let mut account = context
+		.banks_client
+		.get_account(schedule_address)
+		.await
+		.unwrap()
+		.unwrap();
+	account.data = data;
+	context.set_account(&schedule_address, &account.into());
+
+	let receipt_ata = get_associated_token_address(&recipient.keypair.pubkey(), &receipt_mint);
+	process(&mut context, claim(&receipt_mint), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 300);
+	assert_eq!(token_balance(&mut context, &receipt_ata).await, 1);
+
+	set_clock(&mut context, START + 5 * DAY).await;
+	process(&mut context, claim(&receipt_mint), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 500);
+	assert_eq!(token_balance(&mut context, &receipt_ata).await, 2);
+}
+
+#[tokio::test]
+async fn test_create_account() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	program_test.add_account(
+		authority.pubkey(),
+		SolanaAccount {
+			lamports: 1_000_000_000,
+			..SolanaAccount::default()
+		},
+	);
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	let other_mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	add_mint(&mut program_test, &other_mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + DAY).await;
+
+	let owner = Pubkey::new_unique();
+	let (vesting_account, _) = find_vesting_account_address(&program_id, &schedule_address, &owner);
+	let vesting_account_ata = get_associated_token_address(&vesting_account, &mint);
+	let create = |authority: &Pubkey, mint: &Pubkey| {
+		instruction::create_account(
+			&program_id,
+			&schedule_address,
+			authority,
+			mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&owner,
+			1_000,
+			0,
+			&spl_token::id(),
+		)
+	};
+
+	for len in [5, 6, 7] {
+		let mut ix = create(&authority.pubkey(), &mint);
+		ix.accounts.truncate(len);
+		let error = process(&mut context, ix, &[&authority]).await.unwrap_err();
+		assert_eq!(
+			error.unwrap(),
+			custom_error(VestingError::MissingRequiredAccount)
+		);
+	}
+
+	let impostor = Keypair::new();
+	let error = process(
+		&mut context,
+		create(&impostor.pubkey(), &mint),
+		&[&impostor],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	let error = process(
+		&mut context,
+		create(&authority.pubkey(), &other_mint),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidMint));
+
+	process(
+		&mut context,
+		create(&authority.pubkey(), &mint),
+		&[&authority],
+	)
+	.await
+	.unwrap();
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert!(state.is_initialized);
+	assert_eq!(state.vesting_schedule, schedule_address);
+	assert_eq!(state.owner, owner);
+	assert_eq!(state.mint, mint);
+	assert_eq!(state.amount, 1_000);
+	assert_eq!(state.claimed, 0);
+	assert_eq!(state.created_at, START + DAY);
+	assert_eq!(state.rent_payer, authority.pubkey());
+
+	let ata = context
+		.banks_client
+		.get_account(vesting_account_ata)
+		.await
+		.unwrap()
+		.unwrap();
+	let ata = TokenAccount::unpack(&ata.data).unwrap();
+	assert_eq!(ata.owner, vesting_account);
+	assert_eq!(ata.mint, mint);
+
+	let error = process(
+		&mut context,
+		create(&authority.pubkey(), &mint),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::AlreadyInitialized)
+	);
+}
+
+#[tokio::test]
+async fn test_create_account_ata_owned_by_vesting_account() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	program_test.add_account(
+		authority.pubkey(),
+		SolanaAccount {
+			lamports: 1_000_000_000,
+			..SolanaAccount::default()
+		},
+	);
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let authority_ata = add_ata(&mut program_test, &authority.pubkey(), &mint, 0);
+
+	// An ATA created ahead of time with the authority as its delegate
+	let delegated_owner = Pubkey::new_unique();
+	let (delegated_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &delegated_owner);
+	let delegated_ata = get_associated_token_address(&delegated_account, &mint);
+	let state = TokenAccount {
+		mint,
+		owner: delegated_account,
+		delegate: COption::Some(authority.pubkey()),
+		delegated_amount: u64::MAX,
+		state: AccountState::Initialized,
+		..TokenAccount::default()
+	};
+	add_packed_at(&mut program_test, &delegated_ata, &spl_token::id(), state);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + DAY).await;
+	let create = |owner: &Pubkey, vesting_account: &Pubkey, ata: &Pubkey| {
+		instruction::create_account(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&mint,
+			vesting_account,
+			ata,
+			owner,
+			1_000,
+			0,
+			&spl_token::id(),
+		)
+	};
+
+	let owner = Pubkey::new_unique();
+	let (vesting_account, _) = find_vesting_account_address(&program_id, &schedule_address, &owner);
+	let vesting_account_ata = get_associated_token_address(&vesting_account, &mint);
+	process(
+		&mut context,
+		create(&owner, &vesting_account, &vesting_account_ata),
+		&[&authority],
+	)
+	.await
+	.unwrap();
+	let mut ata = context
+		.banks_client
+		.get_account(vesting_account_ata)
+		.await
+		.unwrap()
+		.unwrap();
+	let mut token_account = TokenAccount::unpack(&ata.data).unwrap();
+	assert_eq!(token_account.owner, vesting_account);
+	assert!(!vesting_account.is_on_curve());
+	assert_eq!(token_account.delegate, COption::None);
+	assert_eq!(token_account.close_authority, COption::None);
+
+	// Once pre-loaded the authority has no way to move the tokens out
+	token_account.amount = 1_000;
+	TokenAccount::pack(token_account, &mut ata.data).unwrap();
+	context.set_account(&vesting_account_ata, &ata.into());
+	let ix = spl_token::instruction::transfer(
+		&spl_token::id(),
+		&vesting_account_ata,
+		&authority_ata,
+		&authority.pubkey(),
+		&[],
+		1_000,
+	)
+	.unwrap();
+	let error = process(&mut context, ix, &[&authority]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(
+			0,
+			InstructionError::Custom(TokenError::OwnerMismatch as u32)
+		)
+	);
+	assert_eq!(
+		token_balance(&mut context, &vesting_account_ata).await,
+		1_000
+	);
+
+	// Nor can an ATA someone else has authority over be used
+	let error = process(
+		&mut context,
+		create(&delegated_owner, &delegated_account, &delegated_ata),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::TokenOwnerMismatch)
+	);
+}
+
+#[tokio::test]
+async fn test_create_account_after_completion() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	program_test.add_account(
+		authority.pubkey(),
+		SolanaAccount {
+			lamports: 1_000_000_000,
+			..SolanaAccount::default()
+		},
+	);
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let owner = Pubkey::new_unique();
+	let (vesting_account, _) = find_vesting_account_address(&program_id, &schedule_address, &owner);
+	let vesting_account_ata = get_associated_token_address(&vesting_account, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 10 * DAY).await;
+
+	let ix = instruction::create_account(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&owner,
+		1_000,
+		0,
+		&spl_token::id(),
+	);
+	let error = process(&mut context, ix, &[&authority]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::ScheduleAlreadyCompleted)
+	);
+
+	let ix = instruction::create_account_after_completion(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&owner,
+		1_000,
+		0,
+		&spl_token::id(),
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.amount, 1_000);
+}
+
+#[tokio::test]
+async fn test_redeem_voucher() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let owner = Keypair::new();
+	program_test.add_account(
+		owner.pubkey(),
+		SolanaAccount {
+			lamports: 1_000_000_000,
+			..SolanaAccount::default()
+		},
+	);
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &owner.pubkey());
+	let vesting_account_ata = get_associated_token_address(&vesting_account, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START).await;
+	let expiry = START + DAY;
+
+	// The authority signs the voucher off-chain, the owner redeems it after an ed25519 program
+	// instruction verifying that signature
+	let redeem = |signer: &Keypair, amount: u64| {
+		let message =
+			instruction::voucher_message(&schedule_address, &owner.pubkey(), amount, expiry);
+		let signature: [u8; 64] = signer.sign_message(&message).into();
+		let verify = new_ed25519_instruction_with_signature(
+			&message,
+			&signature,
+			&signer.pubkey().to_bytes(),
+		);
+		let redeem = instruction::redeem_voucher(
+			&program_id,
+			&schedule_address,
+			&owner.pubkey(),
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			1_000,
+			expiry,
+			signature,
+			&spl_token::id(),
+		);
+		[verify, redeem]
+	};
+	let voucher_error = |index: u8, error: VestingError| {
+		TransactionError::InstructionError(index, InstructionError::Custom(error as u32))
+	};
+
+	// Without the signature check, forged by someone other than the authority, or for a
+	// different amount than was signed
+	let [_, unverified] = redeem(&authority, 1_000);
+	let error = process(&mut context, unverified, &[&owner])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		voucher_error(0, VestingError::InvalidVoucher)
+	);
+	for (signer, amount) in [(&owner, 1_000), (&authority, 2_000)] {
+		let error = process_all(&mut context, &redeem(signer, amount), &[&owner])
+			.await
+			.unwrap_err();
+		assert_eq!(
+			error.unwrap(),
+			voucher_error(1, VestingError::InvalidVoucher)
+		);
+	}
+
+	process_all(&mut context, &redeem(&authority, 1_000), &[&owner])
+		.await
+		.unwrap();
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.owner, owner.pubkey());
+	assert_eq!(state.amount, 1_000);
+	assert_eq!(state.rent_payer, owner.pubkey());
+
+	// Vouchers can't be redeemed once they've expired
+	set_clock(&mut context, expiry + 1).await;
+	let error = process_all(&mut context, &redeem(&authority, 1_000), &[&owner])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		voucher_error(1, VestingError::VoucherExpired)
+	);
+}
+
+#[tokio::test]
+async fn test_create_account_with_immediate_release() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let owner = Keypair::new();
+	for keypair in [&authority, &owner] {
+		program_test.add_account(
+			keypair.pubkey(),
+			SolanaAccount {
+				lamports: 1_000_000_000,
+				..SolanaAccount::default()
+			},
+		);
+	}
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	schedule.cliff = 3 * DAY;
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &owner.pubkey());
+	let vesting_account_ata = get_associated_token_address(&vesting_account, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START - DAY).await;
+	let create = |immediate_bps: u16| {
+		instruction::create_account(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&owner.pubkey(),
+			1_000,
+			immediate_bps,
+			&spl_token::id(),
+		)
+	};
+
+	let error = process(&mut context, create(10_001), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+	);
+	process(&mut context, create(2_000), &[&authority])
+		.await
+		.unwrap();
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.immediate_bps, 2_000);
+
+	// Pre-load the vesting account's ATA
+	let mut ata = context
+		.banks_client
+		.get_account(vesting_account_ata)
+		.await
+		.unwrap()
+		.unwrap();
+	let mut token_account = TokenAccount::unpack(&ata.data).unwrap();
+	token_account.amount = 1_000;
+	TokenAccount::pack(token_account, &mut ata.data).unwrap();
+	context.set_account(&vesting_account_ata, &ata.into());
+
+	let recipient_ata = get_associated_token_address(&owner.pubkey(), &mint);
+	let claim = instruction::claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&owner.pubkey(),
+		&recipient_ata,
+		&spl_token::id(),
+	);
+
+	// The immediate share is available at the start, ahead of the cliff
+	set_clock(&mut context, START).await;
+	process(&mut context, claim.clone(), &[&owner])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient_ata).await, 200);
+
+	set_clock(&mut context, START + 5 * DAY).await;
+	process(&mut context, claim, &[&owner]).await.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient_ata).await, 600);
+}
+
+#[tokio::test]
+async fn test_claim_cliff_lump() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let owner = Keypair::new();
+	for keypair in [&authority, &owner] {
+		program_test.add_account(
+			keypair.pubkey(),
+			SolanaAccount {
+				lamports: 1_000_000_000,
+				..SolanaAccount::default()
+			},
+		);
+	}
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	schedule.cliff = 3 * DAY;
+	schedule.cliff_bps = 2_500;
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &owner.pubkey());
+	let vesting_account_ata = get_associated_token_address(&vesting_account, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START - DAY).await;
+	let create = |immediate_bps: u16| {
+		instruction::create_account(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&owner.pubkey(),
+			1_000,
+			immediate_bps,
+			&spl_token::id(),
+		)
+	};
+
+	// The immediate share and the cliff lump can't add up to more than the whole amount
+	let error = process(&mut context, create(7_501), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+	);
+	process(&mut context, create(1_000), &[&authority])
+		.await
+		.unwrap();
+
+	let mut ata = context
+		.banks_client
+		.get_account(vesting_account_ata)
+		.await
+		.unwrap()
+		.unwrap();
+	let mut token_account = TokenAccount::unpack(&ata.data).unwrap();
+	token_account.amount = 1_000;
+	TokenAccount::pack(token_account, &mut ata.data).unwrap();
+	context.set_account(&vesting_account_ata, &ata.into());
+
+	let recipient_ata = get_associated_token_address(&owner.pubkey(), &mint);
+	let claim = instruction::claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&owner.pubkey(),
+		&recipient_ata,
+		&spl_token::id(),
+	);
+
+	// Only the immediate share is released before the cliff
+	set_clock(&mut context, START + 2 * DAY).await;
+	process(&mut context, claim.clone(), &[&owner])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient_ata).await, 100);
+
+	// A quarter is released at the cliff, the rest vests over the 7 days after it
+	set_clock(&mut context, START + 3 * DAY).await;
+	process(&mut context, claim.clone(), &[&owner])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient_ata).await, 350);
+
+	set_clock(&mut context, START + 4 * DAY).await;
+	process(&mut context, claim.clone(), &[&owner])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient_ata).await, 442);
+
+	set_clock(&mut context, START + 10 * DAY).await;
+	process(&mut context, claim, &[&owner]).await.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient_ata).await, 1_000);
+}
+
+#[tokio::test]
+async fn test_create_account_by_bps() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	program_test.add_account(
+		authority.pubkey(),
+		SolanaAccount {
+			lamports: 1_000_000_000,
+			..SolanaAccount::default()
+		},
+	);
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	schedule.pool_total = 10_001;
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + DAY).await;
+
+	let create = |owner: &Pubkey, bps: u16| {
+		let (vesting_account, _) =
+			find_vesting_account_address(&program_id, &schedule_address, owner);
+		let vesting_account_ata = get_associated_token_address(&vesting_account, &mint);
+		let ix = instruction::create_account_by_bps(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			owner,
+			bps,
+			&spl_token::id(),
+		);
+		(ix, vesting_account)
+	};
+
+	let mut total = 0;
+	for (bps, amount) in [(5_000, 5_000), (3_000, 3_000), (1_999, 1_999)] {
+		let (ix, vesting_account) = create(&Pubkey::new_unique(), bps);
+		process(&mut context, ix, &[&authority]).await.unwrap();
+		let state = vesting_account_state(&mut context, &vesting_account).await;
+		assert_eq!(state.amount, amount);
+		total += state.amount;
+	}
+	assert!(total <= 10_001);
+	let state = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(state.allocated_bps, 9_999);
+
+	let (ix, _) = create(&Pubkey::new_unique(), 2);
+	let error = process(&mut context, ix, &[&authority]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::AllocationExceedsPool)
+	);
+	let state = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(state.allocated_bps, 9_999);
+}
+
+#[tokio::test]
+async fn test_adjust_claimed() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let state = vesting_account(&schedule_address, &mint, 1_000, 300);
+	let vesting_account = add_packed(&mut program_test, &program_id, state);
+	let mut context = program_test.start_with_context().await;
+	let adjust = |authority: &Pubkey, delta: i64| {
+		instruction::adjust_claimed(
+			&program_id,
+			&schedule_address,
+			authority,
+			&vesting_account,
+			delta,
+		)
+	};
+
+	let impostor = Keypair::new();
+	let error = process(&mut context, adjust(&impostor.pubkey(), 1), &[&impostor])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	// Adjustments are clamped to between nothing and everything claimed
+	for (delta, claimed) in [(200, 500), (-600, 0), (2_000, 1_000), (-250, 750)] {
+		process(
+			&mut context,
+			adjust(&authority.pubkey(), delta),
+			&[&authority],
+		)
+		.await
+		.unwrap();
+		let state = vesting_account_state(&mut context, &vesting_account).await;
+		assert_eq!(state.claimed, claimed);
+	}
+
+	// Nothing changes without a delta, or once claimed is at the bound being pushed against
+	let error = process(&mut context, adjust(&authority.pubkey(), 0), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::AdjustmentOutOfRange)
+	);
+	for (delta, excess) in [(250, 1), (-1_000, -1)] {
+		process(
+			&mut context,
+			adjust(&authority.pubkey(), delta),
+			&[&authority],
+		)
+		.await
+		.unwrap();
+		let error = process(
+			&mut context,
+			adjust(&authority.pubkey(), excess),
+			&[&authority],
+		)
+		.await
+		.unwrap_err();
+		assert_eq!(
+			error.unwrap(),
+			custom_error(VestingError::AdjustmentOutOfRange)
+		);
+	}
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.claimed, 0);
+}
+
+#[tokio::test]
+async fn test_amend_amount() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let state = vesting_account(&schedule_address, &mint, 1_000, 300);
+	let vesting_account = add_packed(&mut program_test, &program_id, state);
+	let source = add_ata(&mut program_test, &vesting_account, &mint, 1_000);
+	let mut context = program_test.start_with_context().await;
+
+	let amend = |authority: &Pubkey, amount: u64| {
+		instruction::amend_amount(
+			&program_id,
+			&schedule_address,
+			authority,
+			&vesting_account,
+			&source,
+			amount,
+			&spl_token::id(),
+		)
+	};
+
+	let impostor = Keypair::new();
+	let error = process(&mut context, amend(&impostor.pubkey(), 500), &[&impostor])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	// 300 has been claimed so the source can cover a total of up to 1_300
+	let error = process(
+		&mut context,
+		amend(&authority.pubkey(), 1_301),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::InsufficientVaultBalance)
+	);
+	process(
+		&mut context,
+		amend(&authority.pubkey(), 1_300),
+		&[&authority],
+	)
+	.await
+	.unwrap();
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.amount, 1_300);
+
+	process(&mut context, amend(&authority.pubkey(), 300), &[&authority])
+		.await
+		.unwrap();
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.amount, 300);
+	assert_eq!(state.claimed, 300);
+
+	let error = process(&mut context, amend(&authority.pubkey(), 299), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+}
+
+#[tokio::test]
+async fn test_amend_amount_with_vault() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let (schedule_address, bump) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	schedule.bump = bump;
+	let vault = add_ata(&mut program_test, &schedule_address, &mint, 500);
+	schedule.vault = COption::Some(vault);
+	add_packed_at(&mut program_test, &schedule_address, &program_id, schedule);
+	let state = vesting_account(&schedule_address, &mint, 1_000, 0);
+	let vesting_account = add_packed(&mut program_test, &program_id, state);
+	let mut context = program_test.start_with_context().await;
+
+	let amend = |source: &Pubkey, amount: u64| {
+		instruction::amend_amount(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&vesting_account,
+			source,
+			amount,
+			&spl_token::id(),
+		)
+	};
+
+	let wrong_source = get_associated_token_address(&vesting_account, &mint);
+	let error = process(&mut context, amend(&wrong_source, 2_000), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+	);
+
+	// The shared vault isn't held against a single account's allocation
+	process(&mut context, amend(&vault, 2_000), &[&authority])
+		.await
+		.unwrap();
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.amount, 2_000);
+}
+
+#[tokio::test]
+async fn test_sample_curve() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let state = vesting_account(&schedule_address, &mint, 1_000, 0);
+	let vesting_account = add_packed(&mut program_test, &program_id, state);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 4 * DAY).await;
+
+	let ix = instruction::sample_curve(
+		&program_id,
+		&schedule_address,
+		&vesting_account,
+		START,
+		START + 4 * DAY,
+		5,
+	);
+	let return_data = simulate_return_data(&mut context, ix).await.unwrap();
+	let curve: Vec<(i64, u64)> = return_data
+		.chunks_exact(16)
+		.map(|point| {
+			(
+				i64::from_le_bytes(point[..8].try_into().unwrap()),
+				u64::from_le_bytes(point[8..].try_into().unwrap()),
+			)
+		})
+		.collect();
+	assert_eq!(
+		curve,
+		[
+			(START, 0),
+			(START + DAY, 100),
+			(START + 2 * DAY, 200),
+			(START + 3 * DAY, 300),
+			(START + 4 * DAY, 400),
+		]
+	);
+
+	// The last point is what can be claimed now
+	let ix = instruction::get_claimable(&program_id, &schedule_address, &vesting_account);
+	let return_data = simulate_return_data(&mut context, ix).await.unwrap();
+	assert_eq!(return_data, 400u64.to_le_bytes());
+
+	let ix = instruction::sample_curve(
+		&program_id,
+		&schedule_address,
+		&vesting_account,
+		START,
+		START + 4 * DAY,
+		MAX_CURVE_POINTS + 1,
+	);
+	let error = simulate_return_data(&mut context, ix).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+	);
+}
+
+#[tokio::test]
+async fn test_amend_amount_keeps_vested() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let state = vesting_account(&schedule_address, &mint, 1_000, 0);
+	let vesting_account = add_packed(&mut program_test, &program_id, state);
+	let source = add_ata(&mut program_test, &vesting_account, &mint, 2_000);
+	let mut context = program_test.start_with_context().await;
+
+	set_clock(&mut context, START + 4 * DAY).await;
+	let ix = instruction::amend_amount(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&vesting_account,
+		&source,
+		2_000,
+		&spl_token::id(),
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.amount, 2_000);
+	assert_eq!(state.anchored_at, START + 4 * DAY);
+	assert_eq!(state.vested_at_anchor, 400);
+
+	// Only the remaining 1_600 vests over the last 6 days
+	set_clock(&mut context, START + 7 * DAY).await;
+	let ix = instruction::get_claimable(&program_id, &schedule_address, &vesting_account);
+	let return_data = simulate_return_data(&mut context, ix).await.unwrap();
+	assert_eq!(return_data, 1_200u64.to_le_bytes());
+}
+
+#[tokio::test]
+async fn test_crank_claim_to_pda_recipient() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let schedule = daily_schedule(10);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+
+	// A treasury program's PDA, off the curve so it can't sign for itself
+	let (treasury, _) = Pubkey::find_program_address(&[b"treasury"], &Pubkey::new_unique());
+	assert!(!treasury.is_on_curve());
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &treasury);
+	let mut state = self::vesting_account(&schedule_address, &mint, 1_000, 0);
+	state.owner = treasury;
+	add_packed_at(&mut program_test, &vesting_account, &program_id, state);
+	let source = add_ata(&mut program_test, &vesting_account, &mint, 1_000);
+	let treasury_ata = get_associated_token_address(&treasury, &mint);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+
+	// The treasury's ATA is created on its behalf
+	let ix = instruction::crank_claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&vesting_account,
+		&source,
+		&treasury,
+		&treasury_ata,
+		&context.payer.pubkey(),
+		&spl_token::id(),
+	);
+	process(&mut context, ix, &[]).await.unwrap();
+	assert_eq!(token_balance(&mut context, &treasury_ata).await, 300);
+	let account = context
+		.banks_client
+		.get_account(treasury_ata)
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(TokenAccount::unpack(&account.data).unwrap().owner, treasury);
+}
+
+#[tokio::test]
+async fn test_crank_claim_pays_tip() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mut schedule = daily_schedule(10);
+	schedule.tip_lamports = 5_000;
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+
+	// The schedule holds enough above its rent-exempt minimum for a single tip
+	let schedule_address = Pubkey::new_unique();
+	let mut data = vec![0; VestingSchedule::LEN];
+	VestingSchedule::pack(schedule, &mut data).unwrap();
+	let rent = Rent::default().minimum_balance(VestingSchedule::LEN);
+	program_test.add_account(
+		schedule_address,
+		SolanaAccount {
+			lamports: rent + 5_000,
+			data,
+			owner: program_id,
+			..SolanaAccount::default()
+		},
+	);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let cranker = Keypair::new();
+	program_test.add_account(
+		cranker.pubkey(),
+		SolanaAccount {
+			lamports: 1_000_000_000,
+			..SolanaAccount::default()
+		},
+	);
+	let mut context = program_test.start_with_context().await;
+	let ix = instruction::crank_claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&recipient.vesting_account,
+		&source,
+		&recipient.keypair.pubkey(),
+		&recipient.ata,
+		&cranker.pubkey(),
+		&spl_token::id(),
+	);
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+	process(&mut context, ix.clone(), &[&cranker])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 300);
+	assert_eq!(
+		lamports(&mut context, &cranker.pubkey()).await,
+		1_000_000_000 + 5_000
+	);
+	assert_eq!(lamports(&mut context, &schedule_address).await, rent);
+
+	// Once the pool is exhausted the claim still goes through without a tip
+	set_clock(&mut context, START + 5 * DAY).await;
+	process(&mut context, ix, &[&cranker]).await.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 500);
+	assert_eq!(
+		lamports(&mut context, &cranker.pubkey()).await,
+		1_000_000_000 + 5_000
+	);
+	assert_eq!(lamports(&mut context, &schedule_address).await, rent);
+}
+
+#[tokio::test]
+async fn test_amend_schedule() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	schedule.past_start_tolerance = 60 * DAY;
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 2 * DAY).await;
+
+	let amend = |authority: &Pubkey,
+	             start: Option<i64>,
+	             schedule: Option<Frequency>,
+	             duration: Option<i64>,
+	             force: bool| {
+		instruction::amend_schedule(
+			&program_id,
+			&schedule_address,
+			authority,
+			start,
+			schedule,
+			duration,
+			force,
+		)
+	};
+
+	let impostor = Keypair::new();
+	let error = process(
+		&mut context,
+		amend(&impostor.pubkey(), None, None, Some(20 * DAY), false),
+		&[&impostor],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	// Only the fields passed are amended
+	process(
+		&mut context,
+		amend(&authority.pubkey(), None, None, Some(20 * DAY), false),
+		&[&authority],
+	)
+	.await
+	.unwrap();
+	let state = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(state.start, START);
+	assert_eq!(state.frequency, Frequency::Day);
+	assert_eq!(state.duration, 20 * DAY);
+
+	// A periodic schedule can't have its duration removed
+	let error = process(
+		&mut context,
+		amend(&authority.pubkey(), None, None, Some(0), false),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+
+	// Nor amended to a duration that isn't a whole number of periods
+	let error = process(
+		&mut context,
+		amend(
+			&authority.pubkey(),
+			None,
+			Some(Frequency::Week),
+			None,
+			false,
+		),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+
+	// Moving the start back far enough to vest everything needs to be forced
+	let error = process(
+		&mut context,
+		amend(
+			&authority.pubkey(),
+			Some(START - 30 * DAY),
+			None,
+			None,
+			false,
+		),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidSchedule));
+	process(
+		&mut context,
+		amend(
+			&authority.pubkey(),
+			Some(START - 30 * DAY),
+			Some(Frequency::Week),
+			Some(21 * DAY),
+			true,
+		),
+		&[&authority],
+	)
+	.await
+	.unwrap();
+	let state = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(state.start, START - 30 * DAY);
+	assert_eq!(state.frequency, Frequency::Week);
+	assert_eq!(state.duration, 21 * DAY);
+}
+
+#[tokio::test]
+async fn test_amend_schedule_backdate_limit() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	schedule.past_start_tolerance = 5 * DAY;
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 2 * DAY).await;
+	let amend = |start: i64| {
+		instruction::amend_schedule(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			Some(start),
+			None,
+			None,
+			true,
+		)
+	};
+
+	// Forcing doesn't lift the limit, nor does scheduling the amendment for later
+	let error = process(&mut context, amend(START - 3 * DAY - 1), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::StartTooFarInPast)
+	);
+	let scheduled = instruction::schedule_amend(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		START + 3 * DAY,
+		Some(START - 2 * DAY - 1),
+		None,
+		None,
+	);
+	let error = process(&mut context, scheduled, &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::StartTooFarInPast)
+	);
+
+	process(&mut context, amend(START - 3 * DAY), &[&authority])
+		.await
+		.unwrap();
+	let state = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(state.start, START - 3 * DAY);
+
+	// Moving the start forward is never limited, even once it lies beyond the tolerance
+	set_clock(&mut context, START + 20 * DAY).await;
+	process(&mut context, amend(START - 2 * DAY), &[&authority])
+		.await
+		.unwrap();
+	let state = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(state.start, START - 2 * DAY);
+}
+
+#[tokio::test]
+async fn test_close_vesting_schedule() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+
+	// Two schedules over the same mint, only one of which has drained its vault
+	let mut add_schedule = |identifier: &str, vault_balance: u64| {
+		let (address, bump) = find_vesting_schedule_address(&program_id, &mint, identifier);
+		let vault = add_ata(&mut program_test, &address, &mint, vault_balance);
+		let mut schedule = schedule.clone();
+		schedule.identifier = hash_identifier(identifier);
+		schedule.bump = bump;
+		schedule.vault = COption::Some(vault);
+		add_packed_at(&mut program_test, &address, &program_id, schedule);
+		(address, vault)
+	};
+	let (full_schedule, full_vault) = add_schedule("full", 100);
+	let (empty_schedule, empty_vault) = add_schedule("empty", 0);
+
+	// A schedule without a vault can't show its vesting accounts are empty
+	let vaultless_schedule = add_packed(&mut program_test, &program_id, schedule.clone());
+	let vesting_account_ata = add_ata(&mut program_test, &Pubkey::new_unique(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	let close = |schedule: &Pubkey, authority: &Pubkey, vault: &Pubkey| {
+		instruction::close_vesting_schedule(
+			&program_id,
+			schedule,
+			authority,
+			vault,
+			&spl_token::id(),
+		)
+	};
+
+	let error = process(
+		&mut context,
+		close(&full_schedule, &authority.pubkey(), &full_vault),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::ScheduleNotEmpty));
+
+	// The schedule's own vault must be the one checked
+	let error = process(
+		&mut context,
+		close(&full_schedule, &authority.pubkey(), &empty_vault),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+	);
+
+	let error = process(
+		&mut context,
+		close(
+			&vaultless_schedule,
+			&authority.pubkey(),
+			&vesting_account_ata,
+		),
+		&[&authority],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::NoVault));
+
+	let impostor = Keypair::new();
+	let error = process(
+		&mut context,
+		close(&empty_schedule, &impostor.pubkey(), &empty_vault),
+		&[&impostor],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	// Both the schedule's and the vault's rent is refunded
+	let rent =
+		lamports(&mut context, &empty_schedule).await + lamports(&mut context, &empty_vault).await;
+	let receipt = process_return_data(
+		&mut context,
+		close(&empty_schedule, &authority.pubkey(), &empty_vault),
+		&[&authority],
+	)
+	.await
+	.unwrap();
+	assert_eq!(
+		CloseReceipt::unpack(&receipt),
+		Ok(CloseReceipt {
+			lamports_refunded: rent,
+			tokens_swept: 0,
+		})
+	);
+	assert_eq!(lamports(&mut context, &authority.pubkey()).await, rent);
+	for address in [empty_schedule, empty_vault] {
+		assert!(context
+			.banks_client
+			.get_account(address)
+			.await
+			.unwrap()
+			.is_none());
+	}
+}
+
+#[tokio::test]
+async fn test_close_account() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let owner = Pubkey::new_unique();
+	let (vesting_account, _) = find_vesting_account_address(&program_id, &schedule_address, &owner);
+	let mut state = self::vesting_account(&schedule_address, &mint, 1_000, 0);
+	state.owner = owner;
+	state.rent_payer = authority.pubkey();
+	add_packed_at(&mut program_test, &vesting_account, &program_id, state);
+	let source = add_ata(&mut program_test, &vesting_account, &mint, 1_000);
+	let destination = add_ata(&mut program_test, &authority.pubkey(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 5 * DAY).await;
+	let close = |authority: &Pubkey, acknowledged_forfeit: u64| {
+		instruction::close_account(
+			&program_id,
+			&schedule_address,
+			authority,
+			&mint,
+			&vesting_account,
+			&source,
+			&destination,
+			acknowledged_forfeit,
+			&spl_token::id(),
+		)
+	};
+
+	let impostor = Keypair::new();
+	let error = process(&mut context, close(&impostor.pubkey(), 500), &[&impostor])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	// Half of the allocation has vested, closing has to acknowledge exactly that
+	for acknowledged_forfeit in [0, 499, 1_000] {
+		let error = process(
+			&mut context,
+			close(&authority.pubkey(), acknowledged_forfeit),
+			&[&authority],
+		)
+		.await
+		.unwrap_err();
+		assert_eq!(
+			error.unwrap(),
+			custom_error(VestingError::ForfeitAcknowledgementMismatch)
+		);
+	}
+
+	let rent =
+		lamports(&mut context, &source).await + lamports(&mut context, &vesting_account).await;
+	let receipt = process_return_data(&mut context, close(&authority.pubkey(), 500), &[&authority])
+		.await
+		.unwrap();
+	assert_eq!(
+		CloseReceipt::unpack(&receipt),
+		Ok(CloseReceipt {
+			lamports_refunded: rent,
+			tokens_swept: 1_000,
+		})
+	);
+	assert_eq!(token_balance(&mut context, &destination).await, 1_000);
+	assert_eq!(lamports(&mut context, &authority.pubkey()).await, rent);
+	for address in [source, vesting_account] {
+		assert!(context
+			.banks_client
+			.get_account(address)
+			.await
+			.unwrap()
+			.is_none());
+	}
+}
+
+#[tokio::test]
+async fn test_forfeit() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Pubkey::new_unique();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority;
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let destination = add_ata(&mut program_test, &authority, &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	let forfeit = |owner: &Pubkey| {
+		instruction::forfeit(
+			&program_id,
+			&schedule_address,
+			owner,
+			&mint,
+			&recipient.vesting_account,
+			&source,
+			&destination,
+			&spl_token::id(),
+		)
+	};
+	let claim = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+
+	set_clock(&mut context, START + 3 * DAY).await;
+	process(&mut context, claim.clone(), &[&recipient.keypair])
+		.await
+		.unwrap();
+
+	// Only the owner may forfeit
+	set_clock(&mut context, START + 5 * DAY).await;
+	let impostor = Keypair::new();
+	let error = process(&mut context, forfeit(&impostor.pubkey()), &[&impostor])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+	);
+
+	process(
+		&mut context,
+		forfeit(&recipient.keypair.pubkey()),
+		&[&recipient.keypair],
+	)
+	.await
+	.unwrap();
+	assert_eq!(token_balance(&mut context, &destination).await, 500);
+	assert_eq!(token_balance(&mut context, &source).await, 200);
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.amount, 500);
+	assert_eq!(state.claimed, 300);
+
+	// What had vested stays claimable, and nothing vests after the forfeit
+	process(&mut context, claim.clone(), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 500);
+	set_clock(&mut context, START + 10 * DAY).await;
+	let error = process(&mut context, claim, &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::NothingToClaim));
+	let error = process(
+		&mut context,
+		forfeit(&recipient.keypair.pubkey()),
+		&[&recipient.keypair],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::NothingToForfeit));
+}
+
+#[tokio::test]
+async fn test_close_account_frozen_source() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let owner = Pubkey::new_unique();
+	let (vesting_account, _) = find_vesting_account_address(&program_id, &schedule_address, &owner);
+	let mut state = self::vesting_account(&schedule_address, &mint, 1_000, 0);
+	state.owner = owner;
+	state.rent_payer = authority.pubkey();
+	add_packed_at(&mut program_test, &vesting_account, &program_id, state);
+	let source = get_associated_token_address(&vesting_account, &mint);
+	let state = TokenAccount {
+		mint,
+		owner: vesting_account,
+		amount: 1_000,
+		state: AccountState::Frozen,
+		..TokenAccount::default()
+	};
+	add_packed_at(&mut program_test, &source, &spl_token::id(), state);
+	let destination = add_ata(&mut program_test, &authority.pubkey(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 5 * DAY).await;
+
+	// The token program's failure surfaces as the matching vesting error
+	let ix = instruction::close_account(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&mint,
+		&vesting_account,
+		&source,
+		&destination,
+		500,
+		&spl_token::id(),
+	);
+	let error = process(&mut context, ix, &[&authority]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::TokenAccountFrozen)
+	);
+}
+
+#[tokio::test]
+async fn test_close_account_bundled_with_claim() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let keypair = Keypair::new();
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &keypair.pubkey());
+	let mut state = self::vesting_account(&schedule_address, &mint, 1_000, 0);
+	state.owner = keypair.pubkey();
+	state.rent_payer = authority.pubkey();
+	add_packed_at(&mut program_test, &vesting_account, &program_id, state);
+	let recipient = Recipient {
+		ata: add_ata(&mut program_test, &keypair.pubkey(), &mint, 0),
+		keypair,
+		vesting_account,
+	};
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let destination = add_ata(&mut program_test, &authority.pubkey(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 5 * DAY).await;
+	let close = |acknowledged_forfeit: u64| {
+		instruction::close_account(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&mint,
+			&recipient.vesting_account,
+			&source,
+			&destination,
+			acknowledged_forfeit,
+			&spl_token::id(),
+		)
+	};
+	let claim = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+
+	// Closing ahead of the claim has to acknowledge the 500 vested, and the claim then fails on
+	// the closed account, so neither goes through
+	for acknowledged_forfeit in [0, 500] {
+		let blockhash = context.get_new_latest_blockhash().await.unwrap();
+		let transaction = Transaction::new_signed_with_payer(
+			&[close(acknowledged_forfeit), claim.clone()],
+			Some(&context.payer.pubkey()),
+			&[&context.payer, &authority, &recipient.keypair],
+			blockhash,
+		);
+		assert!(context
+			.banks_client
+			.process_transaction(transaction)
+			.await
+			.is_err());
+		assert_eq!(token_balance(&mut context, &source).await, 1_000);
+		assert_eq!(token_balance(&mut context, &recipient.ata).await, 0);
+	}
+
+	// Claiming first leaves nothing vested to forfeit
+	let blockhash = context.get_new_latest_blockhash().await.unwrap();
+	let transaction = Transaction::new_signed_with_payer(
+		&[claim, close(0)],
+		Some(&context.payer.pubkey()),
+		&[&context.payer, &authority, &recipient.keypair],
+		blockhash,
+	);
+	context
+		.banks_client
+		.process_transaction(transaction)
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 500);
+	assert_eq!(token_balance(&mut context, &destination).await, 500);
+}
+
+#[tokio::test]
+async fn test_close_account_refunds_rent_payer() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let owner = Pubkey::new_unique();
+	let (vesting_account, _) = find_vesting_account_address(&program_id, &schedule_address, &owner);
+	let mut state = self::vesting_account(&schedule_address, &mint, 1_000, 0);
+	state.owner = owner;
+	state.rent_payer = authority.pubkey();
+	add_packed_at(&mut program_test, &vesting_account, &program_id, state);
+	let source = add_ata(&mut program_test, &vesting_account, &mint, 1_000);
+	let destination = add_ata(&mut program_test, &authority.pubkey(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START).await;
+
+	// Only the recorded rent payer can hand the refund over
+	let rent_payer = Keypair::new();
+	let impostor = Keypair::new();
+	let ix = instruction::reassign_rent_payer(
+		&program_id,
+		&impostor.pubkey(),
+		&vesting_account,
+		&rent_payer.pubkey(),
+	);
+	let error = process(&mut context, ix, &[&impostor]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+	);
+
+	let ix = instruction::reassign_rent_payer(
+		&program_id,
+		&authority.pubkey(),
+		&vesting_account,
+		&rent_payer.pubkey(),
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.rent_payer, rent_payer.pubkey());
+
+	let close = instruction::close_account(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&mint,
+		&vesting_account,
+		&source,
+		&destination,
+		0,
+		&spl_token::id(),
+	);
+	let error = process(&mut context, close, &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::MissingRequiredAccount)
+	);
+
+	let close = |rent_payer: &Pubkey| {
+		instruction::close_account_with_rent_payer(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&mint,
+			&vesting_account,
+			&source,
+			&destination,
+			0,
+			rent_payer,
+			&spl_token::id(),
+		)
+	};
+	let error = process(&mut context, close(&impostor.pubkey()), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+	);
+
+	let rent =
+		lamports(&mut context, &source).await + lamports(&mut context, &vesting_account).await;
+	process(&mut context, close(&rent_payer.pubkey()), &[&authority])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &destination).await, 1_000);
+	assert_eq!(lamports(&mut context, &rent_payer.pubkey()).await, rent);
+	assert_eq!(lamports(&mut context, &authority.pubkey()).await, 0);
+}
+
+#[tokio::test]
+async fn test_close_account_with_vault() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let (schedule_address, bump) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	schedule.bump = bump;
+	let vault = add_ata(&mut program_test, &schedule_address, &mint, 5_000);
+	schedule.vault = COption::Some(vault);
+	add_packed_at(&mut program_test, &schedule_address, &program_id, schedule);
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &Pubkey::new_unique());
+	let mut state = self::vesting_account(&schedule_address, &mint, 1_000, 200);
+	state.rent_payer = authority.pubkey();
+	add_packed_at(&mut program_test, &vesting_account, &program_id, state);
+	let destination = add_ata(&mut program_test, &authority.pubkey(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY).await;
+
+	// Only the account's outstanding allocation leaves the vault, it stays open for others
+	let ix = instruction::close_account(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&mint,
+		&vesting_account,
+		&vault,
+		&destination,
+		100,
+		&spl_token::id(),
+	);
+	let rent = lamports(&mut context, &vesting_account).await;
+	let receipt = process_return_data(&mut context, ix, &[&authority])
+		.await
+		.unwrap();
+	assert_eq!(
+		CloseReceipt::unpack(&receipt),
+		Ok(CloseReceipt {
+			lamports_refunded: rent,
+			tokens_swept: 800,
+		})
+	);
+	assert_eq!(token_balance(&mut context, &destination).await, 800);
+	assert_eq!(token_balance(&mut context, &vault).await, 4_200);
+	assert!(context
+		.banks_client
+		.get_account(vesting_account)
+		.await
+		.unwrap()
+		.is_none());
+}
+
+/// Stub manager program tallying the amounts it's notified of in its manager account's data
+fn stub_manager(_program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+	let (discriminator, amount) = input.split_at(ON_CLAIM_DISCRIMINATOR.len());
+	assert_eq!(discriminator, ON_CLAIM_DISCRIMINATOR);
+	let amount = u64::from_le_bytes(amount.try_into().unwrap());
+	let mut data = accounts[0].data.borrow_mut();
+	let total = u64::from_le_bytes(data[..8].try_into().unwrap()) + amount;
+	data[..8].copy_from_slice(&total.to_le_bytes());
+	Ok(())
+}
+
+#[tokio::test]
+async fn test_claim_with_manager() {
+	let program_id = Pubkey::new_unique();
+	let manager_program = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	program_test.add_program("stub_manager", manager_program, processor!(stub_manager));
+	let mut schedule = daily_schedule(10);
+	schedule.manager_program = COption::Some(manager_program);
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+	let manager_account = Pubkey::new_unique();
+	program_test.add_account(
+		manager_account,
+		SolanaAccount {
+			lamports: Rent::default().minimum_balance(8),
+			data: vec![0; 8],
+			owner: manager_program,
+			..SolanaAccount::default()
+		},
+	);
+	let manager_token_account = add_ata(&mut program_test, &manager_account, &mint, 0);
+	let foreign_token_account = add_ata(&mut program_test, &Pubkey::new_unique(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	let claim = |manager_token_account: &Pubkey| {
+		instruction::claim_with_manager(
+			&program_id,
+			&schedule_address,
+			&mint,
+			&recipient.vesting_account,
+			&source,
+			&recipient.keypair.pubkey(),
+			&recipient.ata,
+			&manager_program,
+			&manager_account,
+			manager_token_account,
+			&spl_token::id(),
+		)
+	};
+
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+	let error = process(
+		&mut context,
+		claim(&foreign_token_account),
+		&[&recipient.keypair],
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+	);
+
+	process(
+		&mut context,
+		claim(&manager_token_account),
+		&[&recipient.keypair],
+	)
+	.await
+	.unwrap();
+	set_clock(&mut context, START + 5 * DAY).await;
+	process(
+		&mut context,
+		claim(&manager_token_account),
+		&[&recipient.keypair],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		token_balance(&mut context, &manager_token_account).await,
+		500
+	);
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 0);
+	let manager = context
+		.banks_client
+		.get_account(manager_account)
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(manager.data, 500u64.to_le_bytes());
+}
+
+#[tokio::test]
+async fn test_claim_token_2022() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let recipient = Keypair::new();
+	for keypair in [&authority, &recipient] {
+		program_test.add_account(
+			keypair.pubkey(),
+			SolanaAccount {
+				lamports: 1_000_000_000,
+				..SolanaAccount::default()
+			},
+		);
+	}
+	let mint = Pubkey::new_unique();
+	let state = Mint {
+		mint_authority: COption::Some(authority.pubkey()),
+		decimals: 6,
+		is_initialized: true,
+		..Mint::default()
+	};
+	add_packed_at(&mut program_test, &mint, &spl_token_2022::id(), state);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START).await;
+
+	let token_program = spl_token_2022::id();
+	let ata = |wallet: &Pubkey| {
+		get_associated_token_address_with_program_id(wallet, &mint, &token_program)
+	};
+	let (schedule_address, _) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &recipient.pubkey());
+	let vault = ata(&schedule_address);
+
+	let ix = instruction::init_vesting_schedule(
+		&program_id,
+		"daily",
+		&context.payer.pubkey(),
+		&authority.pubkey(),
+		&mint,
+		Frequency::Day,
+		START,
+		10 * DAY,
+		0,
+		COption::Some(vault),
+		false,
+		COption::None,
+		0,
+		COption::None,
+		None,
+		false,
+		DEFAULT_PAST_START_TOLERANCE,
+		0,
+		0,
+		&token_program,
+	);
+	process(&mut context, ix, &[]).await.unwrap();
+	let ix = spl_token_2022::instruction::mint_to(
+		&token_program,
+		&mint,
+		&vault,
+		&authority.pubkey(),
+		&[],
+		1_000,
+	)
+	.unwrap();
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	let ix = instruction::create_account(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&mint,
+		&vesting_account,
+		&ata(&vesting_account),
+		&recipient.pubkey(),
+		1_000,
+		0,
+		&token_program,
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+	let recipient_ata = ata(&recipient.pubkey());
+	let ix = instruction::claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&vesting_account,
+		&vault,
+		&recipient.pubkey(),
+		&recipient_ata,
+		&token_program,
+	);
+	process(&mut context, ix, &[&recipient]).await.unwrap();
+
+	let balance = |account: SolanaAccount| {
+		assert_eq!(account.owner, token_program);
+		StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+			.unwrap()
+			.base
+			.amount
+	};
+	let recipient_ata = context
+		.banks_client
+		.get_account(recipient_ata)
+		.await
+		.unwrap();
+	assert_eq!(balance(recipient_ata.unwrap()), 300);
+	let vault = context.banks_client.get_account(vault).await.unwrap();
+	assert_eq!(balance(vault.unwrap()), 700);
+}
+
+#[tokio::test]
+async fn test_claim_token_2022_transfer_fee() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let recipient = Keypair::new();
+	for keypair in [&authority, &recipient] {
+		program_test.add_account(
+			keypair.pubkey(),
+			SolanaAccount {
+				lamports: 1_000_000_000,
+				..SolanaAccount::default()
+			},
+		);
+	}
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START).await;
+
+	// 1% transfer fee
+	let token_program = spl_token_2022::id();
+	let mint_keypair = Keypair::new();
+	let mint = mint_keypair.pubkey();
+	let space =
+		ExtensionType::try_calculate_account_len::<Mint2022>(&[ExtensionType::TransferFeeConfig])
+			.unwrap();
+	let rent = context.banks_client.get_rent().await.unwrap();
+	let ix = solana_sdk::system_instruction::create_account(
+		&context.payer.pubkey(),
+		&mint,
+		rent.minimum_balance(space),
+		space as u64,
+		&token_program,
+	);
+	process(&mut context, ix, &[&mint_keypair]).await.unwrap();
+	let ix =
+		initialize_transfer_fee_config(&token_program, &mint, None, None, 100, u64::MAX).unwrap();
+	process(&mut context, ix, &[]).await.unwrap();
+	let ix = spl_token_2022::instruction::initialize_mint2(
+		&token_program,
+		&mint,
+		&authority.pubkey(),
+		None,
+		6,
+	)
+	.unwrap();
+	process(&mut context, ix, &[]).await.unwrap();
+
+	let ata = |wallet: &Pubkey| {
+		get_associated_token_address_with_program_id(wallet, &mint, &token_program)
+	};
+	let (schedule_address, _) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	let (vesting_account, _) =
+		find_vesting_account_address(&program_id, &schedule_address, &recipient.pubkey());
+	let vault = ata(&schedule_address);
+
+	let ix = instruction::init_vesting_schedule(
+		&program_id,
+		"daily",
+		&context.payer.pubkey(),
+		&authority.pubkey(),
+		&mint,
+		Frequency::Day,
+		START,
+		10 * DAY,
+		0,
+		COption::Some(vault),
+		false,
+		COption::None,
+		0,
+		COption::None,
+		None,
+		false,
+		DEFAULT_PAST_START_TOLERANCE,
+		0,
+		0,
+		&token_program,
+	);
+	process(&mut context, ix, &[]).await.unwrap();
+	let ix = spl_token_2022::instruction::mint_to(
+		&token_program,
+		&mint,
+		&vault,
+		&authority.pubkey(),
+		&[],
+		1_000,
+	)
+	.unwrap();
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	let ix = instruction::create_account(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&mint,
+		&vesting_account,
+		&ata(&vesting_account),
+		&recipient.pubkey(),
+		1_000,
+		0,
+		&token_program,
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+
+	set_clock(&mut context, START + 3 * DAY + 1).await;
+	let recipient_ata = ata(&recipient.pubkey());
+	let ix = instruction::claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&vesting_account,
+		&vault,
+		&recipient.pubkey(),
+		&recipient_ata,
+		&token_program,
+	);
+	process(&mut context, ix, &[&recipient]).await.unwrap();
+
+	// `claimed` tracks the gross amount debited from the vault, the recipient receives it net of
+	// the withheld fee
+	let account = |data: &[u8]| {
+		let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(data).unwrap();
+		let withheld = state
+			.get_extension::<TransferFeeAmount>()
+			.map_or(0, |extension| u64::from(extension.withheld_amount));
+		(state.base.amount, withheld)
+	};
+	let recipient_ata = context
+		.banks_client
+		.get_account(recipient_ata)
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(account(&recipient_ata.data), (297, 3));
+	let vault = context
+		.banks_client
+		.get_account(vault)
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(account(&vault.data).0, 700);
+	let state = vesting_account_state(&mut context, &vesting_account).await;
+	assert_eq!(state.claimed, 300);
+	assert_eq!(state.amount - state.claimed, account(&vault.data).0);
+
+	// The off-chain helper agrees with the amount received
+	let mint = context
+		.banks_client
+		.get_account(mint)
+		.await
+		.unwrap()
+		.unwrap();
+	let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+	assert_eq!(
+		calculate_net_after_fee(&mint.data, clock.epoch, 300).unwrap(),
+		297
+	);
+}
+
+/// Runs a vault-backed schedule through its whole lifecycle, checking every token and lamport
+/// ends up back with its owner
+#[tokio::test]
+async fn test_lifecycle() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	let authority = Keypair::new();
+	let recipients: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+	for keypair in recipients.iter().chain([&authority]) {
+		program_test.add_account(
+			keypair.pubkey(),
+			SolanaAccount {
+				lamports: 1_000_000_000,
+				..SolanaAccount::default()
+			},
+		);
+	}
+	let funding = add_ata(&mut program_test, &authority.pubkey(), &mint, 6_000);
+	let mut context = program_test.start_with_context().await;
+	let payer = context.payer.pubkey();
+	let (schedule_address, _) = find_vesting_schedule_address(&program_id, &mint, "lifecycle");
+	let vault = get_associated_token_address(&schedule_address, &mint);
+	let rent = lamports(&mut context, &authority.pubkey()).await;
+
+	// Each account vests over 10 days from its own creation
+	set_clock(&mut context, START).await;
+	let ix = instruction::init_vesting_schedule(
+		&program_id,
+		"lifecycle",
+		&payer,
+		&authority.pubkey(),
+		&mint,
+		Frequency::Day,
+		START,
+		10 * DAY,
+		0,
+		COption::Some(vault),
+		true,
+		COption::None,
+		0,
+		COption::None,
+		None,
+		false,
+		DEFAULT_PAST_START_TOLERANCE,
+		0,
+		0,
+		&spl_token::id(),
+	);
+	process(&mut context, ix, &[]).await.unwrap();
+	let rent = rent
+		+ lamports(&mut context, &schedule_address).await
+		+ lamports(&mut context, &vault).await;
+	let ix = spl_token::instruction::transfer(
+		&spl_token::id(),
+		&funding,
+		&vault,
+		&authority.pubkey(),
+		&[],
+		6_000,
+	)
+	.unwrap();
+	process(&mut context, ix, &[&authority]).await.unwrap();
+
+	let mut vesting_accounts = vec![];
+	for (recipient, (offset, amount)) in recipients.iter().zip([(0, 1_000), (2, 2_000), (4, 3_000)])
+	{
+		set_clock(&mut context, START + offset * DAY).await;
+		let (vesting_account, _) =
+			find_vesting_account_address(&program_id, &schedule_address, &recipient.pubkey());
+		let ix = instruction::create_account(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&mint,
+			&vesting_account,
+			&vault,
+			&recipient.pubkey(),
+			amount,
+			0,
+			&spl_token::id(),
+		);
+		process(&mut context, ix, &[&authority]).await.unwrap();
+		vesting_accounts.push(vesting_account);
+	}
+	let recipient_atas: Vec<Pubkey> = recipients
+		.iter()
+		.map(|recipient| get_associated_token_address(&recipient.pubkey(), &mint))
+		.collect();
+	let claimable = |vesting_accounts: &[Pubkey]| {
+		instruction::get_claimable_many(&program_id, &schedule_address, vesting_accounts).unwrap()
+	};
+	let claim = |index: usize| {
+		instruction::claim(
+			&program_id,
+			&schedule_address,
+			&mint,
+			&vesting_accounts[index],
+			&vault,
+			&recipients[index].pubkey(),
+			&recipient_atas[index],
+			&spl_token::id(),
+		)
+	};
+	let close = |index: usize, acknowledged_forfeit: u64| {
+		instruction::close_account(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&mint,
+			&vesting_accounts[index],
+			&vault,
+			&funding,
+			acknowledged_forfeit,
+			&spl_token::id(),
+		)
+	};
+	let to_amounts = |data: Vec<u8>| -> Vec<u64> {
+		data.chunks_exact(8)
+			.map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+			.collect()
+	};
+
+	// Partial claims
+	set_clock(&mut context, START + 5 * DAY).await;
+	let data = simulate_return_data(&mut context, claimable(&vesting_accounts))
+		.await
+		.unwrap();
+	assert_eq!(to_amounts(data), vec![500, 600, 300]);
+	for index in [0, 1] {
+		process(&mut context, claim(index), &[&recipients[index]])
+			.await
+			.unwrap();
+	}
+	assert_eq!(token_balance(&mut context, &recipient_atas[0]).await, 500);
+	assert_eq!(token_balance(&mut context, &recipient_atas[1]).await, 600);
+
+	// The third account is revoked, its vested but unclaimed tokens forfeited with the rest
+	set_clock(&mut context, START + 7 * DAY).await;
+	let data = simulate_return_data(&mut context, claimable(&vesting_accounts))
+		.await
+		.unwrap();
+	assert_eq!(to_amounts(data), vec![200, 400, 900]);
+	process(&mut context, close(2, 900), &[&authority])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &funding).await, 3_000);
+
+	// Both remaining accounts complete and are claimed in full
+	set_clock(&mut context, START + 14 * DAY).await;
+	let data = simulate_return_data(&mut context, claimable(&vesting_accounts[..2]))
+		.await
+		.unwrap();
+	assert_eq!(to_amounts(data), vec![500, 1_400]);
+	for index in [0, 1] {
+		process(&mut context, claim(index), &[&recipients[index]])
+			.await
+			.unwrap();
+	}
+	let data = simulate_return_data(&mut context, claimable(&vesting_accounts[..2]))
+		.await
+		.unwrap();
+	assert_eq!(to_amounts(data), vec![0, 0]);
+	for index in [0, 1] {
+		process(&mut context, close(index, 0), &[&authority])
+			.await
+			.unwrap();
+	}
+
+	let ix = instruction::close_vesting_schedule(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&vault,
+		&spl_token::id(),
+	);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+
+	assert_eq!(token_balance(&mut context, &recipient_atas[0]).await, 1_000);
+	assert_eq!(token_balance(&mut context, &recipient_atas[1]).await, 2_000);
+	assert_eq!(token_balance(&mut context, &funding).await, 3_000);
+	for address in vesting_accounts.iter().chain([&schedule_address, &vault]) {
+		assert!(context
+			.banks_client
+			.get_account(*address)
+			.await
+			.unwrap()
+			.is_none());
+	}
+	assert_eq!(lamports(&mut context, &authority.pubkey()).await, rent);
+}
+
+#[tokio::test]
+async fn test_claim_per_second_accrual() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let mut schedule = daily_schedule(0);
+	schedule.frequency = Frequency::Second;
+	schedule.duration = 10;
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(&mut program_test, &program_id, &schedule_address, &mint, 7);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 7);
+	let mut context = program_test.start_with_context().await;
+	let claim = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+
+	// 0.7 tokens accrue each second, released as soon as they add up to a whole token rather
+	// than flooring each second's share to nothing
+	let vested = [0, 1, 2, 2, 3, 4, 4, 5, 6, 7];
+	let mut balance = 0;
+	for (second, vested) in (1..).zip(vested) {
+		set_clock(&mut context, START + second).await;
+		let result = process(&mut context, claim.clone(), &[&recipient.keypair]).await;
+		let claimed = vested > balance;
+		match claimed {
+			true => result.unwrap(),
+			false => assert_eq!(
+				result.unwrap_err().unwrap(),
+				custom_error(VestingError::NothingToClaim)
+			),
+		}
+		balance = vested;
+		assert_eq!(token_balance(&mut context, &recipient.ata).await, vested);
+	}
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.claimed, 7);
+}
+
+#[tokio::test]
+async fn test_rotate_vault() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	add_mint(&mut program_test, &mint);
+	let (schedule_address, bump) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	schedule.bump = bump;
+	let vault = add_ata(&mut program_test, &schedule_address, &mint, 5_000);
+	schedule.vault = COption::Some(vault);
+	add_packed_at(&mut program_test, &schedule_address, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let mut context = program_test.start_with_context().await;
+	set_clock(&mut context, START + 3 * DAY).await;
+	let payer = context.payer.pubkey();
+	let rotate = |authority: &Pubkey, vault: &Pubkey| {
+		instruction::rotate_vault(
+			&program_id,
+			&schedule_address,
+			authority,
+			&payer,
+			&mint,
+			vault,
+			&spl_token::id(),
+		)
+	};
+
+	// Only the authority may rotate the vault
+	let impostor = Keypair::new();
+	let ix = rotate(&impostor.pubkey(), &vault);
+	let error = process(&mut context, ix, &[&impostor]).await.unwrap_err();
+	assert_eq!(error.unwrap(), custom_error(VestingError::InvalidAuthority));
+
+	process(
+		&mut context,
+		rotate(&authority.pubkey(), &vault),
+		&[&authority],
+	)
+	.await
+	.unwrap();
+	let (new_vault, _) = find_vault_address(&program_id, &schedule_address, &vault);
+	let schedule = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(schedule.vault, COption::Some(new_vault));
+	assert_eq!(token_balance(&mut context, &new_vault).await, 5_000);
+	let account = context
+		.banks_client
+		.get_account(new_vault)
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(
+		TokenAccount::unpack(&account.data).unwrap().owner,
+		schedule_address
+	);
+	assert!(context
+		.banks_client
+		.get_account(vault)
+		.await
+		.unwrap()
+		.is_none());
+
+	// Claims are paid from the new vault and no longer from the old one
+	let ix = claim(&program_id, &schedule_address, &mint, &vault, &recipient);
+	let error = process(&mut context, ix, &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+	);
+	let ix = claim(
+		&program_id,
+		&schedule_address,
+		&mint,
+		&new_vault,
+		&recipient,
+	);
+	process(&mut context, ix, &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 300);
+	assert_eq!(token_balance(&mut context, &new_vault).await, 4_700);
+
+	// The rotated vault can be rotated again, to a fresh address
+	let ix = rotate(&authority.pubkey(), &new_vault);
+	process(&mut context, ix, &[&authority]).await.unwrap();
+	let (newer_vault, _) = find_vault_address(&program_id, &schedule_address, &new_vault);
+	assert_ne!(newer_vault, new_vault);
+	assert_eq!(token_balance(&mut context, &newer_vault).await, 4_700);
+}
+
+#[tokio::test]
+async fn test_get_supported_instructions() {
+	let program_id = Pubkey::new_unique();
+	let program_test = program_test(&program_id);
+	let mut context = program_test.start_with_context().await;
+
+	let query = instruction::get_supported_instructions(&program_id);
+	let return_data = simulate_return_data(&mut context, query).await.unwrap();
+	assert_eq!(return_data, supported_instructions());
+	let tag = InstructionTag::GetSupportedInstructions as usize;
+	assert_ne!(return_data[tag / 8] & (1 << (tag % 8)), 0);
+}
+
+#[tokio::test]
+async fn test_migrate_mint() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	let new_mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	add_mint(&mut program_test, &new_mint);
+	let schedule_address = add_packed(&mut program_test, &program_id, schedule);
+	let recipient = add_recipient(
+		&mut program_test,
+		&program_id,
+		&schedule_address,
+		&mint,
+		1_000,
+	);
+	let source = add_ata(&mut program_test, &recipient.vesting_account, &mint, 1_000);
+
+	// The relaunched tokens are swapped in outside the program, two for every old one still to
+	// be claimed
+	let new_source = add_ata(
+		&mut program_test,
+		&recipient.vesting_account,
+		&new_mint,
+		1_399,
+	);
+	let new_ata = add_ata(&mut program_test, &recipient.keypair.pubkey(), &new_mint, 0);
+	let destination = add_ata(&mut program_test, &authority.pubkey(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+	let claim_new = instruction::claim(
+		&program_id,
+		&schedule_address,
+		&new_mint,
+		&recipient.vesting_account,
+		&new_source,
+		&recipient.keypair.pubkey(),
+		&new_ata,
+		&spl_token::id(),
+	);
+	let migrate = |ratio_num: u64, ratio_den: u64| {
+		instruction::migrate_mint(
+			&program_id,
+			&schedule_address,
+			&authority.pubkey(),
+			&new_mint,
+			ratio_num,
+			ratio_den,
+		)
+	};
+
+	set_clock(&mut context, START + 3 * DAY).await;
+	let ix = claim(&program_id, &schedule_address, &mint, &source, &recipient);
+	process(&mut context, ix, &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &recipient.ata).await, 300);
+
+	for (ratio_num, ratio_den) in [(0, 1), (2, 0)] {
+		let error = process(&mut context, migrate(ratio_num, ratio_den), &[&authority])
+			.await
+			.unwrap_err();
+		assert_eq!(
+			error.unwrap(),
+			custom_error(VestingError::InvalidMigrationRatio)
+		);
+	}
+	process(&mut context, migrate(2, 1), &[&authority])
+		.await
+		.unwrap();
+	let schedule = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(schedule.mint, new_mint);
+	assert_eq!(schedule.seed_mint, mint);
+	assert_eq!(schedule.previous_mint, COption::Some(mint));
+	assert_eq!(
+		(schedule.migration_ratio_num, schedule.migration_ratio_den),
+		(2, 1)
+	);
+
+	// A second migration would strand accounts still on the original mint
+	let error = process(&mut context, migrate(3, 1), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::ScheduleAlreadyMigrated)
+	);
+
+	// Until the account is rebound it can't be claimed from
+	set_clock(&mut context, START + 5 * DAY).await;
+	let error = process(&mut context, claim_new.clone(), &[&recipient.keypair])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::AccountNotMigrated)
+	);
+
+	let rent_payer = vesting_account_state(&mut context, &recipient.vesting_account)
+		.await
+		.rent_payer;
+	let migrate_account = instruction::migrate_pre_loaded_account_mint(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&recipient.vesting_account,
+		&mint,
+		&new_mint,
+		&destination,
+		&rent_payer,
+		&spl_token::id(),
+	);
+
+	// The new tokens have to be in place before the old ones are swept
+	let error = process(&mut context, migrate_account.clone(), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::InsufficientTokenBalance)
+	);
+	set_token_balance(&mut context, &new_source, 1_400).await;
+	process(&mut context, migrate_account.clone(), &[&authority])
+		.await
+		.unwrap();
+	let state = vesting_account_state(&mut context, &recipient.vesting_account).await;
+	assert_eq!(state.mint, new_mint);
+	assert_eq!(state.amount, 2_000);
+	assert_eq!(state.claimed, 600);
+	assert_eq!(token_balance(&mut context, &destination).await, 700);
+	assert!(context
+		.banks_client
+		.get_account(source)
+		.await
+		.unwrap()
+		.is_none());
+	let error = process(&mut context, migrate_account, &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::AccountAlreadyMigrated)
+	);
+
+	// Vesting carries on in proportion, half vested at day five
+	process(&mut context, claim_new.clone(), &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &new_ata).await, 400);
+	set_clock(&mut context, START + 10 * DAY).await;
+	process(&mut context, claim_new, &[&recipient.keypair])
+		.await
+		.unwrap();
+	assert_eq!(token_balance(&mut context, &new_ata).await, 1_400);
+	assert_eq!(token_balance(&mut context, &new_source).await, 0);
+}
+
+#[tokio::test]
+async fn test_migrate_mint_with_vault() {
+	let program_id = Pubkey::new_unique();
+	let mut program_test = program_test(&program_id);
+	let authority = Keypair::new();
+	program_test.add_account(
+		authority.pubkey(),
+		SolanaAccount {
+			lamports: 1_000_000_000,
+			..SolanaAccount::default()
+		},
+	);
+	let mut schedule = daily_schedule(10);
+	schedule.authority = authority.pubkey();
+	let mint = schedule.mint;
+	let new_mint = Pubkey::new_unique();
+	add_mint(&mut program_test, &mint);
+	add_mint(&mut program_test, &new_mint);
+	let (schedule_address, bump) = find_vesting_schedule_address(&program_id, &mint, "daily");
+	schedule.bump = bump;
+	let vault = add_ata(&mut program_test, &schedule_address, &mint, 1_000);
+	schedule.vault = COption::Some(vault);
+	add_packed_at(&mut program_test, &schedule_address, &program_id, schedule);
+
+	// The authority swaps the vault's tokens and funds the schedule's ATA of the new mint with
+	// them ahead of the migration
+	let new_vault = add_ata(&mut program_test, &schedule_address, &new_mint, 1_999);
+	let destination = add_ata(&mut program_test, &authority.pubkey(), &mint, 0);
+	let mut context = program_test.start_with_context().await;
+
+	// The vaults have to be passed to move the schedule across
+	let ix = instruction::migrate_mint(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&new_mint,
+		2,
+		1,
+	);
+	let error = process(&mut context, ix, &[&authority]).await.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::MissingRequiredAccount)
+	);
+	let migrate = instruction::migrate_mint_with_vault(
+		&program_id,
+		&schedule_address,
+		&authority.pubkey(),
+		&mint,
+		&vault,
+		&new_mint,
+		&destination,
+		2,
+		1,
+		&spl_token::id(),
+	);
+	let error = process(&mut context, migrate.clone(), &[&authority])
+		.await
+		.unwrap_err();
+	assert_eq!(
+		error.unwrap(),
+		custom_error(VestingError::InsufficientTokenBalance)
+	);
+
+	// Once funded the new vault takes over and the old one is emptied into the destination
+	set_token_balance(&mut context, &new_vault, 2_000).await;
+	process(&mut context, migrate, &[&authority]).await.unwrap();
+	let schedule = vesting_schedule_state(&mut context, &schedule_address).await;
+	assert_eq!(schedule.vault, COption::Some(new_vault));
+	assert_eq!(token_balance(&mut context, &new_vault).await, 2_000);
+	assert_eq!(token_balance(&mut context, &destination).await, 1_000);
+	assert!(context
+		.banks_client
+		.get_account(vault)
+		.await
+		.unwrap()
+		.is_none());
+}
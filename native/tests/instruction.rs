@@ -0,0 +1,1511 @@
+use {
+	fsp_vesting::{
+		error::VestingError,
+		find_vault_address, find_vesting_schedule_address, hash_identifier,
+		instruction::{
+			self, anchor_sighash, max_batch_items, supported_instructions, BatchKind, CloseReceipt,
+			InstructionTag, VestingInstruction, ANCHOR_DISCRIMINATORS, INSTRUCTION_MAGIC,
+			INSTRUCTION_PREFIX, MAX_CLAIMABLE_QUERY_ACCOUNTS, MAX_CURVE_POINTS,
+			ON_CLAIM_DISCRIMINATOR, TAG_LEN, VOUCHER_DOMAIN,
+		},
+		pad_label,
+		processor::Processor,
+		state::Frequency,
+	},
+	solana_program::{
+		instruction::{AccountMeta, Instruction},
+		message::Message,
+		program::MAX_RETURN_DATA,
+		program_error::ProgramError,
+		program_option::COption,
+		pubkey::Pubkey,
+		sysvar,
+	},
+};
+
+#[allow(deprecated)]
+use solana_program::system_program;
+
+const FREQUENCIES: [Frequency; 11] = [
+	Frequency::Once,
+	Frequency::Slot,
+	Frequency::Second,
+	Frequency::Minute,
+	Frequency::Hour,
+	Frequency::Day,
+	Frequency::Week,
+	Frequency::Month,
+	Frequency::Quarter,
+	Frequency::Year,
+	Frequency::Linear,
+];
+const I64_VALUES: [i64; 5] = [i64::MIN, -1, 0, 1_700_000_000, i64::MAX];
+const U64_VALUES: [u64; 4] = [0, 1, 1_000_000_000, u64::MAX];
+
+/// Every variant of the instruction enum, with a spread of edge-case field values
+fn all_instructions() -> Vec<VestingInstruction> {
+	let mut instructions = Vec::new();
+	for &schedule in FREQUENCIES.iter() {
+		for &start in I64_VALUES.iter() {
+			for vault in [COption::None, COption::Some(Pubkey::new_unique())] {
+				for anchor_to_creation in [false, true] {
+					instructions.push(VestingInstruction::InitVestingSchedule {
+						authority: Pubkey::new_unique(),
+						mint: Pubkey::new_unique(),
+						schedule,
+						start,
+						duration: start.wrapping_neg(),
+						cliff: start,
+						vault,
+						anchor_to_creation,
+						identifier: start.to_le_bytes(),
+						receipt_mint: vault,
+						tip_lamports: start as u64,
+						manager_program: vault,
+						quantize_start_to: anchor_to_creation.then_some(start),
+						allow_past_start: !anchor_to_creation,
+						past_start_tolerance: start,
+						pool_total: start as u64,
+						cliff_bps: start as u16,
+					});
+					instructions.push(VestingInstruction::InitByCount {
+						authority: Pubkey::new_unique(),
+						mint: Pubkey::new_unique(),
+						schedule,
+						start,
+						emissions: start as u32,
+						cliff: start,
+						vault,
+						anchor_to_creation,
+						identifier: start.to_le_bytes(),
+						receipt_mint: vault,
+						tip_lamports: start as u64,
+						manager_program: vault,
+						quantize_start_to: anchor_to_creation.then_some(start),
+						allow_past_start: !anchor_to_creation,
+						past_start_tolerance: start,
+						pool_total: start as u64,
+						cliff_bps: start as u16,
+					});
+				}
+			}
+		}
+	}
+	for &amount in U64_VALUES.iter() {
+		instructions.push(VestingInstruction::CreateAccount {
+			owner: Pubkey::new_unique(),
+			amount,
+			immediate_bps: amount as u16,
+			allow_post_completion: amount % 2 == 0,
+		});
+		instructions.push(VestingInstruction::AmendAmount { amount });
+		instructions.push(VestingInstruction::CloseAccount {
+			acknowledged_forfeit: amount,
+		});
+	}
+	let i64_options = I64_VALUES.iter().copied().map(Some).chain([None]);
+	for start in i64_options.clone() {
+		for schedule in FREQUENCIES.iter().copied().map(Some).chain([None]) {
+			for duration in i64_options.clone() {
+				instructions.push(VestingInstruction::AmendSchedule {
+					start,
+					schedule,
+					duration,
+					force: duration.is_none(),
+				});
+			}
+		}
+	}
+	for expected_start in i64_options.clone() {
+		instructions.push(VestingInstruction::Claim { expected_start });
+	}
+	instructions.push(VestingInstruction::CloseVestingSchedule);
+	instructions.push(VestingInstruction::GetClaimableMany);
+	for &effective_at in I64_VALUES.iter() {
+		for schedule in [None, Some(Frequency::Day)] {
+			instructions.push(VestingInstruction::ScheduleAmend {
+				effective_at,
+				start: schedule.map(|_| effective_at),
+				schedule,
+				duration: Some(effective_at.wrapping_neg()),
+			});
+		}
+	}
+	instructions.push(VestingInstruction::CrankClaim);
+	instructions.push(VestingInstruction::GetClaimable);
+	instructions.push(VestingInstruction::GetUiContext);
+	instructions.push(VestingInstruction::LockAccount);
+	instructions.push(VestingInstruction::UnlockAccount);
+	instructions.push(VestingInstruction::RenounceAmendAuthority);
+	for &amount in U64_VALUES.iter() {
+		instructions.push(VestingInstruction::RedeemVoucher {
+			amount,
+			expiry: amount as i64,
+			signature: [amount as u8; 64],
+		});
+	}
+	for &start_ts in I64_VALUES.iter() {
+		for points in [0, 1, u16::MAX] {
+			instructions.push(VestingInstruction::SampleCurve {
+				start_ts,
+				end_ts: start_ts.wrapping_add(1),
+				points,
+			});
+		}
+	}
+	for allowed_destination in [COption::None, COption::Some(Pubkey::new_unique())] {
+		instructions.push(VestingInstruction::SetAllowedDestination {
+			allowed_destination,
+		});
+	}
+	instructions.push(VestingInstruction::ClaimTo);
+	instructions.push(VestingInstruction::ClaimAndUnwrap);
+	instructions.push(VestingInstruction::ClaimFallback);
+	instructions.push(VestingInstruction::Forfeit);
+	instructions.push(VestingInstruction::RotateVault);
+	instructions.push(VestingInstruction::GetSupportedInstructions);
+	for (ratio_num, ratio_den) in [(1, 1), (2, 1), (0, u64::MAX)] {
+		instructions.push(VestingInstruction::MigrateMint {
+			new_mint: Pubkey::new_unique(),
+			ratio_num,
+			ratio_den,
+		});
+	}
+	instructions.push(VestingInstruction::MigrateAccountMint);
+	instructions.push(VestingInstruction::PauseAccount);
+	instructions.push(VestingInstruction::UnpauseAccount);
+	for &inactivity_seconds in I64_VALUES.iter() {
+		for fallback_owner in [COption::None, COption::Some(Pubkey::new_unique())] {
+			instructions.push(VestingInstruction::SetFallback {
+				fallback_owner,
+				inactivity_seconds,
+			});
+		}
+	}
+	instructions.push(VestingInstruction::SetLabel {
+		label: pad_label("seed round").unwrap(),
+	});
+	instructions.push(VestingInstruction::SetLabel { label: [0; 32] });
+	for &delta in I64_VALUES.iter() {
+		instructions.push(VestingInstruction::AdjustClaimed { delta });
+	}
+	instructions.push(VestingInstruction::ReassignRentPayer {
+		new_payer: Pubkey::new_unique(),
+	});
+	for bps in [0, 1, 10_000, u16::MAX] {
+		instructions.push(VestingInstruction::CreateAccountByBps {
+			owner: Pubkey::new_unique(),
+			bps,
+		});
+	}
+	for (max_claim_per_window, window_seconds) in [(0, 0), (1, 1), (u64::MAX, i64::MIN)] {
+		instructions.push(VestingInstruction::SetClaimWindow {
+			max_claim_per_window,
+			window_seconds,
+		});
+	}
+	instructions
+}
+
+#[test]
+fn test_pack_unpack_round_trip() {
+	for instruction in all_instructions() {
+		let packed = instruction.pack();
+		let unpacked = VestingInstruction::unpack(&packed).unwrap();
+		assert_eq!(unpacked, instruction);
+	}
+}
+
+#[test]
+fn test_instruction_magic() {
+	let packed = VestingInstruction::GetClaimable.pack();
+	assert!(packed.starts_with(INSTRUCTION_PREFIX));
+	assert_eq!(packed.len(), INSTRUCTION_PREFIX.len() + TAG_LEN);
+	assert_eq!(
+		VestingInstruction::unpack(&packed),
+		Ok(VestingInstruction::GetClaimable)
+	);
+
+	// Data for a fork with different magic, or none, isn't read as ours
+	if cfg!(feature = "instruction-magic") {
+		let mut forked = packed.clone();
+		forked[0] ^= 0xff;
+		assert_eq!(
+			VestingInstruction::unpack(&forked),
+			Err(ProgramError::from(VestingError::InvalidInstruction))
+		);
+		assert!(VestingInstruction::unpack(&packed[INSTRUCTION_MAGIC.len()..]).is_err());
+	}
+}
+
+#[test]
+fn test_unpack_rejects_trailing_bytes() {
+	for instruction in all_instructions() {
+		let mut packed = instruction.pack();
+		packed.push(0);
+		assert_eq!(
+			VestingInstruction::unpack(&packed),
+			Err(ProgramError::from(VestingError::InvalidInstruction))
+		);
+	}
+}
+
+#[test]
+fn test_unpack_rejects_truncated_data() {
+	for instruction in all_instructions() {
+		let packed = instruction.pack();
+		for len in 0..packed.len() {
+			assert!(VestingInstruction::unpack(&packed[..len]).is_err());
+		}
+	}
+}
+
+#[test]
+fn test_anchor_discriminators() {
+	assert_eq!(
+		anchor_sighash("claim"),
+		[62, 198, 214, 193, 213, 159, 108, 210]
+	);
+	for (name, discriminator) in ANCHOR_DISCRIMINATORS {
+		assert_eq!(anchor_sighash(name), discriminator, "{name}");
+	}
+
+	for (tag, (name, _)) in ANCHOR_DISCRIMINATORS.iter().enumerate() {
+		let tag = InstructionTag::try_from(tag as u8).unwrap();
+		assert_eq!(name.replace('_', ""), format!("{tag:?}").to_lowercase());
+	}
+
+	// Discriminators of other programs' instructions aren't read as ours
+	if cfg!(feature = "anchor-compat") {
+		let unknown = [INSTRUCTION_PREFIX, &anchor_sighash("initialize")].concat();
+		assert_eq!(
+			VestingInstruction::unpack(&unknown),
+			Err(ProgramError::from(VestingError::InvalidInstruction))
+		);
+	}
+}
+
+#[test]
+fn test_instruction_tags() {
+	let mut tagged = [false; ANCHOR_DISCRIMINATORS.len()];
+	for instruction in all_instructions() {
+		// The tag is named after its variant, as the processor logs it
+		let tag = instruction.tag();
+		let debug = format!("{instruction:?}");
+		assert_eq!(debug.split([' ', '(']).next(), Some(&*format!("{tag:?}")));
+		tagged[tag as usize] = true;
+
+		// Packing writes the tag and unpacking reads the same one back
+		let packed = instruction.pack();
+		let expected = if cfg!(feature = "anchor-compat") {
+			ANCHOR_DISCRIMINATORS[tag as usize].1.to_vec()
+		} else {
+			vec![tag as u8]
+		};
+		let offset = INSTRUCTION_PREFIX.len();
+		assert_eq!(packed[offset..offset + TAG_LEN], expected);
+		assert_eq!(VestingInstruction::unpack(&packed).unwrap().tag(), tag);
+	}
+
+	// Tags are numbered without gaps and every one of them is in use
+	assert!(tagged.iter().all(|&tagged| tagged));
+	for tag in 0..=u8::MAX {
+		assert_eq!(
+			InstructionTag::try_from(tag).is_ok(),
+			usize::from(tag) < ANCHOR_DISCRIMINATORS.len()
+		);
+	}
+}
+
+#[test]
+fn test_supported_instructions() {
+	let mask = supported_instructions();
+	let supported = |tag: u8| mask[usize::from(tag / 8)] & (1 << (tag % 8)) != 0;
+	for tag in 0..=u8::MAX {
+		assert_eq!(supported(tag), InstructionTag::try_from(tag).is_ok());
+	}
+
+	// Every supported instruction reaches a handler in the processor, failing on its missing
+	// accounts rather than being turned away as unknown
+	let program_id = Pubkey::new_unique();
+	let mut dispatched = [false; ANCHOR_DISCRIMINATORS.len()];
+	for instruction in all_instructions() {
+		let tag = instruction.tag();
+		if std::mem::replace(&mut dispatched[tag as usize], true) {
+			continue;
+		}
+		let result = Processor::process(&program_id, &[], &instruction.pack());
+		assert_ne!(
+			result,
+			Err(VestingError::InvalidInstruction.into()),
+			"{tag:?}"
+		);
+		assert!(supported(tag as u8));
+	}
+	assert!(dispatched.iter().all(|&dispatched| dispatched));
+}
+
+#[test]
+fn test_close_receipt() {
+	let receipt = CloseReceipt {
+		lamports_refunded: 2_039_280,
+		tokens_swept: 500,
+	};
+	let packed = receipt.pack();
+	assert_eq!(packed[..8], 2_039_280u64.to_le_bytes());
+	assert_eq!(packed[8..], 500u64.to_le_bytes());
+	assert_eq!(CloseReceipt::unpack(&packed), Ok(receipt));
+	assert!(CloseReceipt::unpack(&packed[..CloseReceipt::LEN - 1]).is_err());
+	assert!(CloseReceipt::unpack(&[packed.as_slice(), &[0]].concat()).is_err());
+}
+
+#[test]
+fn test_unpack_rejects_invalid_data() {
+	let tagged = |tag: u8| [INSTRUCTION_PREFIX, &[tag]].concat();
+	assert!(VestingInstruction::unpack(&[]).is_err());
+	assert!(VestingInstruction::unpack(&tagged(35)).is_err());
+	assert!(VestingInstruction::unpack(&tagged(u8::MAX)).is_err());
+	let offset = INSTRUCTION_PREFIX.len() + TAG_LEN;
+
+	// Unknown frequency
+	let mut packed = VestingInstruction::AmendSchedule {
+		start: None,
+		schedule: Some(Frequency::Year),
+		duration: None,
+		force: false,
+	}
+	.pack();
+	packed[offset + 2] = 11;
+	assert!(VestingInstruction::unpack(&packed).is_err());
+
+	// Invalid option tag
+	packed[offset + 1] = 2;
+	assert!(VestingInstruction::unpack(&packed).is_err());
+
+	// Label that isn't UTF-8
+	let mut packed = VestingInstruction::SetLabel {
+		label: pad_label("seed round").unwrap(),
+	}
+	.pack();
+	packed[offset] = 0xff;
+	assert!(VestingInstruction::unpack(&packed).is_err());
+}
+
+/// Asserts the instruction targets `program_id`, carries `data` and lists exactly `accounts`
+fn assert_instruction(
+	instruction: &Instruction,
+	program_id: &Pubkey,
+	data: VestingInstruction,
+	accounts: &[AccountMeta],
+) {
+	assert_eq!(instruction.program_id, *program_id);
+	assert_eq!(instruction.data, data.pack());
+	assert_eq!(instruction.accounts, accounts);
+}
+
+#[test]
+fn test_init_vesting_schedule_builder() {
+	let program_id = Pubkey::new_unique();
+	let payer = Pubkey::new_unique();
+	let authority = Pubkey::new_unique();
+	let mint = Pubkey::new_unique();
+	let vault = Pubkey::new_unique();
+	let (vesting_schedule, _) = find_vesting_schedule_address(&program_id, &mint, "seed-round");
+
+	for vault in [COption::None, COption::Some(vault)] {
+		let ix = instruction::init_vesting_schedule(
+			&program_id,
+			"seed-round",
+			&payer,
+			&authority,
+			&mint,
+			Frequency::Day,
+			1_700_000_000,
+			86_400,
+			3_600,
+			vault,
+			false,
+			COption::None,
+			5_000,
+			COption::None,
+			Some(3_600),
+			true,
+			60,
+			1_000_000,
+			2_500,
+			&spl_token::id(),
+		);
+		let mut accounts = vec![
+			AccountMeta::new(vesting_schedule, false),
+			AccountMeta::new(payer, true),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(mint, false),
+		];
+		if let COption::Some(vault) = vault {
+			accounts.extend([
+				AccountMeta::new(vault, false),
+				AccountMeta::new_readonly(spl_token::id(), false),
+				AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			]);
+		}
+		assert_instruction(
+			&ix,
+			&program_id,
+			VestingInstruction::InitVestingSchedule {
+				authority,
+				mint,
+				schedule: Frequency::Day,
+				start: 1_700_000_000,
+				duration: 86_400,
+				cliff: 3_600,
+				vault,
+				anchor_to_creation: false,
+				identifier: hash_identifier("seed-round"),
+				receipt_mint: COption::None,
+				tip_lamports: 5_000,
+				manager_program: COption::None,
+				quantize_start_to: Some(3_600),
+				allow_past_start: true,
+				past_start_tolerance: 60,
+				pool_total: 1_000_000,
+				cliff_bps: 2_500,
+			},
+			&accounts,
+		);
+
+		let ix = instruction::init_by_count(
+			&program_id,
+			"seed-round",
+			&payer,
+			&authority,
+			&mint,
+			Frequency::Month,
+			1_700_000_000,
+			36,
+			3_600,
+			vault,
+			false,
+			COption::None,
+			5_000,
+			COption::None,
+			Some(3_600),
+			true,
+			60,
+			1_000_000,
+			2_500,
+			&spl_token::id(),
+		);
+		assert_instruction(
+			&ix,
+			&program_id,
+			VestingInstruction::InitByCount {
+				authority,
+				mint,
+				schedule: Frequency::Month,
+				start: 1_700_000_000,
+				emissions: 36,
+				cliff: 3_600,
+				vault,
+				anchor_to_creation: false,
+				identifier: hash_identifier("seed-round"),
+				receipt_mint: COption::None,
+				tip_lamports: 5_000,
+				manager_program: COption::None,
+				quantize_start_to: Some(3_600),
+				allow_past_start: true,
+				past_start_tolerance: 60,
+				pool_total: 1_000_000,
+				cliff_bps: 2_500,
+			},
+			&accounts,
+		);
+	}
+}
+
+#[test]
+fn test_account_builders() {
+	let program_id = Pubkey::new_unique();
+	let vesting_schedule = Pubkey::new_unique();
+	let authority = Pubkey::new_unique();
+	let mint = Pubkey::new_unique();
+	let vesting_account = Pubkey::new_unique();
+	let vesting_account_ata = Pubkey::new_unique();
+	let recipient = Pubkey::new_unique();
+	let recipient_ata = Pubkey::new_unique();
+
+	assert_instruction(
+		&instruction::create_account(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&recipient,
+			1_000,
+			2_000,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::CreateAccount {
+			owner: recipient,
+			amount: 1_000,
+			immediate_bps: 2_000,
+			allow_post_completion: false,
+		},
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new(authority, true),
+			AccountMeta::new_readonly(mint, false),
+			AccountMeta::new(vesting_account, false),
+			AccountMeta::new(vesting_account_ata, false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+		],
+	);
+
+	let ix = instruction::create_account_after_completion(
+		&program_id,
+		&vesting_schedule,
+		&authority,
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&recipient,
+		1_000,
+		2_000,
+		&spl_token::id(),
+	);
+	assert_eq!(
+		VestingInstruction::unpack(&ix.data),
+		Ok(VestingInstruction::CreateAccount {
+			owner: recipient,
+			amount: 1_000,
+			immediate_bps: 2_000,
+			allow_post_completion: true,
+		})
+	);
+	assert_eq!(
+		ix.accounts,
+		instruction::create_account(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&recipient,
+			1_000,
+			2_000,
+			&spl_token::id(),
+		)
+		.accounts
+	);
+
+	assert_instruction(
+		&instruction::create_account_by_bps(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&recipient,
+			2_500,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::CreateAccountByBps {
+			owner: recipient,
+			bps: 2_500,
+		},
+		&[
+			AccountMeta::new(vesting_schedule, false),
+			AccountMeta::new(authority, true),
+			AccountMeta::new_readonly(mint, false),
+			AccountMeta::new(vesting_account, false),
+			AccountMeta::new(vesting_account_ata, false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+		],
+	);
+
+	assert_instruction(
+		&instruction::amend_amount(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&vesting_account,
+			&vesting_account_ata,
+			500,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::AmendAmount { amount: 500 },
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new(authority, true),
+			AccountMeta::new(vesting_account, false),
+			AccountMeta::new_readonly(vesting_account_ata, false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+		],
+	);
+
+	assert_instruction(
+		&instruction::claim(
+			&program_id,
+			&vesting_schedule,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&recipient,
+			&recipient_ata,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::Claim {
+			expected_start: None,
+		},
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new_readonly(mint, false),
+			AccountMeta::new(vesting_account, false),
+			AccountMeta::new(vesting_account_ata, false),
+			AccountMeta::new(recipient, true),
+			AccountMeta::new(recipient_ata, false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+		],
+	);
+
+	let destination = Pubkey::new_unique();
+	assert_instruction(
+		&instruction::claim_to(
+			&program_id,
+			&vesting_schedule,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&recipient,
+			&destination,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::ClaimTo,
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new_readonly(mint, false),
+			AccountMeta::new(vesting_account, false),
+			AccountMeta::new(vesting_account_ata, false),
+			AccountMeta::new(recipient, true),
+			AccountMeta::new(destination, false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+		],
+	);
+
+	let ix = instruction::claim_expecting_start(
+		&program_id,
+		&vesting_schedule,
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&recipient,
+		&recipient_ata,
+		1_700_000_000,
+		&spl_token::id(),
+	);
+	assert_eq!(
+		ix.data,
+		VestingInstruction::Claim {
+			expected_start: Some(1_700_000_000),
+		}
+		.pack()
+	);
+	assert_eq!(
+		ix.accounts,
+		instruction::claim(
+			&program_id,
+			&vesting_schedule,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&recipient,
+			&recipient_ata,
+			&spl_token::id(),
+		)
+		.accounts
+	);
+
+	let fallback_owner = Pubkey::new_unique();
+	let ix = instruction::claim_fallback(
+		&program_id,
+		&vesting_schedule,
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&fallback_owner,
+		&recipient_ata,
+		&spl_token::id(),
+	);
+	assert_eq!(ix.data, VestingInstruction::ClaimFallback.pack());
+	assert_eq!(
+		ix.accounts,
+		instruction::claim(
+			&program_id,
+			&vesting_schedule,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&fallback_owner,
+			&recipient_ata,
+			&spl_token::id(),
+		)
+		.accounts
+	);
+
+	let ix = instruction::claim_and_unwrap(
+		&program_id,
+		&vesting_schedule,
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&recipient,
+		&recipient_ata,
+		&spl_token::id(),
+	);
+	assert_eq!(ix.data, VestingInstruction::ClaimAndUnwrap.pack());
+	assert_eq!(
+		ix.accounts,
+		instruction::claim(
+			&program_id,
+			&vesting_schedule,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&recipient,
+			&recipient_ata,
+			&spl_token::id(),
+		)
+		.accounts
+	);
+
+	let receipt_mint = Pubkey::new_unique();
+	let ix = instruction::claim_with_receipt(
+		&program_id,
+		&vesting_schedule,
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&recipient,
+		&recipient_ata,
+		&receipt_mint,
+		&spl_token::id(),
+	);
+	assert_eq!(
+		ix.data,
+		VestingInstruction::Claim {
+			expected_start: None
+		}
+		.pack()
+	);
+	assert_eq!(ix.accounts.len(), 11);
+	assert_eq!(ix.accounts[9], AccountMeta::new(receipt_mint, false));
+	assert_eq!(
+		ix.accounts[10],
+		AccountMeta::new(
+			spl_associated_token_account::get_associated_token_address(&recipient, &receipt_mint),
+			false
+		)
+	);
+
+	let manager_program = Pubkey::new_unique();
+	let manager_account = Pubkey::new_unique();
+	let manager_token_account = Pubkey::new_unique();
+	let ix = instruction::claim_with_manager(
+		&program_id,
+		&vesting_schedule,
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&recipient,
+		&recipient_ata,
+		&manager_program,
+		&manager_account,
+		&manager_token_account,
+		&spl_token::id(),
+	);
+	assert_eq!(
+		ix.data,
+		VestingInstruction::Claim {
+			expected_start: None
+		}
+		.pack()
+	);
+	assert_eq!(
+		ix.accounts[9..],
+		[
+			AccountMeta::new_readonly(manager_program, false),
+			AccountMeta::new(manager_account, false),
+			AccountMeta::new(manager_token_account, false),
+		]
+	);
+
+	let ix = instruction::on_claim(
+		&manager_program,
+		&manager_account,
+		&manager_token_account,
+		&vesting_account,
+		&recipient,
+		300,
+	);
+	assert_eq!(ix.program_id, manager_program);
+	assert_eq!(ix.data[..8], ON_CLAIM_DISCRIMINATOR);
+	assert_eq!(ix.data[8..], 300u64.to_le_bytes());
+	assert_eq!(
+		ix.accounts,
+		[
+			AccountMeta::new(manager_account, false),
+			AccountMeta::new(manager_token_account, false),
+			AccountMeta::new_readonly(vesting_account, false),
+			AccountMeta::new_readonly(recipient, false),
+		]
+	);
+
+	let cranker = Pubkey::new_unique();
+	assert_instruction(
+		&instruction::crank_claim(
+			&program_id,
+			&vesting_schedule,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&recipient,
+			&recipient_ata,
+			&cranker,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::CrankClaim,
+		&[
+			AccountMeta::new(vesting_schedule, false),
+			AccountMeta::new_readonly(mint, false),
+			AccountMeta::new(vesting_account, false),
+			AccountMeta::new(vesting_account_ata, false),
+			AccountMeta::new_readonly(recipient, false),
+			AccountMeta::new(recipient_ata, false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new(cranker, true),
+		],
+	);
+
+	let destination = Pubkey::new_unique();
+	assert_instruction(
+		&instruction::close_account(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&destination,
+			250,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::CloseAccount {
+			acknowledged_forfeit: 250,
+		},
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new(authority, true),
+			AccountMeta::new_readonly(mint, false),
+			AccountMeta::new(vesting_account, false),
+			AccountMeta::new(vesting_account_ata, false),
+			AccountMeta::new(destination, false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+		],
+	);
+
+	assert_instruction(
+		&instruction::forfeit(
+			&program_id,
+			&vesting_schedule,
+			&recipient,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			&destination,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::Forfeit,
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new_readonly(recipient, true),
+			AccountMeta::new_readonly(mint, false),
+			AccountMeta::new(vesting_account, false),
+			AccountMeta::new(vesting_account_ata, false),
+			AccountMeta::new(destination, false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+		],
+	);
+
+	assert_instruction(
+		&instruction::adjust_claimed(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&vesting_account,
+			-250,
+		),
+		&program_id,
+		VestingInstruction::AdjustClaimed { delta: -250 },
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new_readonly(authority, true),
+			AccountMeta::new(vesting_account, false),
+		],
+	);
+
+	assert_instruction(
+		&instruction::redeem_voucher(
+			&program_id,
+			&vesting_schedule,
+			&recipient,
+			&mint,
+			&vesting_account,
+			&vesting_account_ata,
+			1_000,
+			1_700_000_000,
+			[7; 64],
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::RedeemVoucher {
+			amount: 1_000,
+			expiry: 1_700_000_000,
+			signature: [7; 64],
+		},
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new(recipient, true),
+			AccountMeta::new_readonly(mint, false),
+			AccountMeta::new(vesting_account, false),
+			AccountMeta::new(vesting_account_ata, false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new_readonly(sysvar::instructions::id(), false),
+		],
+	);
+	let message = instruction::voucher_message(&vesting_schedule, &recipient, 1_000, 1);
+	assert!(message.starts_with(VOUCHER_DOMAIN));
+	assert_eq!(message.len(), VOUCHER_DOMAIN.len() + 32 + 32 + 8 + 8);
+
+	let accounts = [
+		AccountMeta::new_readonly(vesting_schedule, false),
+		AccountMeta::new_readonly(authority, true),
+		AccountMeta::new(vesting_account, false),
+	];
+	assert_instruction(
+		&instruction::lock_account(&program_id, &vesting_schedule, &authority, &vesting_account),
+		&program_id,
+		VestingInstruction::LockAccount,
+		&accounts,
+	);
+	assert_instruction(
+		&instruction::unlock_account(&program_id, &vesting_schedule, &authority, &vesting_account),
+		&program_id,
+		VestingInstruction::UnlockAccount,
+		&accounts,
+	);
+	assert_instruction(
+		&instruction::pause_account(&program_id, &vesting_schedule, &authority, &vesting_account),
+		&program_id,
+		VestingInstruction::PauseAccount,
+		&accounts,
+	);
+	assert_instruction(
+		&instruction::unpause_account(&program_id, &vesting_schedule, &authority, &vesting_account),
+		&program_id,
+		VestingInstruction::UnpauseAccount,
+		&accounts,
+	);
+
+	assert_instruction(
+		&instruction::set_claim_window(&program_id, &recipient, &vesting_account, 250, 86_400),
+		&program_id,
+		VestingInstruction::SetClaimWindow {
+			max_claim_per_window: 250,
+			window_seconds: 86_400,
+		},
+		&[
+			AccountMeta::new_readonly(recipient, true),
+			AccountMeta::new(vesting_account, false),
+		],
+	);
+
+	assert_instruction(
+		&instruction::set_fallback(
+			&program_id,
+			&recipient,
+			&vesting_account,
+			COption::Some(fallback_owner),
+			365 * 86_400,
+		),
+		&program_id,
+		VestingInstruction::SetFallback {
+			fallback_owner: COption::Some(fallback_owner),
+			inactivity_seconds: 365 * 86_400,
+		},
+		&[
+			AccountMeta::new_readonly(recipient, true),
+			AccountMeta::new(vesting_account, false),
+		],
+	);
+
+	let rent_payer = Pubkey::new_unique();
+	let ix = instruction::close_account_with_rent_payer(
+		&program_id,
+		&vesting_schedule,
+		&authority,
+		&mint,
+		&vesting_account,
+		&vesting_account_ata,
+		&destination,
+		0,
+		&rent_payer,
+		&spl_token::id(),
+	);
+	assert_eq!(ix.accounts.len(), 8);
+	assert_eq!(ix.accounts[7], AccountMeta::new(rent_payer, false));
+
+	assert_instruction(
+		&instruction::reassign_rent_payer(&program_id, &authority, &vesting_account, &recipient),
+		&program_id,
+		VestingInstruction::ReassignRentPayer {
+			new_payer: recipient,
+		},
+		&[
+			AccountMeta::new_readonly(authority, true),
+			AccountMeta::new(vesting_account, false),
+		],
+	);
+
+	assert_instruction(
+		&instruction::set_allowed_destination(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&vesting_account,
+			COption::Some(destination),
+		),
+		&program_id,
+		VestingInstruction::SetAllowedDestination {
+			allowed_destination: COption::Some(destination),
+		},
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new_readonly(authority, true),
+			AccountMeta::new(vesting_account, false),
+		],
+	);
+}
+
+#[test]
+fn test_schedule_builders() {
+	let program_id = Pubkey::new_unique();
+	let vesting_schedule = Pubkey::new_unique();
+	let authority = Pubkey::new_unique();
+	let vault = Pubkey::new_unique();
+
+	// The new vault's address is derived from the one it replaces
+	let payer = Pubkey::new_unique();
+	let mint = Pubkey::new_unique();
+	let (new_vault, _) = find_vault_address(&program_id, &vesting_schedule, &vault);
+	assert_ne!(
+		find_vault_address(&program_id, &vesting_schedule, &new_vault).0,
+		new_vault
+	);
+	assert_instruction(
+		&instruction::rotate_vault(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&payer,
+			&mint,
+			&vault,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::RotateVault,
+		&[
+			AccountMeta::new(vesting_schedule, false),
+			AccountMeta::new(authority, true),
+			AccountMeta::new(payer, true),
+			AccountMeta::new_readonly(mint, false),
+			AccountMeta::new(vault, false),
+			AccountMeta::new(new_vault, false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+		],
+	);
+
+	assert_instruction(
+		&instruction::amend_schedule(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			Some(1_700_000_000),
+			None,
+			Some(86_400),
+			true,
+		),
+		&program_id,
+		VestingInstruction::AmendSchedule {
+			start: Some(1_700_000_000),
+			schedule: None,
+			duration: Some(86_400),
+			force: true,
+		},
+		&[
+			AccountMeta::new(vesting_schedule, false),
+			AccountMeta::new(authority, true),
+		],
+	);
+
+	assert_instruction(
+		&instruction::schedule_amend(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			1_800_000_000,
+			None,
+			Some(Frequency::Week),
+			None,
+		),
+		&program_id,
+		VestingInstruction::ScheduleAmend {
+			effective_at: 1_800_000_000,
+			start: None,
+			schedule: Some(Frequency::Week),
+			duration: None,
+		},
+		&[
+			AccountMeta::new(vesting_schedule, false),
+			AccountMeta::new_readonly(authority, true),
+		],
+	);
+
+	assert_instruction(
+		&instruction::close_vesting_schedule(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&vault,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::CloseVestingSchedule,
+		&[
+			AccountMeta::new(vesting_schedule, false),
+			AccountMeta::new(authority, true),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new(vault, false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+		],
+	);
+
+	assert_instruction(
+		&instruction::set_label(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			pad_label("seed round").unwrap(),
+		),
+		&program_id,
+		VestingInstruction::SetLabel {
+			label: pad_label("seed round").unwrap(),
+		},
+		&[
+			AccountMeta::new(vesting_schedule, false),
+			AccountMeta::new_readonly(authority, true),
+		],
+	);
+
+	assert_instruction(
+		&instruction::renounce_amend_authority(&program_id, &vesting_schedule, &authority),
+		&program_id,
+		VestingInstruction::RenounceAmendAuthority,
+		&[
+			AccountMeta::new(vesting_schedule, false),
+			AccountMeta::new_readonly(authority, true),
+		],
+	);
+}
+
+#[test]
+fn test_get_claimable_builder() {
+	let program_id = Pubkey::new_unique();
+	let vesting_schedule = Pubkey::new_unique();
+	let vesting_account = Pubkey::new_unique();
+
+	assert_instruction(
+		&instruction::get_claimable(&program_id, &vesting_schedule, &vesting_account),
+		&program_id,
+		VestingInstruction::GetClaimable,
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new_readonly(vesting_account, false),
+		],
+	);
+}
+
+#[test]
+fn test_sample_curve_builder() {
+	let program_id = Pubkey::new_unique();
+	let vesting_schedule = Pubkey::new_unique();
+	let vesting_account = Pubkey::new_unique();
+
+	assert_instruction(
+		&instruction::sample_curve(
+			&program_id,
+			&vesting_schedule,
+			&vesting_account,
+			1_700_000_000,
+			1_800_000_000,
+			MAX_CURVE_POINTS,
+		),
+		&program_id,
+		VestingInstruction::SampleCurve {
+			start_ts: 1_700_000_000,
+			end_ts: 1_800_000_000,
+			points: MAX_CURVE_POINTS,
+		},
+		&[
+			AccountMeta::new_readonly(vesting_schedule, false),
+			AccountMeta::new_readonly(vesting_account, false),
+		],
+	);
+	assert_eq!(usize::from(MAX_CURVE_POINTS) * 16, MAX_RETURN_DATA);
+}
+
+#[test]
+fn test_get_ui_context_builder() {
+	let program_id = Pubkey::new_unique();
+	let vesting_schedule = Pubkey::new_unique();
+
+	assert_instruction(
+		&instruction::get_ui_context(&program_id, &vesting_schedule),
+		&program_id,
+		VestingInstruction::GetUiContext,
+		&[AccountMeta::new_readonly(vesting_schedule, false)],
+	);
+}
+
+#[test]
+fn test_migrate_builders() {
+	let program_id = Pubkey::new_unique();
+	let vesting_schedule = Pubkey::new_unique();
+	let authority = Pubkey::new_unique();
+	let mint = Pubkey::new_unique();
+	let vault = Pubkey::new_unique();
+	let new_mint = Pubkey::new_unique();
+	let destination = Pubkey::new_unique();
+	let vesting_account = Pubkey::new_unique();
+	let rent_payer = Pubkey::new_unique();
+	let migrate_mint = VestingInstruction::MigrateMint {
+		new_mint,
+		ratio_num: 2,
+		ratio_den: 1,
+	};
+
+	// Without a vault the token accounts and programs are omitted
+	let accounts = [
+		AccountMeta::new(vesting_schedule, false),
+		AccountMeta::new(authority, true),
+		AccountMeta::new_readonly(new_mint, false),
+	];
+	assert_instruction(
+		&instruction::migrate_mint(&program_id, &vesting_schedule, &authority, &new_mint, 2, 1),
+		&program_id,
+		migrate_mint.clone(),
+		&accounts,
+	);
+	assert_instruction(
+		&instruction::migrate_mint_with_vault(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&mint,
+			&vault,
+			&new_mint,
+			&destination,
+			2,
+			1,
+			&spl_token::id(),
+		),
+		&program_id,
+		migrate_mint,
+		&[
+			&accounts[..],
+			&[
+				AccountMeta::new(
+					spl_associated_token_account::get_associated_token_address(
+						&vesting_schedule,
+						&new_mint,
+					),
+					false,
+				),
+				AccountMeta::new(vault, false),
+				AccountMeta::new_readonly(mint, false),
+				AccountMeta::new(destination, false),
+				AccountMeta::new_readonly(system_program::id(), false),
+				AccountMeta::new_readonly(spl_token::id(), false),
+				AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			],
+		]
+		.concat(),
+	);
+
+	let accounts = [
+		AccountMeta::new_readonly(vesting_schedule, false),
+		AccountMeta::new_readonly(authority, true),
+		AccountMeta::new(vesting_account, false),
+	];
+	assert_instruction(
+		&instruction::migrate_account_mint(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&vesting_account,
+		),
+		&program_id,
+		VestingInstruction::MigrateAccountMint,
+		&accounts,
+	);
+	assert_instruction(
+		&instruction::migrate_pre_loaded_account_mint(
+			&program_id,
+			&vesting_schedule,
+			&authority,
+			&vesting_account,
+			&mint,
+			&new_mint,
+			&destination,
+			&rent_payer,
+			&spl_token::id(),
+		),
+		&program_id,
+		VestingInstruction::MigrateAccountMint,
+		&[
+			&accounts[..],
+			&[
+				AccountMeta::new(
+					spl_associated_token_account::get_associated_token_address(
+						&vesting_account,
+						&mint,
+					),
+					false,
+				),
+				AccountMeta::new_readonly(mint, false),
+				AccountMeta::new_readonly(
+					spl_associated_token_account::get_associated_token_address(
+						&vesting_account,
+						&new_mint,
+					),
+					false,
+				),
+				AccountMeta::new(destination, false),
+				AccountMeta::new(rent_payer, false),
+				AccountMeta::new_readonly(spl_token::id(), false),
+			],
+		]
+		.concat(),
+	);
+}
+
+#[test]
+fn test_get_supported_instructions_builder() {
+	let program_id = Pubkey::new_unique();
+
+	assert_instruction(
+		&instruction::get_supported_instructions(&program_id),
+		&program_id,
+		VestingInstruction::GetSupportedInstructions,
+		&[],
+	);
+}
+
+#[test]
+fn test_get_claimable_many_builder() {
+	let program_id = Pubkey::new_unique();
+	let vesting_schedule = Pubkey::new_unique();
+	let vesting_accounts: Vec<Pubkey> = (0..MAX_CLAIMABLE_QUERY_ACCOUNTS + 1)
+		.map(|_| Pubkey::new_unique())
+		.collect();
+
+	let ix = instruction::get_claimable_many(
+		&program_id,
+		&vesting_schedule,
+		&vesting_accounts[..MAX_CLAIMABLE_QUERY_ACCOUNTS],
+	)
+	.unwrap();
+	let mut accounts = vec![AccountMeta::new_readonly(vesting_schedule, false)];
+	accounts.extend(
+		vesting_accounts[..MAX_CLAIMABLE_QUERY_ACCOUNTS]
+			.iter()
+			.map(|key| AccountMeta::new_readonly(*key, false)),
+	);
+	assert_instruction(
+		&ix,
+		&program_id,
+		VestingInstruction::GetClaimableMany,
+		&accounts,
+	);
+
+	assert_eq!(
+		instruction::get_claimable_many(&program_id, &vesting_schedule, &vesting_accounts),
+		Err(ProgramError::from(VestingError::TooManyAccounts))
+	);
+}
+
+#[test]
+fn test_max_batch_items() {
+	let program_id = Pubkey::new_unique();
+	let vesting_schedule = Pubkey::new_unique();
+	let payer = Pubkey::new_unique();
+	let max_items = max_batch_items(BatchKind::GetClaimableMany, false);
+	assert_eq!(
+		max_items,
+		if INSTRUCTION_PREFIX.len() + TAG_LEN > 1 {
+			30
+		} else {
+			31
+		}
+	);
+	assert_eq!(
+		max_batch_items(BatchKind::GetClaimableMany, true),
+		max_items
+	);
+
+	// A transaction of one signature and the message, which only just fits at the estimate
+	let transaction_len = |items: usize| {
+		let vesting_accounts: Vec<Pubkey> = (0..items).map(|_| Pubkey::new_unique()).collect();
+		let ix = instruction::get_claimable_many(&program_id, &vesting_schedule, &vesting_accounts)
+			.unwrap();
+		1 + 64 + Message::new(&[ix], Some(&payer)).serialize().len()
+	};
+	assert!(transaction_len(max_items) <= 1232);
+	// The estimate allows for the widest length prefixes, which magic bytes or long tags tip over
+	if INSTRUCTION_PREFIX.len() + TAG_LEN == 1 {
+		assert!(transaction_len(max_items + 1) > 1232);
+	}
+}
@@ -0,0 +1,214 @@
+//! Pins the exact on-chain byte layout of every state struct
+
+use {
+	fsp_vesting::{
+		pad_label,
+		state::{Account, AccountType, Frequency, PendingAmendment, VestingSchedule},
+	},
+	solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey},
+};
+
+fn pack<T: Pack>(state: T) -> Vec<u8> {
+	let mut data = vec![0; T::LEN];
+	T::pack(state, &mut data).unwrap();
+	data
+}
+
+#[test]
+fn test_vesting_schedule_layout() {
+	let authority = Pubkey::new_from_array([1; 32]);
+	let mint = Pubkey::new_from_array([2; 32]);
+	let vault = Pubkey::new_from_array([3; 32]);
+	let receipt_mint = Pubkey::new_from_array([4; 32]);
+	let manager_program = Pubkey::new_from_array([5; 32]);
+	let seed_mint = Pubkey::new_from_array([6; 32]);
+	let data = pack(VestingSchedule {
+		is_initialized: true,
+		authority,
+		mint,
+		frequency: Frequency::Month,
+		start: 0x0102_0304_0506_0708,
+		duration: -2,
+		vault: COption::Some(vault),
+		anchor_to_creation: true,
+		identifier: [4, 5, 6, 7, 8, 9, 10, 11],
+		cliff: 0x0a0b_0c0d,
+		pending_amendment: Some(PendingAmendment {
+			effective_at: 0x0a0b_0c0d,
+			start: Some(-1),
+			frequency: Some(Frequency::Week),
+			duration: None,
+		}),
+		receipt_mint: COption::Some(receipt_mint),
+		tip_lamports: 0x1122_3344,
+		manager_program: COption::Some(manager_program),
+		bump: 254,
+		past_start_tolerance: 3_600,
+		pool_total: 0x0102_0304,
+		allocated_bps: 0x0506,
+		label: pad_label("seed round").unwrap(),
+		decimals: 6,
+		immutable: true,
+		seed_mint,
+		previous_mint: COption::Some(receipt_mint),
+		migration_ratio_num: 0x0708,
+		migration_ratio_den: 0x090a,
+		cliff_bps: 2_500,
+	});
+
+	assert_eq!(VestingSchedule::LEN, 383);
+	assert_eq!(data.len(), VestingSchedule::LEN);
+	assert_eq!(data[0], u8::from(AccountType::VestingSchedule));
+	assert_eq!(&data[1..33], authority.as_ref());
+	assert_eq!(&data[33..65], mint.as_ref());
+	assert_eq!(data[65], Frequency::Month as u8);
+	assert_eq!(&data[66..74], &[8, 7, 6, 5, 4, 3, 2, 1]);
+	assert_eq!(
+		&data[74..82],
+		&[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+	);
+	assert_eq!(&data[82..86], &[1, 0, 0, 0]);
+	assert_eq!(&data[86..118], vault.as_ref());
+	assert_eq!(data[118], 1);
+	assert_eq!(&data[119..127], &[4, 5, 6, 7, 8, 9, 10, 11]);
+	assert_eq!(&data[127..135], &[0x0d, 0x0c, 0x0b, 0x0a, 0, 0, 0, 0]);
+	assert_eq!(data[135], 1);
+	assert_eq!(&data[136..144], &[0x0d, 0x0c, 0x0b, 0x0a, 0, 0, 0, 0]);
+	assert_eq!(data[144], 1);
+	assert_eq!(&data[145..153], &[0xff; 8]);
+	assert_eq!(&data[153..155], &[1, Frequency::Week as u8]);
+	assert_eq!(&data[155..164], &[0; 9]);
+	assert_eq!(&data[164..168], &[1, 0, 0, 0]);
+	assert_eq!(&data[168..200], receipt_mint.as_ref());
+	assert_eq!(&data[200..208], &[0x44, 0x33, 0x22, 0x11, 0, 0, 0, 0]);
+	assert_eq!(&data[208..212], &[1, 0, 0, 0]);
+	assert_eq!(&data[212..244], manager_program.as_ref());
+	assert_eq!(data[244], 254);
+	assert_eq!(&data[245..253], &[0x10, 0x0e, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(&data[253..261], &[4, 3, 2, 1, 0, 0, 0, 0]);
+	assert_eq!(&data[261..263], &[6, 5]);
+	assert_eq!(&data[263..273], b"seed round");
+	assert_eq!(&data[273..295], &[b' '; 22]);
+	assert_eq!(data[295], 6);
+	assert_eq!(data[296], 1);
+	assert_eq!(&data[297..329], seed_mint.as_ref());
+	assert_eq!(&data[329..333], &[1, 0, 0, 0]);
+	assert_eq!(&data[333..365], receipt_mint.as_ref());
+	assert_eq!(&data[365..373], &[8, 7, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(&data[373..381], &[0x0a, 9, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(&data[381..383], &[0xc4, 0x09]);
+
+	let data = pack(VestingSchedule {
+		is_initialized: false,
+		authority,
+		mint,
+		frequency: Frequency::Once,
+		start: 0,
+		duration: 0,
+		vault: COption::None,
+		anchor_to_creation: false,
+		identifier: [0; 8],
+		cliff: 0,
+		pending_amendment: None,
+		receipt_mint: COption::None,
+		tip_lamports: 0,
+		manager_program: COption::None,
+		bump: 0,
+		past_start_tolerance: 0,
+		pool_total: 0,
+		allocated_bps: 0,
+		label: [b' '; 32],
+		decimals: 0,
+		immutable: false,
+		seed_mint: mint,
+		previous_mint: COption::None,
+		migration_ratio_num: 0,
+		migration_ratio_den: 0,
+		cliff_bps: 0,
+	});
+	assert_eq!(data[0], u8::from(AccountType::Uninitialized));
+	assert_eq!(data[65], 0);
+	assert_eq!(&data[82..118], &[0; 36]);
+	assert_eq!(data[118], 0);
+	assert_eq!(&data[119..127], &[0; 8]);
+	assert_eq!(&data[127..135], &[0; 8]);
+	assert_eq!(&data[135..164], &[0; 29]);
+	assert_eq!(&data[164..200], &[0; 36]);
+	assert_eq!(&data[200..208], &[0; 8]);
+	assert_eq!(&data[208..244], &[0; 36]);
+	assert_eq!(data[244], 0);
+	assert_eq!(data[296], 0);
+	assert_eq!(&data[329..365], &[0; 36]);
+	assert_eq!(&data[381..383], &[0; 2]);
+}
+
+#[test]
+fn test_account_layout() {
+	let vesting_schedule = Pubkey::new_from_array([1; 32]);
+	let owner = Pubkey::new_from_array([2; 32]);
+	let mint = Pubkey::new_from_array([3; 32]);
+	let allowed_destination = Pubkey::new_from_array([4; 32]);
+	let rent_payer = Pubkey::new_from_array([5; 32]);
+	let fallback_owner = Pubkey::new_from_array([6; 32]);
+	let data = pack(Account {
+		is_initialized: true,
+		vesting_schedule,
+		owner,
+		mint,
+		amount: 0x1112_1314_1516_1718,
+		claimed: u64::MAX,
+		created_at: 1,
+		allowed_destination: COption::Some(allowed_destination),
+		rent_payer,
+		immediate_bps: 0x0102,
+		anchored_at: -2,
+		vested_at_anchor: 0x0304,
+		max_claim_per_window: 0x0506,
+		window_seconds: 0x0708,
+		window_start: -3,
+		claimed_in_window: 0x090a,
+		locked: true,
+		fallback_owner: COption::Some(fallback_owner),
+		inactivity_seconds: 0x0b0c,
+		last_claim: -4,
+		claims_paused: true,
+	});
+
+	assert_eq!(Account::LEN, 293);
+	assert_eq!(data.len(), Account::LEN);
+	assert_eq!(data[0], u8::from(AccountType::Account));
+	assert_eq!(&data[1..33], vesting_schedule.as_ref());
+	assert_eq!(&data[33..65], owner.as_ref());
+	assert_eq!(&data[65..97], mint.as_ref());
+	assert_eq!(
+		&data[97..105],
+		&[0x18, 0x17, 0x16, 0x15, 0x14, 0x13, 0x12, 0x11]
+	);
+	assert_eq!(&data[105..113], &[0xff; 8]);
+	assert_eq!(&data[113..121], &[1, 0, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(&data[121..125], &[1, 0, 0, 0]);
+	assert_eq!(&data[125..157], allowed_destination.as_ref());
+	assert_eq!(&data[157..189], rent_payer.as_ref());
+	assert_eq!(&data[189..191], &[2, 1]);
+	assert_eq!(
+		&data[191..199],
+		&[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+	);
+	assert_eq!(&data[199..207], &[4, 3, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(&data[207..215], &[6, 5, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(&data[215..223], &[8, 7, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(
+		&data[223..231],
+		&[0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+	);
+	assert_eq!(&data[231..239], &[0x0a, 9, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(data[239], 1);
+	assert_eq!(&data[240..244], &[1, 0, 0, 0]);
+	assert_eq!(&data[244..276], fallback_owner.as_ref());
+	assert_eq!(&data[276..284], &[0x0c, 0x0b, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(
+		&data[284..292],
+		&[0xfc, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+	);
+	assert_eq!(data[292], 1);
+}
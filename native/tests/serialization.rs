@@ -0,0 +1,162 @@
+#![cfg(feature = "serde")]
+
+use {
+	fsp_vesting::{
+		hash_identifier,
+		state::{claim_preview_json, Account, Frequency, VestingSchedule},
+	},
+	serde_json::{json, Value},
+	solana_program::{
+		program_error::ProgramError, program_option::COption, program_pack::Pack, pubkey::Pubkey,
+	},
+};
+
+fn test_schedule(vault: COption<Pubkey>) -> VestingSchedule {
+	let mint = Pubkey::new_unique();
+	VestingSchedule {
+		is_initialized: true,
+		authority: Pubkey::new_unique(),
+		mint,
+		frequency: Frequency::Month,
+		start: 1_700_000_000,
+		duration: 31_556_952,
+		vault,
+		anchor_to_creation: false,
+		identifier: hash_identifier("serde"),
+		cliff: 0,
+		pending_amendment: None,
+		receipt_mint: COption::None,
+		tip_lamports: 0,
+		manager_program: COption::None,
+		bump: 0,
+		past_start_tolerance: 0,
+		pool_total: 0,
+		allocated_bps: 0,
+		label: [b' '; 32],
+		decimals: 0,
+		immutable: false,
+		seed_mint: mint,
+		previous_mint: COption::None,
+		migration_ratio_num: 0,
+		migration_ratio_den: 0,
+		cliff_bps: 0,
+	}
+}
+
+#[test]
+fn test_vault_none_round_trip() {
+	let schedule = test_schedule(COption::None);
+	let value = serde_json::to_value(&schedule).unwrap();
+	assert_eq!(value["vault"], Value::Null);
+	assert_eq!(value["authority"], json!(schedule.authority.to_string()));
+
+	let decoded: VestingSchedule = serde_json::from_value(value).unwrap();
+	assert_eq!(decoded.vault, COption::None);
+	assert_eq!(decoded.authority, schedule.authority);
+	assert_eq!(decoded.frequency, Frequency::Month);
+}
+
+#[test]
+fn test_vault_some_round_trip() {
+	let vault = Pubkey::new_unique();
+	let schedule = test_schedule(COption::Some(vault));
+	let json = serde_json::to_string(&schedule).unwrap();
+	let value: Value = serde_json::from_str(&json).unwrap();
+	assert_eq!(value["vault"], json!(vault.to_string()));
+
+	let decoded: VestingSchedule = serde_json::from_str(&json).unwrap();
+	assert_eq!(decoded.vault, COption::Some(vault));
+	assert_eq!(decoded.mint, schedule.mint);
+	assert_eq!(decoded.identifier, schedule.identifier);
+}
+
+#[test]
+fn test_vault_invalid_base58_rejected() {
+	let mut value = serde_json::to_value(test_schedule(COption::None)).unwrap();
+	value["vault"] = json!("not-a-pubkey");
+	assert!(serde_json::from_value::<VestingSchedule>(value).is_err());
+}
+
+#[test]
+fn test_claim_preview_json() {
+	const DAY: i64 = 86_400;
+	let schedule = VestingSchedule {
+		frequency: Frequency::Day,
+		duration: 10 * DAY,
+		..test_schedule(COption::None)
+	};
+	let start = schedule.start;
+	let mut schedule_bytes = vec![0; VestingSchedule::LEN];
+	VestingSchedule::pack(schedule, &mut schedule_bytes).unwrap();
+	let account = Account {
+		is_initialized: true,
+		vesting_schedule: Pubkey::new_unique(),
+		owner: Pubkey::new_unique(),
+		mint: Pubkey::new_unique(),
+		amount: 1_000,
+		claimed: 100,
+		created_at: start,
+		allowed_destination: COption::None,
+		rent_payer: Pubkey::new_unique(),
+		immediate_bps: 0,
+		anchored_at: 0,
+		vested_at_anchor: 0,
+		max_claim_per_window: 0,
+		window_seconds: 0,
+		window_start: 0,
+		claimed_in_window: 0,
+		locked: false,
+		fallback_owner: COption::None,
+		inactivity_seconds: 0,
+		last_claim: 0,
+		claims_paused: false,
+	};
+	let preview = |account: &Account, now: i64| {
+		let mut account_bytes = vec![0; Account::LEN];
+		Account::pack(account.clone(), &mut account_bytes).unwrap();
+		let json = claim_preview_json(&schedule_bytes, &account_bytes, now).unwrap();
+		serde_json::from_str::<Value>(&json).unwrap()
+	};
+
+	assert_eq!(
+		preview(&account, start + 3 * DAY + 5),
+		json!({
+			"claimable": 200,
+			"vested_bps": 3_000,
+			"next_unlock": start + 4 * DAY,
+			"phase": "Vesting",
+		})
+	);
+	assert_eq!(
+		preview(&account, start - 10),
+		json!({
+			"claimable": 0,
+			"vested_bps": 0,
+			"next_unlock": start + DAY,
+			"phase": "NotStarted",
+		})
+	);
+	assert_eq!(
+		preview(&account, start + 10 * DAY),
+		json!({
+			"claimable": 900,
+			"vested_bps": 10_000,
+			"next_unlock": null,
+			"phase": "Completed",
+		})
+	);
+
+	// An immediate share unlocks at the start itself
+	let immediate = Account {
+		immediate_bps: 2_000,
+		..account.clone()
+	};
+	assert_eq!(preview(&immediate, start - 10)["next_unlock"], json!(start));
+
+	// Buffers of the wrong length or uninitialised are rejected
+	assert!(claim_preview_json(&schedule_bytes, &schedule_bytes, start).is_err());
+	assert_eq!(
+		claim_preview_json(&schedule_bytes, &[0; Account::LEN], start),
+		Err(ProgramError::UninitializedAccount)
+	);
+}
@@ -0,0 +1,1519 @@
+use {
+	fsp_vesting::{
+		error::VestingError,
+		hash_identifier,
+		state::{
+			amend_impact, audit_consistency, calculate_net_after_fee, claimable_display,
+			completing_within, group_vested, liability_by_mint, quantize_start, sample_curve,
+			solvency_threshold, summarize_portfolio, total_liability, transfer_fee,
+			unpack_accounts, Account, AccountType, AmendImpact, ConsistencyError, Frequency,
+			PendingAmendment, Phase, VestingSchedule, MAX_BPS, SLOT_MILLISECONDS,
+		},
+	},
+	solana_program::{
+		program_error::ProgramError, program_option::COption, program_pack::Pack, pubkey::Pubkey,
+	},
+	spl_token_2022::state::Mint,
+};
+
+fn test_schedule() -> VestingSchedule {
+	let mint = Pubkey::new_unique();
+	VestingSchedule {
+		is_initialized: true,
+		authority: Pubkey::new_unique(),
+		mint,
+		frequency: Frequency::Month,
+		start: 1_700_000_000,
+		duration: 31_556_952,
+		vault: COption::None,
+		anchor_to_creation: false,
+		identifier: hash_identifier("test"),
+		cliff: 0,
+		pending_amendment: None,
+		receipt_mint: COption::None,
+		tip_lamports: 0,
+		manager_program: COption::None,
+		bump: 0,
+		past_start_tolerance: 0,
+		pool_total: 0,
+		allocated_bps: 0,
+		label: [b' '; 32],
+		decimals: 0,
+		immutable: false,
+		seed_mint: mint,
+		previous_mint: COption::None,
+		migration_ratio_num: 0,
+		migration_ratio_den: 0,
+		cliff_bps: 0,
+	}
+}
+
+fn test_account(amount: u64, claimed: u64) -> Account {
+	Account {
+		is_initialized: true,
+		vesting_schedule: Pubkey::new_unique(),
+		owner: Pubkey::new_unique(),
+		mint: Pubkey::new_unique(),
+		amount,
+		claimed,
+		created_at: 1_700_000_000,
+		allowed_destination: COption::None,
+		rent_payer: Pubkey::new_unique(),
+		immediate_bps: 0,
+		anchored_at: 0,
+		vested_at_anchor: 0,
+		max_claim_per_window: 0,
+		window_seconds: 0,
+		window_start: 0,
+		claimed_in_window: 0,
+		locked: false,
+		fallback_owner: COption::None,
+		inactivity_seconds: 0,
+		last_claim: 0,
+		claims_paused: false,
+	}
+}
+
+fn packed(account: &Account) -> Vec<u8> {
+	let mut data = vec![0; Account::LEN];
+	Account::pack(account.clone(), &mut data).unwrap();
+	data
+}
+
+fn packed_schedule(schedule: &VestingSchedule) -> Vec<u8> {
+	let mut data = vec![0; VestingSchedule::LEN];
+	VestingSchedule::pack(schedule.clone(), &mut data).unwrap();
+	data
+}
+
+#[test]
+fn test_unpack_accounts() {
+	let first = test_account(1_000, 0);
+	let second = test_account(500, 250);
+	let keys: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+	let raw = vec![
+		(keys[0], packed(&first)),
+		(keys[1], vec![0; Account::LEN]),
+		(keys[2], packed(&second)),
+		(keys[3], vec![2; Account::LEN - 1]),
+		(keys[4], vec![]),
+	];
+
+	let unpacked = unpack_accounts(&raw);
+	assert_eq!(unpacked.len(), raw.len());
+	for ((key, _), (unpacked_key, _)) in raw.iter().zip(unpacked.iter()) {
+		assert_eq!(key, unpacked_key);
+	}
+
+	let account = unpacked[0].1.as_ref().unwrap();
+	assert_eq!(account.owner, first.owner);
+	assert_eq!(account.amount, 1_000);
+	assert_eq!(
+		unpacked[1].1.as_ref().unwrap_err(),
+		&ProgramError::UninitializedAccount
+	);
+	let account = unpacked[2].1.as_ref().unwrap();
+	assert_eq!(account.owner, second.owner);
+	assert_eq!(account.claimed, 250);
+	assert_eq!(
+		unpacked[3].1.as_ref().unwrap_err(),
+		&ProgramError::InvalidAccountData
+	);
+	assert_eq!(
+		unpacked[4].1.as_ref().unwrap_err(),
+		&ProgramError::InvalidAccountData
+	);
+}
+
+#[test]
+fn test_audit_consistency() {
+	let schedule = test_schedule();
+	let mut consistent = test_account(1_000, 400);
+	consistent.mint = schedule.mint;
+	assert!(audit_consistency(&schedule, &[consistent.clone()]).is_empty());
+
+	let mut over_claimed = consistent.clone();
+	over_claimed.claimed = 1_001;
+	let wrong_mint = test_account(1_000, 0);
+	let mut uninitialized = consistent.clone();
+	uninitialized.is_initialized = false;
+	let mut huge = consistent.clone();
+	huge.amount = u64::MAX;
+	huge.claimed = 0;
+
+	let errors = audit_consistency(
+		&schedule,
+		&[consistent, over_claimed, wrong_mint, uninitialized, huge],
+	);
+	assert_eq!(
+		errors,
+		vec![
+			ConsistencyError::ClaimedExceedsAmount(1),
+			ConsistencyError::MintMismatch(2),
+			ConsistencyError::AccountNotInitialized(3),
+			ConsistencyError::TotalAllocationOverflow,
+		]
+	);
+
+	let mut uninitialized_schedule = schedule;
+	uninitialized_schedule.is_initialized = false;
+	assert_eq!(
+		audit_consistency(&uninitialized_schedule, &[]),
+		vec![ConsistencyError::ScheduleNotInitialized]
+	);
+}
+
+#[test]
+fn test_total_liability() {
+	let first = test_schedule();
+	let second = test_schedule();
+	let mut shared_mint = test_schedule();
+	shared_mint.mint = first.mint;
+
+	let portfolio = vec![
+		(
+			first.clone(),
+			vec![test_account(1_000, 250), test_account(500, 500)],
+		),
+		(second.clone(), vec![test_account(2_000, 0)]),
+		(shared_mint, vec![test_account(300, 100)]),
+		(test_schedule(), vec![]),
+	];
+	assert_eq!(total_liability(&portfolio), Ok(2_950));
+	assert_eq!(total_liability(&[]), Ok(0));
+
+	let by_mint = liability_by_mint(&portfolio).unwrap();
+	assert_eq!(by_mint.len(), 3);
+	assert_eq!(by_mint[0], (first.mint, 950));
+	assert_eq!(by_mint[1], (second.mint, 2_000));
+	assert_eq!(by_mint[2].1, 0);
+
+	let overflowing = vec![
+		(first, vec![test_account(u64::MAX, 0)]),
+		(second, vec![test_account(1, 0)]),
+	];
+	assert_eq!(
+		total_liability(&overflowing),
+		Err(VestingError::Overflow.into())
+	);
+	assert_eq!(
+		liability_by_mint(&overflowing).map(|totals| totals.len()),
+		Ok(2)
+	);
+}
+
+#[test]
+fn test_group_vested() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	let accounts = [
+		test_account(1_000, 400),
+		test_account(2_500, 0),
+		test_account(7, 7),
+	];
+	let start = schedule.start;
+
+	assert_eq!(
+		group_vested(&schedule, &accounts, start - 1),
+		Ok(vec![0, 0, 0])
+	);
+	assert_eq!(
+		group_vested(&schedule, &accounts, start + 3 * 86_400),
+		Ok(vec![300, 750, 2])
+	);
+	assert_eq!(
+		group_vested(&schedule, &accounts, start + 10 * 86_400),
+		Ok(vec![1_000, 2_500, 7])
+	);
+	assert_eq!(group_vested(&schedule, &[], start), Ok(vec![]));
+}
+
+#[test]
+fn test_account_accrual() {
+	// 0.7 tokens a second
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Second;
+	schedule.duration = 10;
+	let account = test_account(7, 0);
+	let start = schedule.start;
+
+	let mut released = 0;
+	for second in 0..=10 {
+		let vested = schedule.account_vested(&account, start + second).unwrap();
+		let (numerator, denominator) = schedule.account_accrual(&account, start + second).unwrap();
+		assert_eq!(vested, 7 * second as u64 / 10);
+		if numerator != 0 {
+			assert_eq!((numerator, denominator), (7 * second as u64 % 10, 10));
+		}
+		assert!(vested >= released);
+		released = vested;
+	}
+	assert_eq!(released, 7);
+	assert_eq!(schedule.account_accrual(&account, start - 1), Ok((0, 0)));
+	assert_eq!(schedule.account_accrual(&account, start + 10), Ok((0, 0)));
+
+	// Linear schedules accrue over seconds of the duration
+	schedule.frequency = Frequency::Linear;
+	schedule.duration = 4;
+	assert_eq!(schedule.account_accrual(&account, start + 1), Ok((3, 4)));
+	assert_eq!(schedule.account_vested(&account, start + 1), Ok(1));
+}
+
+#[test]
+fn test_migrate_account() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	let mut account = test_account(1_001, 301);
+	account.mint = schedule.mint;
+	account.max_claim_per_window = 100;
+	let old = account.clone();
+	let now = schedule.start + 3 * 86_400;
+
+	// Nothing to migrate until the schedule moves to a new mint
+	assert_eq!(
+		schedule.migrate_account(&mut account),
+		Err(VestingError::AccountAlreadyMigrated.into())
+	);
+	let old_mint = schedule.mint;
+	schedule.previous_mint = COption::Some(old_mint);
+	schedule.mint = Pubkey::new_unique();
+	schedule.migration_ratio_num = 2;
+	schedule.migration_ratio_den = 1;
+
+	// Two new tokens for every old one, vesting in proportion
+	schedule.migrate_account(&mut account).unwrap();
+	assert_eq!(account.mint, schedule.mint);
+	assert_eq!(account.amount, 2_002);
+	assert_eq!(account.claimed, 602);
+	assert_eq!(account.max_claim_per_window, 200);
+	assert_eq!(
+		schedule.account_claimable(&account, now),
+		Ok(2 * schedule.account_claimable(&old, now).unwrap())
+	);
+	assert_eq!(
+		schedule.migrate_account(&mut account),
+		Err(VestingError::AccountAlreadyMigrated.into())
+	);
+
+	// Amounts round down and what was claimed rounds up, so no more remains than the 700
+	// unclaimed tokens convert to, and must fit the new amounts
+	let mut halved = old.clone();
+	schedule.migration_ratio_num = 1;
+	schedule.migration_ratio_den = 2;
+	schedule.migrate_account(&mut halved).unwrap();
+	assert_eq!((halved.amount, halved.claimed), (500, 151));
+	assert_eq!(schedule.migrated_amount(700), Ok(350));
+	let mut overflowing = test_account(u64::MAX, 0);
+	overflowing.mint = old_mint;
+	schedule.migration_ratio_num = 2;
+	schedule.migration_ratio_den = 1;
+	assert_eq!(
+		schedule.migrate_account(&mut overflowing),
+		Err(VestingError::Overflow.into())
+	);
+
+	// Accounts on some other mint can't migrate
+	let mut stranger = test_account(1_000, 0);
+	assert_eq!(
+		schedule.migrate_account(&mut stranger),
+		Err(VestingError::InvalidMint.into())
+	);
+}
+
+#[test]
+fn test_solvency_threshold() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	let accounts = [
+		test_account(1_000, 400),
+		test_account(2_500, 0),
+		test_account(7, 7),
+	];
+	let start = schedule.start;
+	let now = start + 3 * 86_400;
+
+	// Nothing further vests within the same day, only what is already claimable is owed
+	assert_eq!(
+		solvency_threshold(&schedule, &accounts, now, 86_399),
+		Ok(750)
+	);
+	assert_eq!(
+		solvency_threshold(&schedule, &accounts, now, 2 * 86_400),
+		Ok(100 + 1_250)
+	);
+	assert_eq!(
+		solvency_threshold(&schedule, &accounts, start - 86_400, 86_400),
+		Ok(0)
+	);
+	assert_eq!(
+		solvency_threshold(&schedule, &accounts, now, 365 * 86_400),
+		Ok(600 + 2_500)
+	);
+	assert_eq!(solvency_threshold(&schedule, &[], now, 86_400), Ok(0));
+	assert_eq!(
+		solvency_threshold(&schedule, &accounts, now, -1),
+		Err(ProgramError::InvalidArgument)
+	);
+	let overflowing = [test_account(u64::MAX, 0), test_account(1, 0)];
+	assert_eq!(
+		solvency_threshold(&schedule, &overflowing, now, 365 * 86_400),
+		Err(VestingError::Overflow.into())
+	);
+}
+
+#[test]
+fn test_sample_curve() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	let account = test_account(1_000, 400);
+	let start = schedule.start;
+
+	// Claims don't affect the curve
+	let curve = sample_curve(&schedule, &account, start - 86_400, start + 10 * 86_400, 12).unwrap();
+	assert_eq!(curve.len(), 12);
+	assert_eq!(curve[0], (start - 86_400, 0));
+	assert_eq!(curve[1], (start, 0));
+	assert_eq!(curve[4], (start + 3 * 86_400, 300));
+	assert_eq!(curve[11], (start + 10 * 86_400, 1_000));
+	for (at, vested) in curve {
+		assert_eq!(schedule.account_vested(&account, at), Ok(vested));
+	}
+
+	assert_eq!(
+		sample_curve(&schedule, &account, start, start + 86_400, 1),
+		Ok(vec![(start, 0)])
+	);
+	for (end, points) in [(start - 1, 2), (start, 0)] {
+		assert_eq!(
+			sample_curve(&schedule, &account, start, end, points),
+			Err(ProgramError::InvalidArgument)
+		);
+	}
+}
+
+#[test]
+fn test_amend_impact() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	let account = test_account(1_000, 100);
+	let start = schedule.start;
+	let now = start + 4 * 86_400;
+
+	// No change
+	assert_eq!(
+		amend_impact(&schedule, None, None, None, &account, now),
+		Ok(AmendImpact {
+			old_claimable: 300,
+			new_claimable: 300,
+			delta: 0,
+		})
+	);
+	// Halving the duration doubles emissions
+	assert_eq!(
+		amend_impact(&schedule, None, None, Some(5 * 86_400), &account, now),
+		Ok(AmendImpact {
+			old_claimable: 300,
+			new_claimable: 700,
+			delta: 400,
+		})
+	);
+	// Pushing the start back slows them
+	assert_eq!(
+		amend_impact(
+			&schedule,
+			Some(start + 2 * 86_400),
+			None,
+			None,
+			&account,
+			now
+		),
+		Ok(AmendImpact {
+			old_claimable: 300,
+			new_claimable: 100,
+			delta: -200,
+		})
+	);
+	// Moving to linear emissions with a shorter duration
+	assert_eq!(
+		amend_impact(
+			&schedule,
+			Some(start - 86_400),
+			Some(Frequency::Linear),
+			Some(20 * 86_400),
+			&account,
+			now + 43_200,
+		),
+		Ok(AmendImpact {
+			old_claimable: 300,
+			new_claimable: 175,
+			delta: -125,
+		})
+	);
+	// Amendments apply on top of one already in effect
+	schedule.pending_amendment = Some(PendingAmendment {
+		effective_at: start,
+		start: None,
+		frequency: None,
+		duration: Some(5 * 86_400),
+	});
+	assert_eq!(
+		amend_impact(&schedule, None, Some(Frequency::Week), None, &account, now),
+		Ok(AmendImpact {
+			old_claimable: 700,
+			new_claimable: 0,
+			delta: -700,
+		})
+	);
+}
+
+#[test]
+fn test_completing_within() {
+	let schedule = test_schedule();
+	let end = schedule.start + schedule.duration;
+	assert_eq!(schedule.completion_timestamp(), Some(end));
+
+	assert!(!completing_within(&schedule, end - 101, 100));
+	assert!(completing_within(&schedule, end - 100, 100));
+	assert!(completing_within(&schedule, end - 1, 100));
+	assert!(!completing_within(&schedule, end, 100));
+	assert!(!completing_within(&schedule, end + 1, 100));
+	assert!(!completing_within(&schedule, end - 1, 0));
+
+	let mut once = test_schedule();
+	once.frequency = Frequency::Once;
+	once.duration = 0;
+	assert_eq!(once.completion_timestamp(), Some(once.start));
+	assert!(completing_within(&once, once.start - 60, 60));
+	assert!(!completing_within(&once, once.start - 61, 60));
+	assert!(!completing_within(&once, once.start, 60));
+
+	let mut overflowing = test_schedule();
+	overflowing.duration = i64::MAX;
+	assert_eq!(overflowing.completion_timestamp(), None);
+	assert!(!completing_within(&overflowing, 0, i64::MAX));
+}
+
+#[test]
+fn test_summarize_portfolio() {
+	let now = 1_700_000_000;
+	let month = Frequency::Month.as_seconds().unwrap();
+	let pending = VestingSchedule {
+		start: now + 1,
+		..test_schedule()
+	};
+	let cliff = VestingSchedule {
+		start: now - month,
+		cliff: 2 * month,
+		..test_schedule()
+	};
+	let vesting = VestingSchedule {
+		start: now - 3 * month,
+		duration: 12 * month,
+		..test_schedule()
+	};
+	let completed = VestingSchedule {
+		start: now - 12 * month,
+		duration: 12 * month,
+		..test_schedule()
+	};
+	let schedules = [&pending, &cliff, &vesting, &completed];
+	let mut raw: Vec<(Pubkey, Vec<u8>)> = schedules
+		.iter()
+		.map(|schedule| (Pubkey::new_unique(), packed_schedule(schedule)))
+		.collect();
+	raw.insert(2, (Pubkey::new_unique(), vec![0; 3]));
+
+	let summaries = summarize_portfolio(&raw, now);
+	assert_eq!(summaries.len(), 4);
+	let expected = [
+		(Phase::NotStarted, 0),
+		(Phase::Cliff, 0),
+		(Phase::Vesting, 2_500),
+		(Phase::Completed, 10_000),
+	];
+	let keys = raw
+		.iter()
+		.filter(|(_, data)| data.len() > 3)
+		.map(|(key, _)| *key);
+	for (((summary, schedule), (phase, bps)), key) in
+		summaries.iter().zip(schedules).zip(expected).zip(keys)
+	{
+		assert_eq!(summary.pubkey, key);
+		assert_eq!(summary.mint, schedule.mint);
+		assert_eq!(summary.phase, phase);
+		assert_eq!(summary.vested_bps, bps);
+		assert_eq!(summary.completion_ts, schedule.completion_timestamp());
+	}
+
+	let once = VestingSchedule {
+		frequency: Frequency::Once,
+		start: now,
+		..test_schedule()
+	};
+	assert_eq!(once.phase(now - 1), Phase::NotStarted);
+	assert_eq!(once.phase(now), Phase::Completed);
+	assert_eq!(once.vested_bps(now), 10_000);
+}
+
+#[test]
+fn test_views_apply_pending_amendment() {
+	let now = 1_700_000_000;
+	let month = Frequency::Month.as_seconds().unwrap();
+	let schedule = VestingSchedule {
+		start: now - 3 * month,
+		duration: 12 * month,
+		pending_amendment: Some(PendingAmendment {
+			effective_at: now,
+			start: None,
+			frequency: None,
+			duration: Some(3 * month),
+		}),
+		..test_schedule()
+	};
+
+	// Before the amendment takes effect the original schedule is still vesting
+	assert_eq!(schedule.phase(now - 1), Phase::Vesting);
+	assert_eq!(schedule.vested_bps(now - 1), 1_666);
+	assert!(!completing_within(&schedule, now - 1, month));
+
+	// Once it does, the shortened schedule has completed
+	assert_eq!(schedule.phase(now), Phase::Completed);
+	assert_eq!(schedule.vested_bps(now), 10_000);
+	assert!(!completing_within(&schedule, now, month));
+	let summaries = summarize_portfolio(&[(Pubkey::new_unique(), packed_schedule(&schedule))], now);
+	assert_eq!(summaries[0].phase, Phase::Completed);
+	assert_eq!(summaries[0].completion_ts, Some(now));
+}
+
+#[test]
+fn test_calculate_net_after_fee_without_extension() {
+	let mut data = vec![0; Mint::LEN];
+	Mint {
+		is_initialized: true,
+		decimals: 6,
+		..Mint::default()
+	}
+	.pack_into_slice(&mut data);
+	assert_eq!(calculate_net_after_fee(&data, 0, 1_000), Ok(1_000));
+	assert_eq!(transfer_fee(&data, 0, 1_000), Ok(None));
+	assert!(calculate_net_after_fee(&[0; 10], 0, 1_000).is_err());
+}
+
+#[test]
+fn test_claimable_display() {
+	assert_eq!(claimable_display(1_234_567, 6, 2), 1_230_000);
+	assert_eq!(claimable_display(1_234_567, 6, 0), 1_000_000);
+	assert_eq!(claimable_display(1_234_567, 6, 5), 1_234_560);
+	assert_eq!(claimable_display(1_234_567, 6, 6), 1_234_567);
+	assert_eq!(claimable_display(1_234_567, 6, 9), 1_234_567);
+	assert_eq!(claimable_display(999_999, 6, 0), 0);
+	assert_eq!(claimable_display(42, 0, 0), 42);
+	assert_eq!(
+		claimable_display(1_999_999_999_999, 9, 3),
+		1_999_999_000_000
+	);
+	assert_eq!(
+		claimable_display(u64::MAX, 19, 0),
+		10_000_000_000_000_000_000
+	);
+	assert_eq!(claimable_display(u64::MAX, 20, 0), 0);
+	assert_eq!(claimable_display(u64::MAX, 255, 0), 0);
+}
+
+#[test]
+fn test_account_type_discriminant() {
+	let schedule_data = packed_schedule(&test_schedule());
+	let account_data = packed(&test_account(1_000, 0));
+	assert_eq!(
+		AccountType::of(&schedule_data),
+		Ok(AccountType::VestingSchedule)
+	);
+	assert_eq!(AccountType::of(&account_data), Ok(AccountType::Account));
+	assert_eq!(
+		AccountType::of(&[0; Account::LEN]),
+		Ok(AccountType::Uninitialized)
+	);
+	assert_eq!(AccountType::of(&[]), Err(ProgramError::InvalidAccountData));
+
+	// A schedule passed where an account is expected, and vice versa
+	assert_eq!(
+		Account::unpack(&schedule_data).unwrap_err(),
+		VestingError::WrongAccountType.into()
+	);
+	assert_eq!(
+		VestingSchedule::unpack(&account_data).unwrap_err(),
+		VestingError::WrongAccountType.into()
+	);
+	let mut mislabelled = account_data.clone();
+	mislabelled[0] = AccountType::VestingSchedule.into();
+	assert_eq!(
+		Account::unpack_from_slice(&mislabelled).unwrap_err(),
+		VestingError::WrongAccountType.into()
+	);
+
+	let mut unknown = account_data.clone();
+	unknown[0] = 3;
+	assert_eq!(
+		Account::unpack(&unknown).unwrap_err(),
+		ProgramError::InvalidAccountData
+	);
+	assert!(VestingSchedule::unpack(&schedule_data).is_ok());
+	assert!(Account::unpack(&account_data).is_ok());
+}
+
+#[test]
+fn test_claimable_amount() {
+	// 12 monthly periods of 100 tokens each
+	let mut schedule = test_schedule();
+	schedule.duration = 12 * 2_629_746;
+	let month = 2_629_746;
+	let start = schedule.start;
+	let end = start + schedule.duration;
+
+	assert_eq!(schedule.claimable_amount(start - 1, 1_200, 0), Ok(0));
+	assert_eq!(schedule.claimable_amount(start, 1_200, 0), Ok(0));
+	assert_eq!(
+		schedule.claimable_amount(start + month - 1, 1_200, 0),
+		Ok(0)
+	);
+	assert_eq!(schedule.claimable_amount(start + month, 1_200, 0), Ok(100));
+	assert_eq!(
+		schedule.claimable_amount(start + 6 * month, 1_200, 0),
+		Ok(600)
+	);
+	assert_eq!(
+		schedule.claimable_amount(start + 6 * month, 1_200, 200),
+		Ok(400)
+	);
+	assert_eq!(schedule.claimable_amount(end - 1, 1_200, 0), Ok(1_100));
+	assert_eq!(schedule.claimable_amount(end, 1_200, 0), Ok(1_200));
+	assert_eq!(schedule.claimable_amount(end + 1, 1_200, 1_000), Ok(200));
+	assert_eq!(schedule.claimable_amount(i64::MAX, 1_200, 1_200), Ok(0));
+
+	// Claimed beyond what has been emitted, e.g. after an allocation was lowered
+	assert_eq!(schedule.claimable_amount(start + month, 1_200, 500), Ok(0));
+	assert_eq!(schedule.claimable_amount(end, 1_200, 1_500), Ok(0));
+
+	// Each period's fractional share accrues rather than being held back until the end
+	assert_eq!(schedule.claimable_amount(start + month, 1_205, 0), Ok(100));
+	assert_eq!(
+		schedule.claimable_amount(start + 2 * month, 1_205, 0),
+		Ok(200)
+	);
+	assert_eq!(schedule.claimable_amount(end - 1, 1_205, 0), Ok(1_104));
+	assert_eq!(schedule.claimable_amount(end, 1_205, 0), Ok(1_205));
+}
+
+#[test]
+fn test_duration_of() {
+	assert_eq!(Frequency::Month.duration_of(36), Ok(36 * 2_629_746));
+	assert_eq!(Frequency::Day.duration_of(1), Ok(86_400));
+	assert_eq!(Frequency::Slot.duration_of(5), Ok(2));
+	for emissions in [1, 36, u32::MAX] {
+		let mut schedule = test_schedule();
+		schedule.frequency = Frequency::Hour;
+		schedule.duration = Frequency::Hour.duration_of(emissions).unwrap();
+		assert_eq!(schedule.total_periods(), Ok(u64::from(emissions)));
+	}
+
+	// Slots don't always add up to whole seconds
+	assert_eq!(
+		Frequency::Slot.duration_of(3),
+		Err(VestingError::InvalidSchedule.into())
+	);
+	for frequency in [Frequency::Once, Frequency::Linear, Frequency::Day] {
+		let emissions = match frequency {
+			Frequency::Day => 0,
+			_ => 1,
+		};
+		assert_eq!(
+			frequency.duration_of(emissions),
+			Err(VestingError::InvalidSchedule.into())
+		);
+	}
+	assert_eq!(
+		Frequency::Year.duration_of(u32::MAX),
+		Err(VestingError::Overflow.into())
+	);
+}
+
+#[test]
+fn test_total_periods() {
+	let mut schedule = test_schedule();
+	assert_eq!(schedule.total_periods(), Ok(12));
+
+	schedule.frequency = Frequency::Minute;
+	schedule.duration = 13;
+	assert_eq!(
+		schedule.total_periods(),
+		Err(VestingError::InvalidSchedule.into())
+	);
+
+	schedule.frequency = Frequency::Once;
+	assert_eq!(schedule.total_periods(), Ok(1));
+}
+
+#[test]
+fn test_elapsed_periods() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	let end = schedule.start + schedule.duration;
+	assert_eq!(schedule.elapsed_periods(schedule.start - 1), 0);
+	assert_eq!(schedule.elapsed_periods(schedule.start), 0);
+	assert_eq!(schedule.elapsed_periods(schedule.start + 86_400), 1);
+	assert_eq!(schedule.elapsed_periods(end - 1), 9);
+	assert_eq!(schedule.elapsed_periods(end), 10);
+	assert_eq!(schedule.elapsed_periods(end + 86_400), 10);
+	assert_eq!(schedule.elapsed_periods(i64::MAX), 10);
+
+	schedule.frequency = Frequency::Once;
+	assert_eq!(schedule.elapsed_periods(schedule.start - 1), 0);
+	assert_eq!(schedule.elapsed_periods(schedule.start), 1);
+	assert_eq!(schedule.elapsed_periods(end), 1);
+}
+
+#[test]
+fn test_claimable_amount_linear() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Linear;
+	let midpoint = schedule.start + schedule.duration / 2;
+	assert_eq!(
+		schedule.claimable_amount(midpoint, 1_000_000, 0),
+		Ok(500_000)
+	);
+	assert_eq!(
+		schedule.claimable_amount(midpoint, u64::MAX, 0),
+		Ok(u64::MAX / 2)
+	);
+	assert_eq!(
+		schedule.claimable_amount(midpoint, 1_000_000, 100_000),
+		Ok(400_000)
+	);
+
+	// Accrues every second rather than once per period
+	assert_eq!(
+		schedule.claimable_amount(schedule.start + 1, schedule.duration as u64, 0),
+		Ok(1)
+	);
+	assert_eq!(
+		schedule.claimable_amount(schedule.start, 1_000_000, 0),
+		Ok(0)
+	);
+	assert_eq!(
+		schedule.claimable_amount(schedule.start + schedule.duration, 1_000_000, 0),
+		Ok(1_000_000)
+	);
+
+	// Amounts smaller than the number of seconds still vest, just not every second
+	assert_eq!(schedule.validate_amount(1), Ok(()));
+	assert_eq!(schedule.total_periods(), Ok(schedule.duration as u64));
+}
+
+#[test]
+fn test_claimable_amount_once() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Once;
+	schedule.duration = 0;
+	assert_eq!(schedule.claimable_amount(schedule.start - 1, 500, 0), Ok(0));
+	assert_eq!(schedule.claimable_amount(schedule.start, 500, 0), Ok(500));
+	assert_eq!(
+		schedule.claimable_amount(schedule.start + 1, 500, 100),
+		Ok(400)
+	);
+}
+
+#[test]
+fn test_negative_duration() {
+	let error = Err(VestingError::InvalidDuration.into());
+	for frequency in [Frequency::Day, Frequency::Linear, Frequency::Second] {
+		let mut schedule = test_schedule();
+		schedule.frequency = frequency;
+		schedule.duration = -86_400;
+		let start = schedule.start;
+
+		// The end falls before the start, which mustn't release everything
+		for now in [start - 86_400 - 1, start, start + 1, i64::MAX] {
+			assert_eq!(schedule.claimable_amount(now, 1_000, 0), error);
+		}
+		assert_eq!(schedule.claimable_amount(i64::MAX, u64::MAX, 0), error);
+		assert_eq!(schedule.total_periods(), error);
+		assert_eq!(
+			schedule.validate_amount(1_000),
+			Err(VestingError::InvalidDuration.into())
+		);
+		assert_eq!(
+			schedule.account_claimable(&test_account(1_000, 0), start + 1),
+			error
+		);
+		let mut anchored = test_account(1_000, 100);
+		anchored.anchored_at = start;
+		anchored.vested_at_anchor = 100;
+		assert_eq!(schedule.account_vested(&anchored, start + 1), error);
+
+		schedule.duration = i64::MIN;
+		assert_eq!(schedule.claimable_amount(start, 1_000, 0), error);
+	}
+
+	// Once schedules don't use their duration
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Once;
+	schedule.duration = -1;
+	assert_eq!(schedule.claimable_amount(schedule.start, 500, 0), Ok(500));
+	assert_eq!(schedule.total_periods(), Ok(1));
+}
+
+#[test]
+fn test_claimable_amount_period_longer_than_duration() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Minute;
+	schedule.duration = 30;
+	assert_eq!(
+		schedule.claimable_amount(schedule.start + 29, 500, 0),
+		Ok(0)
+	);
+	assert_eq!(
+		schedule.claimable_amount(schedule.start + 30, 500, 0),
+		Ok(500)
+	);
+}
+
+#[test]
+fn test_claimable_amount_overflow() {
+	let mut schedule = test_schedule();
+	schedule.duration = i64::MAX;
+	assert_eq!(
+		schedule.claimable_amount(schedule.start, 500, 0),
+		Err(VestingError::Overflow.into())
+	);
+}
+
+#[test]
+fn test_claimable_amount_max_amount() {
+	// Billions of elapsed periods at the largest possible allocation
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Second;
+	schedule.duration = 100 * 31_556_952;
+	let elapsed = schedule.duration - 1;
+	let emitted = u128::from(u64::MAX) * elapsed as u128 / schedule.duration as u128;
+	assert_eq!(
+		schedule.claimable_amount(schedule.start + elapsed, u64::MAX, 0),
+		Ok(emitted as u64)
+	);
+	assert_eq!(
+		schedule.claimable_amount(schedule.start + schedule.duration, u64::MAX, 0),
+		Ok(u64::MAX)
+	);
+
+	// A duration whose length in milliseconds can't be counted in an `i64` is reported rather than
+	// panicking, while the schedule's end still fits
+	schedule.duration = i64::MAX / 2;
+	assert!(schedule.completion_timestamp().is_some());
+	assert_eq!(
+		schedule.claimable_amount(schedule.start + 1, u64::MAX, 0),
+		Err(VestingError::Overflow.into())
+	);
+}
+
+/// Small xorshift generator so the simulation is reproducible without extra dependencies
+struct XorShift(u64);
+
+impl XorShift {
+	fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	fn below(&mut self, bound: u64) -> u64 {
+		self.next() % bound
+	}
+}
+
+/// The formula documented at the top of `state.rs`, `c = a * ((tc - ts) / f) / (d/f) - (a - b)`,
+/// with the balance `b` written as `a - claimed`. Everything has vested once the schedule
+/// completes, and nothing is owed before it starts or once the emitted amount has been claimed.
+fn documented_claimable(a: u64, d: i64, f: i64, ts: i64, tc: i64, claimed: u64) -> u64 {
+	if tc < ts {
+		return 0;
+	}
+	let b = a - claimed;
+	let emitted = match tc < ts + d {
+		true => a as u128 * ((tc - ts) / f) as u128 / (d / f) as u128,
+		false => a as u128,
+	};
+	emitted.saturating_sub((a - b) as u128) as u64
+}
+
+#[test]
+fn test_claimable_amount_matches_documented_formula() {
+	let frequencies = [
+		Frequency::Slot,
+		Frequency::Second,
+		Frequency::Minute,
+		Frequency::Hour,
+		Frequency::Day,
+		Frequency::Week,
+		Frequency::Month,
+		Frequency::Quarter,
+		Frequency::Year,
+	];
+	let mut rng = XorShift(0x5eed_f00d_cafe_b0ba);
+	let mut schedule = test_schedule();
+	for _ in 0..10_000 {
+		schedule.frequency = frequencies[rng.below(frequencies.len() as u64) as usize];
+		let f = schedule.frequency.as_seconds().unwrap();
+		let periods = 1 + rng.below(1_000) as i64;
+		schedule.duration = periods * f + rng.below(f as u64) as i64;
+
+		// Periods are counted in milliseconds, which only matters for slots
+		let f = schedule.frequency.as_millis().unwrap();
+		schedule.start = 1_600_000_000 + rng.below(200_000_000) as i64;
+		let window = schedule.duration + schedule.duration / 2;
+		let now = schedule.start - schedule.duration / 4 + rng.below(window as u64) as i64;
+		let amount = rng.below(1_000_000_000_000);
+		let claimed = rng.below(amount + 1);
+
+		let expected = documented_claimable(
+			amount,
+			schedule.duration * 1_000,
+			f,
+			schedule.start * 1_000,
+			now * 1_000,
+			claimed,
+		);
+		assert_eq!(
+			schedule.claimable_amount(now, amount, claimed),
+			Ok(expected),
+			"{:?} duration {} start {} now {} amount {} claimed {}",
+			schedule.frequency,
+			schedule.duration,
+			schedule.start,
+			now,
+			amount,
+			claimed,
+		);
+	}
+}
+
+#[test]
+fn test_claimable_amount_cliff() {
+	let day = 86_400;
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * day;
+	schedule.cliff = 3 * day;
+
+	let cliff_end = schedule.start + schedule.cliff;
+	assert_eq!(schedule.claimable_amount(cliff_end - 1, 1_000, 0), Ok(0));
+	assert_eq!(schedule.claimable_amount(cliff_end, 1_000, 0), Ok(300));
+	assert_eq!(
+		schedule.claimable_amount(cliff_end + day, 1_000, 100),
+		Ok(300)
+	);
+
+	// A cliff spanning the whole duration releases everything at once
+	schedule.cliff = schedule.duration;
+	let end = schedule.start + schedule.duration;
+	assert_eq!(schedule.claimable_amount(end - 1, 1_000, 0), Ok(0));
+	assert_eq!(schedule.claimable_amount(end, 1_000, 0), Ok(1_000));
+}
+
+#[test]
+fn test_validate_cliff() {
+	let mut schedule = test_schedule();
+	assert_eq!(schedule.validate(), Ok(()));
+	schedule.cliff = schedule.duration;
+	assert_eq!(schedule.validate(), Ok(()));
+	schedule.cliff = schedule.duration + 1;
+	assert_eq!(
+		schedule.validate(),
+		Err(VestingError::InvalidSchedule.into())
+	);
+	schedule.cliff = -1;
+	assert_eq!(
+		schedule.validate(),
+		Err(VestingError::InvalidSchedule.into())
+	);
+}
+
+#[test]
+fn test_validate_duration() {
+	let mut schedule = test_schedule();
+	schedule.duration = 0;
+	assert_eq!(
+		schedule.validate(),
+		Err(VestingError::InvalidSchedule.into())
+	);
+	schedule.duration = -1;
+	assert_eq!(
+		schedule.validate(),
+		Err(VestingError::InvalidSchedule.into())
+	);
+
+	// Once schedules release everything at start and need no duration
+	schedule.frequency = Frequency::Once;
+	schedule.duration = 0;
+	assert_eq!(schedule.validate(), Ok(()));
+}
+
+#[test]
+fn test_validate_amount() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Second;
+	schedule.duration = 86_400;
+
+	// One token per second at the minimum, anything less emits nothing per period
+	assert_eq!(schedule.validate_amount(86_400), Ok(()));
+	assert_eq!(
+		schedule.validate_amount(86_399),
+		Err(VestingError::EmissionsRoundToZero.into())
+	);
+	assert_eq!(
+		schedule.validate_amount(0),
+		Err(VestingError::EmissionsRoundToZero.into())
+	);
+
+	// A coarser frequency lets the same amount vest
+	schedule.frequency = Frequency::Hour;
+	assert_eq!(schedule.validate_amount(86_399), Ok(()));
+
+	schedule.frequency = Frequency::Once;
+	assert_eq!(schedule.validate_amount(1), Ok(()));
+}
+
+#[test]
+fn test_pending_amendment_takes_effect() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	let effective_at = schedule.start + 2 * 86_400;
+	schedule.pending_amendment = Some(PendingAmendment {
+		effective_at,
+		start: None,
+		frequency: None,
+		duration: Some(5 * 86_400),
+	});
+	let account = test_account(1_000, 0);
+
+	let before = schedule.effective(effective_at - 1);
+	assert_eq!(before.duration, 10 * 86_400);
+	assert_eq!(before.pending_amendment, schedule.pending_amendment);
+	assert_eq!(
+		schedule.account_claimable(&account, effective_at - 1),
+		Ok(100)
+	);
+
+	let after = schedule.effective(effective_at);
+	assert_eq!(after.duration, 5 * 86_400);
+	assert_eq!(after.pending_amendment, None);
+	assert_eq!(schedule.account_claimable(&account, effective_at), Ok(400));
+}
+
+#[test]
+fn test_quantize_start() {
+	let hour = 3_600;
+	assert_eq!(quantize_start(1_700_001_234, hour), Ok(1_699_999_200));
+	assert_eq!(quantize_start(1_699_999_200, hour), Ok(1_699_999_200));
+	assert_eq!(quantize_start(-1, hour), Ok(-hour));
+	assert_eq!(
+		quantize_start(1_700_001_234, 0),
+		Err(VestingError::InvalidSchedule.into())
+	);
+	assert_eq!(
+		quantize_start(i64::MIN, hour),
+		Err(VestingError::Overflow.into())
+	);
+
+	// Periods count from the quantized start, the first emission arrives sooner
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Hour;
+	schedule.duration = 10 * hour;
+	schedule.start = quantize_start(1_700_001_234, hour).unwrap();
+	assert_eq!(schedule.claimable_amount(1_700_002_800, 1_000, 0), Ok(100));
+	schedule.start = 1_700_001_234;
+	assert_eq!(schedule.claimable_amount(1_700_002_800, 1_000, 0), Ok(0));
+}
+
+#[test]
+fn test_verify_forfeit() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	let account = test_account(1_000, 200);
+	let now = schedule.start + 5 * 86_400;
+
+	assert_eq!(schedule.verify_forfeit(&account, now, 300), Ok(()));
+	for acknowledged_forfeit in [0, 299, 301, 1_000] {
+		assert_eq!(
+			schedule.verify_forfeit(&account, now, acknowledged_forfeit),
+			Err(VestingError::ForfeitAcknowledgementMismatch.into())
+		);
+	}
+
+	// Nothing is forfeit before the schedule starts
+	assert_eq!(
+		schedule.verify_forfeit(&account, schedule.start - 1, 0),
+		Ok(())
+	);
+}
+
+#[test]
+fn test_anchor_to_creation() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	let day = 86_400;
+
+	let mut early = test_account(1_000, 0);
+	early.created_at = schedule.start + 2 * day;
+	let mut late = test_account(1_000, 0);
+	late.created_at = schedule.start + 5 * day;
+
+	// Without anchoring both accounts vest from the schedule start
+	let now = schedule.start + 6 * day;
+	assert_eq!(schedule.account_start(&early), schedule.start);
+	assert_eq!(schedule.account_claimable(&early, now), Ok(600));
+	assert_eq!(schedule.account_claimable(&late, now), Ok(600));
+
+	schedule.anchor_to_creation = true;
+	assert_eq!(schedule.account_start(&early), early.created_at);
+	assert_eq!(schedule.account_start(&late), late.created_at);
+	assert_eq!(schedule.account_claimable(&early, now), Ok(400));
+	assert_eq!(schedule.account_claimable(&late, now), Ok(100));
+	assert_eq!(
+		schedule.account_claimable(&late, early.created_at + 10 * day),
+		Ok(700)
+	);
+	assert_eq!(
+		schedule.account_claimable(&early, early.created_at + 10 * day),
+		Ok(1_000)
+	);
+	assert_eq!(
+		schedule.account_claimable(&late, late.created_at + 10 * day),
+		Ok(1_000)
+	);
+	assert_eq!(
+		schedule.account_claimable(&late, late.created_at - 1),
+		Ok(0)
+	);
+}
+
+#[test]
+fn test_is_fully_vested() {
+	let mut schedule = test_schedule();
+	let end = schedule.completion_timestamp().unwrap();
+	assert!(!schedule.is_fully_vested(end - 1));
+	assert!(schedule.is_fully_vested(end));
+
+	// Accounts on schedules anchored to creation vest from when they're created
+	schedule.anchor_to_creation = true;
+	assert!(!schedule.is_fully_vested(end));
+}
+
+#[test]
+fn test_immediate_release() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	schedule.cliff = 3 * 86_400;
+	let day = 86_400;
+	let start = schedule.start;
+
+	// 20% is released at the start regardless of the cliff, the rest vests over the schedule
+	let mut account = test_account(1_000, 0);
+	account.immediate_bps = 2_000;
+	assert_eq!(account.immediate_amount(), 200);
+	assert_eq!(schedule.account_claimable(&account, start - 1), Ok(0));
+	assert_eq!(schedule.account_claimable(&account, start), Ok(200));
+	assert_eq!(
+		schedule.account_claimable(&account, start + 2 * day),
+		Ok(200)
+	);
+	assert_eq!(
+		schedule.account_claimable(&account, start + 5 * day),
+		Ok(600)
+	);
+	assert_eq!(
+		schedule.account_claimable(&account, start + 10 * day),
+		Ok(1_000)
+	);
+
+	account.claimed = 200;
+	assert_eq!(schedule.account_claimable(&account, start), Ok(0));
+	assert_eq!(
+		schedule.account_claimable(&account, start + 5 * day),
+		Ok(400)
+	);
+
+	account.claimed = 0;
+	account.immediate_bps = MAX_BPS;
+	assert_eq!(schedule.account_claimable(&account, start), Ok(1_000));
+	account.immediate_bps = 0;
+	assert_eq!(schedule.account_claimable(&account, start), Ok(0));
+}
+
+#[test]
+fn test_cliff_lump() {
+	let day = 86_400;
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * day;
+	schedule.cliff = 3 * day;
+	schedule.cliff_bps = 2_500;
+	let cliff_end = schedule.start + schedule.cliff;
+	let end = schedule.start + schedule.duration;
+
+	// A quarter is released at the cliff, the rest vests daily over the 7 days after it
+	let mut account = test_account(1_000, 0);
+	assert_eq!(schedule.cliff_amount(&account), 250);
+	assert_eq!(schedule.account_claimable(&account, cliff_end - 1), Ok(0));
+	assert_eq!(schedule.account_claimable(&account, cliff_end), Ok(250));
+	assert_eq!(
+		schedule.account_claimable(&account, cliff_end + day - 1),
+		Ok(250)
+	);
+	assert_eq!(
+		schedule.account_claimable(&account, cliff_end + day),
+		Ok(357)
+	);
+	assert_eq!(
+		schedule.account_accrual(&account, cliff_end + day),
+		Ok((1, 7))
+	);
+	assert_eq!(schedule.account_claimable(&account, end - 1), Ok(892));
+	assert_eq!(schedule.account_claimable(&account, end), Ok(1_000));
+	assert_eq!(schedule.vested_bps(cliff_end - 1), 0);
+	assert_eq!(schedule.vested_bps(cliff_end), 2_500);
+	assert_eq!(schedule.vested_bps(cliff_end + day), 3_571);
+
+	// The immediate share is released at the start on top of the cliff lump
+	account.immediate_bps = 1_000;
+	assert_eq!(
+		schedule.account_claimable(&account, schedule.start),
+		Ok(100)
+	);
+	assert_eq!(schedule.account_claimable(&account, cliff_end), Ok(350));
+	assert_eq!(
+		schedule.account_claimable(&account, cliff_end + day),
+		Ok(442)
+	);
+	assert_eq!(schedule.account_claimable(&account, end), Ok(1_000));
+
+	// Without a lump everything accrued since the start is released at the cliff
+	account.immediate_bps = 0;
+	schedule.cliff_bps = 0;
+	assert_eq!(schedule.account_claimable(&account, cliff_end), Ok(300));
+}
+
+#[test]
+fn test_validate_cliff_bps() {
+	let mut schedule = test_schedule();
+	schedule.cliff_bps = MAX_BPS;
+	assert_eq!(schedule.validate(), Ok(()));
+	schedule.cliff_bps = MAX_BPS + 1;
+	assert_eq!(
+		schedule.validate(),
+		Err(VestingError::InvalidSchedule.into())
+	);
+}
+
+#[test]
+fn test_vested_from_anchor() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Day;
+	schedule.duration = 10 * 86_400;
+	let day = 86_400;
+	let start = schedule.start;
+
+	// 400 had vested when the amount was raised to 2_000, the other 1_600 vests over the
+	// remaining 6 days rather than as if the account had always held 2_000
+	let mut account = test_account(2_000, 0);
+	account.anchored_at = start + 4 * day;
+	account.vested_at_anchor = 400;
+	assert_eq!(schedule.account_vested(&account, start + 4 * day), Ok(400));
+	assert_eq!(
+		schedule.account_vested(&account, start + 7 * day),
+		Ok(1_200)
+	);
+	assert_eq!(
+		schedule.account_vested(&account, start + 10 * day),
+		Ok(2_000)
+	);
+	account.claimed = 400;
+	assert_eq!(
+		schedule.account_claimable(&account, start + 7 * day),
+		Ok(800)
+	);
+
+	// Lowering the amount keeps what had already vested
+	let mut account = test_account(500, 0);
+	account.anchored_at = start + 4 * day;
+	account.vested_at_anchor = 400;
+	assert_eq!(schedule.account_vested(&account, start + 4 * day), Ok(400));
+	assert_eq!(schedule.account_vested(&account, start + 7 * day), Ok(450));
+	assert_eq!(schedule.account_vested(&account, start + 10 * day), Ok(500));
+
+	// Or all of it if the amount fell below what had vested
+	account.amount = 300;
+	assert_eq!(schedule.account_vested(&account, start + 7 * day), Ok(300));
+
+	// Linear schedules continue from the anchor by the second
+	schedule.frequency = Frequency::Linear;
+	let mut account = test_account(2_000, 0);
+	account.anchored_at = start + 5 * day;
+	account.vested_at_anchor = 500;
+	assert_eq!(schedule.account_vested(&account, start + 5 * day), Ok(500));
+	assert_eq!(
+		schedule.account_vested(&account, start + 7 * day + day / 2),
+		Ok(1_250)
+	);
+}
+
+#[test]
+fn test_fallback_active() {
+	let mut account = test_account(1_000, 0);
+	let created_at = account.created_at;
+	account.inactivity_seconds = 100;
+	assert!(!account.fallback_active(created_at + 1_000));
+
+	// Measured from creation until the owner first claims
+	account.fallback_owner = COption::Some(Pubkey::new_unique());
+	assert!(!account.fallback_active(created_at + 99));
+	assert!(account.fallback_active(created_at + 100));
+	account.last_claim = created_at + 50;
+	assert!(!account.fallback_active(created_at + 149));
+	assert!(account.fallback_active(created_at + 150));
+	assert!(account.fallback_active(i64::MAX));
+
+	account.inactivity_seconds = 0;
+	assert!(!account.fallback_active(i64::MAX));
+	account.inactivity_seconds = i64::MAX;
+	assert!(!account.fallback_active(created_at + 1_000));
+}
+
+#[test]
+fn test_claim_window() {
+	let mut account = test_account(1_000, 0);
+	assert_eq!(account.current_window(100), None);
+	assert_eq!(account.window_remaining(100), u64::MAX);
+	account.record_window_claim(100, 50);
+	assert_eq!(account.claimed_in_window, 0);
+
+	account.max_claim_per_window = 100;
+	account.window_seconds = 60;
+	assert_eq!(account.current_window(119), Some(60));
+	assert_eq!(account.current_window(-1), Some(-60));
+	assert_eq!(account.window_remaining(100), 100);
+	account.record_window_claim(100, 70);
+	assert_eq!(account.window_remaining(119), 30);
+	account.record_window_claim(119, 30);
+	assert_eq!(account.window_remaining(119), 0);
+
+	// A new window starts afresh
+	assert_eq!(account.window_remaining(120), 100);
+	account.record_window_claim(120, 10);
+	assert_eq!((account.window_start, account.claimed_in_window), (120, 10));
+}
+
+#[test]
+fn test_created_at_round_trip() {
+	let mut account = test_account(1_000, 10);
+	account.created_at = -42;
+	let unpacked = Account::unpack(&packed(&account)).unwrap();
+	assert_eq!(unpacked.created_at, -42);
+
+	let mut schedule = test_schedule();
+	schedule.anchor_to_creation = true;
+	let unpacked = VestingSchedule::unpack(&packed_schedule(&schedule)).unwrap();
+	assert!(unpacked.anchor_to_creation);
+}
+
+#[test]
+fn test_allocate_bps() {
+	let mut schedule = test_schedule();
+	assert_eq!(
+		schedule.allocate_bps(1),
+		Err(VestingError::AllocationExceedsPool.into())
+	);
+
+	schedule.pool_total = 1_001;
+	assert_eq!(schedule.allocate_bps(5_000), Ok(500));
+	assert_eq!(schedule.allocate_bps(3_333), Ok(333));
+	assert_eq!(schedule.allocated_bps, 8_333);
+	assert_eq!(
+		schedule.allocate_bps(1_668),
+		Err(VestingError::AllocationExceedsPool.into())
+	);
+	assert_eq!(schedule.allocated_bps, 8_333);
+	assert_eq!(schedule.allocate_bps(1_667), Ok(166));
+	assert_eq!(schedule.allocated_bps, MAX_BPS);
+
+	schedule.pool_total = u64::MAX;
+	schedule.allocated_bps = 0;
+	assert_eq!(schedule.allocate_bps(MAX_BPS), Ok(u64::MAX));
+}
+
+#[test]
+fn test_identifier_round_trip() {
+	let mut schedule = test_schedule();
+	schedule.identifier = hash_identifier("seed-round");
+	let data = packed_schedule(&schedule);
+	assert_eq!(VestingSchedule::LEN, 383);
+	assert_eq!(data.len(), VestingSchedule::LEN);
+
+	let unpacked = VestingSchedule::unpack(&data).unwrap();
+	assert_eq!(unpacked.identifier, hash_identifier("seed-round"));
+	assert_eq!(unpacked.vault, schedule.vault);
+	assert_eq!(unpacked.duration, schedule.duration);
+}
+
+#[test]
+fn test_frequency_as_seconds() {
+	assert_eq!(Frequency::Once.as_seconds(), None);
+	assert_eq!(Frequency::Slot.as_seconds(), Some(1));
+	assert_eq!(Frequency::Second.as_seconds(), Some(1));
+	assert_eq!(Frequency::Minute.as_seconds(), Some(60));
+	assert_eq!(Frequency::Hour.as_seconds(), Some(3_600));
+	assert_eq!(Frequency::Day.as_seconds(), Some(86_400));
+	assert_eq!(Frequency::Week.as_seconds(), Some(604_800));
+	assert_eq!(Frequency::Month.as_seconds(), Some(2_629_746));
+	assert_eq!(Frequency::Quarter.as_seconds(), Some(3 * 2_629_746));
+	assert_eq!(Frequency::Year.as_seconds(), Some(31_556_952));
+	assert_eq!(Frequency::Year.as_seconds(), Some(12 * 2_629_746));
+	assert_eq!(Frequency::Linear.as_seconds(), Some(1));
+
+	assert_eq!(Frequency::Once.as_millis(), None);
+	assert_eq!(Frequency::Slot.as_millis(), Some(SLOT_MILLISECONDS));
+	assert_eq!(Frequency::Second.as_millis(), Some(1_000));
+	assert_eq!(Frequency::Year.as_millis(), Some(31_556_952_000));
+}
+
+#[test]
+fn test_claimable_amount_slot() {
+	let mut schedule = test_schedule();
+	schedule.frequency = Frequency::Slot;
+	schedule.duration = 10;
+	let start = schedule.start;
+	assert_eq!(SLOT_MILLISECONDS, 400);
+	assert_eq!(schedule.total_periods(), Ok(25));
+
+	// Slots are released two or three at a time as the one-second clock ticks over
+	assert_eq!(schedule.claimable_amount(start + 1, 2_500, 0), Ok(200));
+	assert_eq!(schedule.claimable_amount(start + 2, 2_500, 0), Ok(500));
+	assert_eq!(schedule.claimable_amount(start + 3, 2_500, 0), Ok(700));
+	assert_eq!(schedule.claimable_amount(start + 10, 2_500, 0), Ok(2_500));
+
+	// Slot durations must be a whole number of slots
+	schedule.duration = 3;
+	assert_eq!(
+		schedule.total_periods(),
+		Err(VestingError::InvalidSchedule.into())
+	);
+}